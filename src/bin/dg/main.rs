@@ -0,0 +1,64 @@
+/*
+    Explicit returns are the crate conventions.
+*/
+#![allow(clippy::needless_return)]
+
+mod problem;
+
+use problem::ProblemFile;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+/**
+ * Solves the problem described by the TOML file given as the only argument:
+ *  dg problem.toml
+ */
+fn main() {
+    let arguments: Vec<String> = env::args().collect();
+    if arguments.len() != 2 {
+        eprintln!("Usage: dg <problem.toml>");
+        process::exit(2);
+    }
+
+    if let Err(error) = run(Path::new(&arguments[1])) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+fn run(path: &Path) -> Result<(), String> {
+    let text =
+        fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let problem = ProblemFile::parse(&text)?;
+    let directory = path.parent().unwrap_or_else(|| Path::new("."));
+
+    let (_, summary) = problem.run(directory)?;
+    println!("Degrees of freedom: {}", summary.dofs);
+    for (step, report) in summary.reports.iter().enumerate() {
+        println!(
+            "Solve {}: {} after {} newton iterations, residual {:e}",
+            step + 1,
+            if report.converged {
+                "converged"
+            } else {
+                "not converged"
+            },
+            report.iterations.len(),
+            report
+                .iterations
+                .last()
+                .map_or(report.initial_residual, |iteration| iteration.residual)
+        );
+    }
+    for output in summary.outputs.iter() {
+        println!("Written {}", output.display());
+    }
+
+    if summary.reports.iter().any(|report| !report.converged) {
+        return Err("Newton iterations did not converge".to_string());
+    }
+    return Ok(());
+}
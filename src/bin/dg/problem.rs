@@ -0,0 +1,541 @@
+use nalgebra::DMatrix;
+use serde::Deserialize;
+
+use discontinuous_galerkin::common::{edge::Edge, point::Point};
+use discontinuous_galerkin::triangle::{
+    io::{csv, serialization::DomainArchive, vtu},
+    mesh,
+    post_processing::{dg_solution::DgSolution, projection},
+    solvers::newton,
+    system_builder::{
+        builder,
+        domain::Domain,
+        heat_transfer_problem::{DynamicState, HeatProblem},
+        nonlinear_diffusion_problem::Conductivity,
+    },
+};
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/* Boundary edges of the mesh by tag name */
+type Tags = HashMap<String, Vec<Rc<Edge>>>;
+
+/**
+ * Problem description read from TOML:
+ *
+ *  [mesh]
+ *  generator = "rectangle"      # rectangle, disc, cylinder or file
+ *  lower = [0.0, 0.0]
+ *  upper = [1.0, 1.0]
+ *  cells = [8, 8]
+ *  degree = 1                   # modal degree, nodal basis if missing
+ *
+ *  [pde]
+ *  kind = "poisson"             # or heat, with conductivity, density and capacity
+ *  source = 1.0
+ *
+ *  [boundary.left]              # one table per tag of the mesh
+ *  type = "dirichlet"           # dirichlet, neumann or radiation (heat only)
+ *  value = 0.0
+ *
+ *  [solver]
+ *  penalty = 10.0
+ *
+ *  [time]                       # heat only: backward euler steps from the initial value
+ *  dt = 0.1
+ *  steps = 10
+ *
+ *  [output]
+ *  vtu = "solution.vtu"
+ *  csv = "solution.csv"
+ *
+ * Relative paths are taken from the directory of the problem file
+ */
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ProblemFile {
+    pub mesh: MeshSection,
+    pub pde: PdeSection,
+    #[serde(default)]
+    pub boundary: BTreeMap<String, BoundarySection>,
+    #[serde(default)]
+    pub solver: SolverSection,
+    pub time: Option<TimeSection>,
+    #[serde(default)]
+    pub output: OutputSection,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "generator", rename_all = "lowercase", deny_unknown_fields)]
+pub enum MeshSection {
+    /* Tags: left, right, bottom, top */
+    Rectangle {
+        lower: (f64, f64),
+        upper: (f64, f64),
+        cells: (usize, usize),
+        degree: Option<usize>,
+    },
+    /* Tag: boundary */
+    Disc {
+        center: (f64, f64),
+        radius: f64,
+        cells: usize,
+        degree: Option<usize>,
+    },
+    /* Tags: cylinder, left, right, bottom, top */
+    Cylinder {
+        center: (f64, f64),
+        radius: f64,
+        half_width: f64,
+        sectors: usize,
+        layers: usize,
+        degree: Option<usize>,
+    },
+    /* JSON domain archive, with its own tags */
+    File {
+        path: PathBuf,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum PdeSection {
+    /* - laplacian(u) = source */
+    Poisson {
+        #[serde(default)]
+        source: f64,
+    },
+    /* density * capacity * du/dt - div(conductivity grad(u)) = source */
+    Heat {
+        conductivity: f64,
+        #[serde(default = "unit")]
+        density: f64,
+        #[serde(default = "unit")]
+        capacity: f64,
+        #[serde(default)]
+        source: f64,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum BoundarySection {
+    Dirichlet { value: f64 },
+    /* Outward flux: du/dn for poisson, conductivity du/dn for heat */
+    Neumann { value: f64 },
+    Radiation { emissivity: f64, ambient: f64 },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct SolverSection {
+    pub penalty: f64,
+    /* Newton settings of the heat problem */
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for SolverSection {
+    fn default() -> Self {
+        let options = newton::Options::default();
+        SolverSection {
+            penalty: 10.0,
+            tolerance: options.absolute,
+            max_iterations: options.max_iterations,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TimeSection {
+    pub dt: f64,
+    pub steps: usize,
+    #[serde(default)]
+    pub initial: f64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputSection {
+    pub vtu: Option<PathBuf>,
+    pub csv: Option<PathBuf>,
+    pub field: String,
+}
+
+impl Default for OutputSection {
+    fn default() -> Self {
+        OutputSection {
+            vtu: None,
+            csv: None,
+            field: "u".to_string(),
+        }
+    }
+}
+
+fn unit() -> f64 {
+    return 1.0;
+}
+
+/**
+ * Outcome of a run: the domain size, the newton reports of the heat problem
+ * and the files written
+ */
+pub struct Summary {
+    pub dofs: usize,
+    pub reports: Vec<newton::Report>,
+    pub outputs: Vec<PathBuf>,
+}
+
+impl ProblemFile {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        return toml::from_str(text).map_err(|error| format!("Invalid problem file: {}", error));
+    }
+
+    /**
+     * Builds the domain with its boundary conditions, solves and writes the outputs
+     */
+    pub fn run(&self, directory: &Path) -> Result<(DMatrix<f64>, Summary), String> {
+        let (mut domain, tags) = self.mesh(directory)?;
+        self.constrain(&mut domain, &tags)?;
+
+        let sigma = self.solver.penalty;
+        let (solution, reports) = match self.pde {
+            PdeSection::Poisson { source } => {
+                if self.time.is_some() {
+                    return Err("Time stepping is only available to the heat problem".to_string());
+                }
+                let (system_matrix, extern_matrix) =
+                    builder::build_with_source(sigma, &|_| source, &domain);
+                let solution = system_matrix
+                    .lu()
+                    .solve(&extern_matrix)
+                    .ok_or_else(|| "Singular system, check the boundary conditions".to_string())?;
+                (solution, Vec::new())
+            }
+            PdeSection::Heat {
+                conductivity,
+                density,
+                capacity,
+                source,
+            } => {
+                let state = match &self.time {
+                    Some(time) => DynamicState::Transient {
+                        dt: time.dt,
+                        steps: time.steps,
+                    },
+                    None => DynamicState::SteadyState,
+                };
+                let initial = self.time.as_ref().map_or(0.0, |time| time.initial);
+                let field = |_: &Point| source;
+                let mut problem = HeatProblem::new(
+                    Conductivity::Constant(conductivity),
+                    density,
+                    capacity,
+                    sigma,
+                    state,
+                )
+                .with_external_field(&field);
+                problem.options.absolute = self.solver.tolerance;
+                problem.options.max_iterations = self.solver.max_iterations;
+                problem.solve(&domain, &projection::l2_projection(&domain, &|_| initial))
+            }
+        };
+
+        let mut outputs = Vec::new();
+        let dg_solution = DgSolution::new(&domain, &solution);
+        if let Some(path) = &self.output.vtu {
+            let path = directory.join(path);
+            let mut file = fs::File::create(&path).map_err(|error| io_error(&path, error))?;
+            vtu::write(&mut file, &dg_solution, &self.output.field)
+                .map_err(|error| io_error(&path, error))?;
+            outputs.push(path);
+        }
+        if let Some(path) = &self.output.csv {
+            let path = directory.join(path);
+            let mut file = fs::File::create(&path).map_err(|error| io_error(&path, error))?;
+            csv::write(&mut file, &dg_solution, &self.output.field)
+                .map_err(|error| io_error(&path, error))?;
+            outputs.push(path);
+        }
+
+        let summary = Summary {
+            dofs: domain.size(),
+            reports,
+            outputs,
+        };
+        return Ok((solution, summary));
+    }
+
+    /**
+     * Domain of the mesh section, with its boundary edges by tag
+     */
+    fn mesh(&self, directory: &Path) -> Result<(Domain, Tags), String> {
+        let new_domain = |degree: &Option<usize>| match degree {
+            Some(degree) => Domain::new_modal(*degree).map_err(|error| error.to_string()),
+            None => Ok(Domain::new_empty()),
+        };
+        let mut tags: Tags = HashMap::new();
+        match &self.mesh {
+            MeshSection::Rectangle {
+                lower,
+                upper,
+                cells,
+                degree,
+            } => {
+                let mut domain = new_domain(degree)?;
+                let boundary = mesh::rectangle(*lower, *upper, cells.0, cells.1, &mut domain);
+                tags.insert("left".to_string(), boundary.left);
+                tags.insert("right".to_string(), boundary.right);
+                tags.insert("bottom".to_string(), boundary.bottom);
+                tags.insert("top".to_string(), boundary.top);
+                return Ok((domain, tags));
+            }
+            MeshSection::Disc {
+                center,
+                radius,
+                cells,
+                degree,
+            } => {
+                let mut domain = new_domain(degree)?;
+                let boundary = mesh::disc(*center, *radius, *cells, &mut domain);
+                tags.insert("boundary".to_string(), boundary);
+                return Ok((domain, tags));
+            }
+            MeshSection::Cylinder {
+                center,
+                radius,
+                half_width,
+                sectors,
+                layers,
+                degree,
+            } => {
+                let mut domain = new_domain(degree)?;
+                let boundary = mesh::cylinder(
+                    *center,
+                    *radius,
+                    *half_width,
+                    *sectors,
+                    *layers,
+                    &mut domain,
+                );
+                tags.insert("cylinder".to_string(), boundary.cylinder);
+                tags.insert("left".to_string(), boundary.outer.left);
+                tags.insert("right".to_string(), boundary.outer.right);
+                tags.insert("bottom".to_string(), boundary.outer.bottom);
+                tags.insert("top".to_string(), boundary.outer.top);
+                return Ok((domain, tags));
+            }
+            MeshSection::File { path } => {
+                let path = directory.join(path);
+                let text = fs::read_to_string(&path).map_err(|error| io_error(&path, error))?;
+                let archive = DomainArchive::from_json(&text)
+                    .map_err(|_| format!("Invalid domain archive {}", path.display()))?;
+                let domain = archive
+                    .domain()
+                    .map_err(|_| format!("Inconsistent domain archive {}", path.display()))?;
+                for name in archive.tags.keys() {
+                    if let Some(edges) = archive.tag(&domain, name) {
+                        tags.insert(name.clone(), edges);
+                    }
+                }
+                return Ok((domain, tags));
+            }
+        }
+    }
+
+    /**
+     * Inserts the boundary conditions of each tag into the domain
+     */
+    fn constrain(&self, domain: &mut Domain, tags: &Tags) -> Result<(), String> {
+        for (name, condition) in self.boundary.iter() {
+            let edges = tags
+                .get(name)
+                .ok_or_else(|| format!("Unknown boundary tag {}", name))?;
+            for edge in edges.iter() {
+                if !domain.adjacency.contains_key(edge) {
+                    return Err(format!("Boundary tag {} has edges outside the mesh", name));
+                }
+                match condition {
+                    BoundarySection::Dirichlet { value } => {
+                        domain.insert_dirichlet_constraint(edge, vec![*value, *value])
+                    }
+                    BoundarySection::Neumann { value } => {
+                        domain.insert_neumann_constraint(edge, vec![*value, *value])
+                    }
+                    BoundarySection::Radiation {
+                        emissivity,
+                        ambient,
+                    } => {
+                        if let PdeSection::Poisson { .. } = self.pde {
+                            return Err(format!(
+                                "Radiation at {} is only available to the heat problem",
+                                name
+                            ));
+                        }
+                        domain.insert_radiation_constraint(
+                            edge,
+                            *emissivity,
+                            vec![*ambient, *ambient],
+                        )
+                    }
+                }
+            }
+        }
+        return Ok(());
+    }
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> String {
+    return format!("{}: {}", path.display(), error);
+}
+
+#[cfg(test)]
+mod problem_file {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /*
+            Poisson over [0,2]x[0,1] with u = 0 at the left and unit outward flux at the right:
+            the solution u = x is written to both outputs
+        */
+        let directory = std::env::temp_dir().join("dg_problem_file_sample_1");
+        fs::create_dir_all(&directory).unwrap();
+        let problem = ProblemFile::parse(
+            r#"
+            [mesh]
+            generator = "rectangle"
+            lower = [0.0, 0.0]
+            upper = [2.0, 1.0]
+            cells = [4, 2]
+            degree = 2
+
+            [pde]
+            kind = "poisson"
+
+            [boundary.left]
+            type = "dirichlet"
+            value = 0.0
+
+            [boundary.right]
+            type = "neumann"
+            value = 1.0
+
+            [output]
+            vtu = "solution.vtu"
+            csv = "solution.csv"
+            "#,
+        )
+        .unwrap();
+        let (solution, summary) = problem.run(&directory).unwrap();
+        assert_eq!(summary.dofs, 16 * 6);
+        assert_eq!(summary.outputs.len(), 2);
+
+        let text = fs::read_to_string(directory.join("solution.csv")).unwrap();
+        assert_eq!(text.lines().count(), 1 + 3 * 16);
+        for line in text.lines().skip(1) {
+            let row: Vec<f64> = line
+                .split(',')
+                .map(|value| value.parse().unwrap())
+                .collect();
+            assert!((row[3] - row[1]).abs() < 1.0E-10);
+        }
+        assert!(fs::read_to_string(directory.join("solution.vtu"))
+            .unwrap()
+            .contains("Name=\"u\""));
+        assert_eq!(solution.nrows(), summary.dofs);
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Transient heat over a disc held at zero, from a unit temperature:
+            the temperature decays and never exceeds the initial value
+        */
+        let problem = ProblemFile::parse(
+            r#"
+            [mesh]
+            generator = "disc"
+            center = [0.0, 0.0]
+            radius = 1.0
+            cells = 2
+
+            [pde]
+            kind = "heat"
+            conductivity = 1.0
+
+            [boundary.boundary]
+            type = "dirichlet"
+            value = 0.0
+
+            [time]
+            dt = 0.05
+            steps = 4
+            initial = 1.0
+            "#,
+        )
+        .unwrap();
+        let (solution, summary) = problem.run(&std::env::temp_dir()).unwrap();
+        assert_eq!(summary.reports.len(), 4);
+        assert!(summary.reports.iter().all(|report| report.converged));
+        assert!(summary.outputs.is_empty());
+        assert!(solution.max() < 1.0 && solution.max() > 0.0);
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Unknown fields, unknown tags and radiation in poisson are reported */
+        assert!(
+            ProblemFile::parse("[mesh]\ngenerator = \"sphere\"\n[pde]\nkind = \"poisson\"")
+                .is_err()
+        );
+        assert!(ProblemFile::parse(
+            "[mesh]\ngenerator = \"disc\"\ncenter = [0, 0]\nradius = 1.0\ncells = 1\n[pde]\nkind = \"poisson\"\nextra = 1"
+        )
+        .is_err());
+
+        let problem = ProblemFile::parse(
+            r#"
+            [mesh]
+            generator = "rectangle"
+            lower = [0.0, 0.0]
+            upper = [1.0, 1.0]
+            cells = [1, 1]
+
+            [pde]
+            kind = "poisson"
+
+            [boundary.inlet]
+            type = "dirichlet"
+            value = 1.0
+            "#,
+        )
+        .unwrap();
+        let error = problem.run(&std::env::temp_dir()).err().unwrap();
+        assert!(error.contains("inlet"));
+
+        let problem = ProblemFile::parse(
+            r#"
+            [mesh]
+            generator = "rectangle"
+            lower = [0.0, 0.0]
+            upper = [1.0, 1.0]
+            cells = [1, 1]
+
+            [pde]
+            kind = "poisson"
+
+            [boundary.top]
+            type = "radiation"
+            emissivity = 0.5
+            ambient = 300.0
+            "#,
+        )
+        .unwrap();
+        assert!(problem.run(&std::env::temp_dir()).is_err());
+    }
+}
@@ -3,7 +3,7 @@ use std::hash::Hash;
 use std::rc::Rc;
 use std::collections::HashSet;
 
-#[derive(Hash)]
+#[derive(Hash, PartialEq, Eq)]
 pub struct Edge {
     pub p1: Rc<Point>,
     pub p2: Rc<Point>,
@@ -43,12 +43,3 @@ impl Edge {
         return double_edge_list;
     }
 }
-
-/* Equality implementation */
-impl PartialEq for Edge {
-    fn eq(&self, other: &Self) -> bool {
-        return self.p1 == other.p1 && self.p2 == other.p2;
-    }
-}
-
-impl Eq for Edge {}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+/**
+ * Failure of an operation of the crate
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /* Edge not found in the element, at the domain boundary or among the constraints */
+    MissingEdge,
+    /* Local matrix without inverse */
+    SingularMatrix,
+    /* Malformed data, on reading or writing */
+    Format,
+    /* Observed convergence orders below the expected ones */
+    Convergence,
+    /* Polynomial degree beyond the quadrature rules, or not allowed by the problem */
+    UnsupportedDegree,
+    /* Block of vectors spanning fewer directions than asked for */
+    RankDeficient,
+    /* Boundary edge left without a boundary condition */
+    MissingCondition,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Error::MissingEdge => "edge not found",
+            Error::SingularMatrix => "singular local matrix",
+            Error::Format => "malformed data",
+            Error::Convergence => "convergence orders below the expected ones",
+            Error::UnsupportedDegree => "unsupported polynomial degree",
+            Error::RankDeficient => "not enough independent vectors",
+            Error::MissingCondition => "boundary edge without condition",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for Error {}
@@ -12,7 +12,7 @@ pub struct Point {
 
 impl Point {
     pub fn new(x: f64, y: f64) -> Self {
-        Self { x: x, y: y }
+        Self { x, y }
     }
 
     pub fn as_matrix(&self) -> Matrix2x1<f64> {
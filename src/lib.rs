@@ -1,17 +1,58 @@
+/*
+    Explicit returns are the crate convention.
+*/
+#![allow(clippy::needless_return)]
+
 pub mod triangle {
     pub mod element;
     pub mod boundary_constraint;
+    pub mod mesh;
+    pub mod basis {
+        pub mod dubiner;
+        pub mod shape;
+    }
+    pub mod quadrature {
+        pub mod dunavant;
+        pub mod gauss_legendre;
+    }
+    pub mod time_stepping {
+        pub mod ssp_rk;
+    }
+    pub mod solvers {
+        pub mod lobpcg;
+        pub mod newton;
+    }
+    pub mod euler {
+        pub mod boundary;
+        pub mod flux;
+        pub mod limiter;
+        pub mod problem;
+    }
     pub mod integrands {
+        pub mod advection;
         pub mod dirichlet_constraint;
+        pub mod divergence;
+        pub mod elasticity;
         pub mod flux_artificial;
         pub mod flux_natural;
         pub mod mass;
         pub mod neumann_constraint;
         pub mod interface_penalty;
+        pub mod l2_mass;
         pub mod utils;
     }
     pub mod system_builder {
         pub mod domain;
+        pub mod dof_manager;
+        pub mod adaptivity;
+        pub mod advection_problem;
+        pub mod stokes_problem;
+        pub mod navier_stokes_problem;
+        pub mod hdg_problem;
+        pub mod elasticity_problem;
+        pub mod nonlinear_diffusion_problem;
+        pub mod heat_transfer_problem;
+        pub mod eigen_problem;
         pub mod builder;
         pub mod assembler_utils;
         pub mod internal {
@@ -19,15 +60,38 @@ pub mod triangle {
             pub mod flux_natural;
             pub mod flux_artificial;
             pub mod jump_penalty;
+            pub mod local_gradient;
+            pub mod advection;
+            pub mod upwind_flux;
+            pub mod l2_mass;
+            pub mod divergence;
         }
         pub mod external {
             pub mod dirichlet;
             pub mod neumann;
+            pub mod inflow;
+            pub mod prescribed_velocity;
+            pub mod source;
         }
     }
+    pub mod io {
+        pub mod csv;
+        pub mod matrix_market;
+        pub mod serialization;
+        pub mod vtu;
+    }
+    pub mod post_processing {
+        pub mod boundary_flux;
+        pub mod convergence;
+        pub mod dg_solution;
+        pub mod error_norms;
+        pub mod projection;
+        pub mod recovery;
+    }
 }
 
 pub mod common {
+    pub mod error;
     pub mod interfaces;
     pub mod field;
     pub mod point;
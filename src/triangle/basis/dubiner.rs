@@ -0,0 +1,312 @@
+use crate::common::point::Point;
+use crate::triangle::integrands::utils;
+
+use nalgebra::{DMatrix, DVector};
+
+/**
+ * Orthonormal Dubiner (Koornwinder) modal basis over default triangle ((0,0),(1,0), (0,1))
+ *
+ *  Modes are sorted by total degree, so the basis of degree p
+ *  is a prefix of the basis of degree p + 1.
+ *
+ *  Reference:
+ *      Hesthaven, J.S.; Warburton, T. Nodal Discontinuous Galerkin Methods:
+ *      Algorithms, Analysis, and Applications. Springer 2008. Section 6.1.
+ */
+pub fn size(degree: usize) -> usize {
+    return (degree + 1) * (degree + 2) / 2;
+}
+
+/**
+ * Pairs (i, j) of each mode, where i + j is the total degree
+ */
+pub fn modes(degree: usize) -> Vec<(usize, usize)> {
+    let mut modes: Vec<(usize, usize)> = Vec::with_capacity(size(degree));
+    for total in 0..=degree {
+        for i in 0..=total {
+            modes.push((i, total - i));
+        }
+    }
+    return modes;
+}
+
+/**
+ * Normalized Jacobi polynomial P_n^(alpha, beta)(x), orthonormal over [-1, 1]
+ * with weight (1 - x)^alpha (1 + x)^beta
+ */
+fn jacobi(n: usize, alpha: f64, beta: f64, x: f64) -> f64 {
+    let gamma = |v: f64| -> f64 { gamma_function(v) };
+
+    let gamma_0 = 2.0_f64.powf(alpha + beta + 1.0) / (alpha + beta + 1.0)
+        * gamma(alpha + 1.0)
+        * gamma(beta + 1.0)
+        / gamma(alpha + beta + 1.0);
+    let p_0 = 1.0 / gamma_0.sqrt();
+    if n == 0 {
+        return p_0;
+    }
+
+    let gamma_1 = (alpha + 1.0) * (beta + 1.0) / (alpha + beta + 3.0) * gamma_0;
+    let p_1 = ((alpha + beta + 2.0) * x / 2.0 + (alpha - beta) / 2.0) / gamma_1.sqrt();
+    if n == 1 {
+        return p_1;
+    }
+
+    /* Three term recurrence of normalized polynomials */
+    let mut a_old =
+        2.0 / (2.0 + alpha + beta) * ((alpha + 1.0) * (beta + 1.0) / (alpha + beta + 3.0)).sqrt();
+    let mut p_prev = p_0;
+    let mut p = p_1;
+    for i in 1..n {
+        let i = i as f64;
+        let h1 = 2.0 * i + alpha + beta;
+        let a_new = 2.0 / (h1 + 2.0)
+            * ((i + 1.0) * (i + 1.0 + alpha + beta) * (i + 1.0 + alpha) * (i + 1.0 + beta)
+                / (h1 + 1.0)
+                / (h1 + 3.0))
+                .sqrt();
+        let b_new = -(alpha * alpha - beta * beta) / h1 / (h1 + 2.0);
+        let p_next = (-a_old * p_prev + (x - b_new) * p) / a_new;
+        a_old = a_new;
+        p_prev = p;
+        p = p_next;
+    }
+    return p;
+}
+
+/**
+ * Derivative of the normalized Jacobi polynomial P_n^(alpha, beta)(x)
+ */
+fn jacobi_derivative(n: usize, alpha: f64, beta: f64, x: f64) -> f64 {
+    if n == 0 {
+        return 0.0;
+    }
+    let n_f = n as f64;
+    return (n_f * (n_f + alpha + beta + 1.0)).sqrt() * jacobi(n - 1, alpha + 1.0, beta + 1.0, x);
+}
+
+/**
+ * Gamma function for the positive arguments used by the Jacobi normalization
+ */
+fn gamma_function(v: f64) -> f64 {
+    /* Integer and half integer arguments only */
+    if (v - v.round()).abs() < 1.0E-12 {
+        return (1..(v.round() as usize)).fold(1.0, |acc, k| acc * k as f64);
+    }
+    let mut value = std::f64::consts::PI.sqrt();
+    let mut k = 0.5;
+    while k < v - 0.25 {
+        value *= k;
+        k += 1.0;
+    }
+    return value;
+}
+
+/**
+ * Collapsed coordinates (a, b) over [-1, 1]^2 of reference coordinates (x, y)
+ */
+fn collapsed_coordinates(x: f64, y: f64) -> (f64, f64) {
+    let r = 2.0 * x - 1.0;
+    let s = 2.0 * y - 1.0;
+    let a = if (1.0 - s).abs() > 1.0E-14 {
+        2.0 * (1.0 + r) / (1.0 - s) - 1.0
+    } else {
+        -1.0
+    };
+    return (a, s);
+}
+
+/**
+ * Values of every mode at reference coordinates (x, y)
+ */
+pub fn evaluate(degree: usize, x: f64, y: f64) -> DVector<f64> {
+    let (a, b) = collapsed_coordinates(x, y);
+    let modes = modes(degree);
+    let mut values = DVector::<f64>::zeros(modes.len());
+    for (index, (i, j)) in modes.iter().enumerate() {
+        let h1 = jacobi(*i, 0.0, 0.0, a);
+        let h2 = jacobi(*j, 2.0 * *i as f64 + 1.0, 0.0, b);
+        /* Factor 2 normalizes over the default triangle, whose area is 1/2 */
+        values[index] = 2.0 * 2.0_f64.sqrt() * h1 * h2 * (1.0 - b).powi(*i as i32);
+    }
+    return values;
+}
+
+/**
+ * Reference gradients of every mode at reference coordinates (x, y), one per row
+ */
+pub fn gradient(degree: usize, x: f64, y: f64) -> DMatrix<f64> {
+    let (a, b) = collapsed_coordinates(x, y);
+    let modes = modes(degree);
+    let mut gradient = DMatrix::<f64>::zeros(modes.len(), 2);
+    for (index, (i, j)) in modes.iter().enumerate() {
+        let i_f = *i as f64;
+        let fa = jacobi(*i, 0.0, 0.0, a);
+        let dfa = jacobi_derivative(*i, 0.0, 0.0, a);
+        let gb = jacobi(*j, 2.0 * i_f + 1.0, 0.0, b);
+        let dgb = jacobi_derivative(*j, 2.0 * i_f + 1.0, 0.0, b);
+
+        let half = 0.5 * (1.0 - b);
+        let (mut d_dr, mut d_ds) = (dfa * gb, dfa * gb * 0.5 * (1.0 + a));
+        if *i > 0 {
+            d_dr *= half.powi(*i as i32 - 1);
+            d_ds *= half.powi(*i as i32 - 1);
+        }
+        let mut tmp = dgb * half.powi(*i as i32);
+        if *i > 0 {
+            tmp -= 0.5 * i_f * gb * half.powi(*i as i32 - 1);
+        }
+        d_ds += fa * tmp;
+
+        /*
+            Mode normalization 2^(i + 1/2) over the biunit triangle,
+            factor 2 from its area and factor 2 from d/dx = 2 d/dr
+        */
+        let scale = 2.0_f64.powf(i_f + 0.5) * 4.0;
+        gradient[(index, 0)] = d_dr * scale;
+        gradient[(index, 1)] = d_ds * scale;
+    }
+    return gradient;
+}
+
+/**
+ * Mass matrix over triangle (p1, p2, p3): diagonal, since the modes are orthonormal
+ */
+pub fn mass_matrix(p1: &Point, p2: &Point, p3: &Point, degree: usize) -> DMatrix<f64> {
+    let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+    return DMatrix::<f64>::identity(size(degree), size(degree)) * determinant;
+}
+
+/**
+ * Equispaced nodes over default triangle: vertices first, then the remaining lattice
+ */
+pub fn nodes(degree: usize) -> Vec<(f64, f64)> {
+    let mut nodes: Vec<(f64, f64)> = vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+    if degree == 0 {
+        return vec![(1.0 / 3.0, 1.0 / 3.0)];
+    }
+    let step = 1.0 / degree as f64;
+    for j in 0..=degree {
+        for i in 0..=(degree - j) {
+            let is_vertex = (i == 0 || i == degree) && j == 0 || (i == 0 && j == degree);
+            if !is_vertex {
+                nodes.push((i as f64 * step, j as f64 * step));
+            }
+        }
+    }
+    return nodes;
+}
+
+/**
+ * Vandermonde matrix: mode j evaluated at node i
+ */
+pub fn vandermonde(degree: usize) -> DMatrix<f64> {
+    let nodes = nodes(degree);
+    let mut matrix = DMatrix::<f64>::zeros(nodes.len(), size(degree));
+    for (row, (x, y)) in nodes.iter().enumerate() {
+        matrix.set_row(row, &evaluate(degree, *x, *y).transpose());
+    }
+    return matrix;
+}
+
+/**
+ * Values at nodes(degree) of the modal expansion
+ */
+pub fn modal_to_nodal(degree: usize, modal: &DVector<f64>) -> DVector<f64> {
+    return vandermonde(degree) * modal;
+}
+
+/**
+ * Modal coefficients of the polynomial interpolating values at nodes(degree)
+ */
+pub fn nodal_to_modal(degree: usize, nodal: &DVector<f64>) -> DVector<f64> {
+    return vandermonde(degree).lu().solve(nodal).unwrap();
+}
+
+/**
+ * Modal coefficients of the linear field with values (u1, u2, u3) at the vertices
+ */
+pub fn linear_to_modal(degree: usize, u1: f64, u2: f64, u3: f64) -> DVector<f64> {
+    let nodal = DVector::from_iterator(
+        size(degree),
+        nodes(degree)
+            .iter()
+            .map(|(x, y)| u1 * (1.0 - x - y) + u2 * x + u3 * y),
+    );
+    return nodal_to_modal(degree, &nodal);
+}
+
+/**
+ * Values of the modal expansion at the vertices of the triangle
+ */
+pub fn vertex_values(degree: usize, modal: &DVector<f64>) -> (f64, f64, f64) {
+    let value = |x: f64, y: f64| evaluate(degree, x, y).dot(modal);
+    return (value(0.0, 0.0), value(1.0, 0.0), value(0.0, 1.0));
+}
+
+#[cfg(test)]
+mod orthonormality {
+    use super::*;
+    use crate::triangle::quadrature::dunavant;
+
+    #[test]
+    fn sample_1() {
+        /* Reference mass matrix is the identity */
+        for degree in 0..=4 {
+            let rule = dunavant::rule(2 * degree);
+            let mut mass = DMatrix::<f64>::zeros(size(degree), size(degree));
+            for ((x, y), w) in rule.points.iter().zip(rule.weights.iter()) {
+                let phi = evaluate(degree, *x, *y);
+                mass += &phi * phi.transpose() * *w;
+            }
+            let error = (mass - DMatrix::<f64>::identity(size(degree), size(degree))).norm();
+            assert!(error < 1.0E-12);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Gradients agree with finite differences */
+        let (x, y, h) = (0.3, 0.2, 1.0E-6);
+        let gradient = gradient(4, x, y);
+        let d_dx = (evaluate(4, x + h, y) - evaluate(4, x - h, y)) / (2.0 * h);
+        let d_dy = (evaluate(4, x, y + h) - evaluate(4, x, y - h)) / (2.0 * h);
+        assert!((gradient.column(0) - d_dx).norm() < 1.0E-7);
+        assert!((gradient.column(1) - d_dy).norm() < 1.0E-7);
+    }
+
+    #[test]
+    fn sample_3() {
+        /* First mode is constant, so constants have a single coefficient */
+        let modal = linear_to_modal(3, 2.0, 2.0, 2.0);
+        assert!((modal[0] - 2.0_f64.sqrt()).abs() < 1.0E-12);
+        for coefficient in modal.iter().skip(1) {
+            assert!(coefficient.abs() < 1.0E-12);
+        }
+    }
+
+    #[test]
+    fn sample_4() {
+        /* Nodal and modal representations round trip */
+        let nodal = DVector::from_iterator(size(3), (0..size(3)).map(|k| (k as f64).sin()));
+        let modal = nodal_to_modal(3, &nodal);
+        assert!((modal_to_nodal(3, &modal) - nodal).norm() < 1.0E-12);
+
+        let modal = linear_to_modal(2, 1.0, -2.0, 0.5);
+        let (u1, u2, u3) = vertex_values(2, &modal);
+        assert!((u1 - 1.0).abs() < 1.0E-12);
+        assert!((u2 + 2.0).abs() < 1.0E-12);
+        assert!((u3 - 0.5).abs() < 1.0E-12);
+    }
+
+    #[test]
+    fn sample_5() {
+        /* Physical mass matrix is diagonal */
+        let p1 = Point::new(2.0, 1.0);
+        let p2 = Point::new(3.0, 2.0);
+        let p3 = Point::new(2.0, 3.0);
+        let mass = mass_matrix(&p1, &p2, &p3, 2);
+        assert!((mass.clone() - DMatrix::from_diagonal(&mass.diagonal())).norm() < 1.0E-14);
+        assert!((mass[(0, 0)] - 2.0).abs() < 1.0E-14);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::common::point::Point;
+use crate::triangle::{basis::dubiner, integrands::utils, quadrature::dunavant};
+
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+
+/* Greatest modal degree whose mass matrix the quadrature rules integrate exactly */
+pub const MAX_MODAL_DEGREE: usize = dunavant::MAX_DEGREE / 2;
+
+/**
+ * Shape functions spanning the local space of an element
+ *  Nodal: linear lagrangian basis, one function per triangle point
+ *  Modal(p): orthonormal Dubiner basis of degree p
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Basis {
+    Nodal,
+    Modal(usize),
+}
+
+impl Basis {
+    /**
+     * Number of local degrees of freedom
+     */
+    pub fn size(&self) -> usize {
+        match self {
+            Basis::Nodal => 3,
+            Basis::Modal(degree) => dubiner::size(*degree),
+        }
+    }
+
+    /**
+     * Polynomial degree, used to choose quadrature rules
+     */
+    pub fn degree(&self) -> usize {
+        match self {
+            Basis::Nodal => 1,
+            Basis::Modal(degree) => *degree,
+        }
+    }
+
+    /**
+     * Values of every shape function at reference coordinates (x, y)
+     */
+    pub fn evaluate(&self, x: f64, y: f64) -> DVector<f64> {
+        match self {
+            Basis::Nodal => DVector::from_iterator(3, utils::reference_basis(x, y).iter().cloned()),
+            Basis::Modal(degree) => dubiner::evaluate(*degree, x, y),
+        }
+    }
+
+    /**
+     * Reference gradients of every shape function at reference coordinates (x, y), one per row
+     */
+    pub fn gradient(&self, x: f64, y: f64) -> DMatrix<f64> {
+        match self {
+            Basis::Nodal => DMatrix::from_iterator(3, 2, utils::grad().iter().cloned()),
+            Basis::Modal(degree) => dubiner::gradient(*degree, x, y),
+        }
+    }
+
+    /**
+     * Values of every shape function of triangle (p1, p2, p3) at a physical point,
+     * which is not required to lie inside the triangle
+     */
+    pub fn evaluate_at(&self, p1: &Point, p2: &Point, p3: &Point, point: &Point) -> DVector<f64> {
+        let (x, y) = utils::reference_coordinates(p1, p2, p3, point);
+        return self.evaluate(x, y);
+    }
+
+    /**
+     * Physical gradients of every shape function of triangle (p1, p2, p3)
+     * at reference coordinates (x, y), one per row
+     */
+    pub fn physical_gradient(
+        &self,
+        p1: &Point,
+        p2: &Point,
+        p3: &Point,
+        x: f64,
+        y: f64,
+    ) -> DMatrix<f64> {
+        let inverse = utils::jacobian(p1, p2, p3).try_inverse().unwrap();
+        return self.gradient(x, y) * DMatrix::from_iterator(2, 2, inverse.iter().cloned());
+    }
+
+    /**
+     * Physical gradients of every shape function of triangle (p1, p2, p3)
+     * at a physical point, one per row
+     */
+    pub fn physical_gradient_at(
+        &self,
+        p1: &Point,
+        p2: &Point,
+        p3: &Point,
+        point: &Point,
+    ) -> DMatrix<f64> {
+        let (x, y) = utils::reference_coordinates(p1, p2, p3, point);
+        return self.physical_gradient(p1, p2, p3, x, y);
+    }
+}
+
+#[cfg(test)]
+mod shape_functions {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /* Nodal basis matches the linear lagrangian basis */
+        let (x, y) = (0.2, 0.3);
+        let values = Basis::Nodal.evaluate(x, y);
+        assert!((values - DVector::from_vec(vec![0.5, 0.2, 0.3])).norm() < 1.0E-15);
+
+        let gradient = Basis::Nodal.gradient(x, y);
+        let expected = DMatrix::from_row_slice(3, 2, &[-1.0, -1.0, 1.0, 0.0, 0.0, 1.0]);
+        assert!((gradient - expected).norm() < 1.0E-15);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Physical gradient of the linear modal field recovers its slope */
+        let p1 = Point::new(2.0, 1.0);
+        let p2 = Point::new(3.0, 2.0);
+        let p3 = Point::new(2.0, 3.0);
+        let basis = Basis::Modal(2);
+
+        /* u = x + 2 y at the vertices */
+        let modal = dubiner::linear_to_modal(2, 4.0, 7.0, 8.0);
+        let point = Point::new(2.4, 2.0);
+        let slope = basis
+            .physical_gradient_at(&p1, &p2, &p3, &point)
+            .transpose()
+            * modal.clone();
+        assert!((slope[0] - 1.0).abs() < 1.0E-12);
+        assert!((slope[1] - 2.0).abs() < 1.0E-12);
+
+        let value = basis.evaluate_at(&p1, &p2, &p3, &point).dot(&modal);
+        assert!((value - 6.4).abs() < 1.0E-12);
+    }
+}
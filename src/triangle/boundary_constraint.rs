@@ -10,3 +10,19 @@ pub struct BoundaryConstraint {
     pub boundary_edge: Rc<Edge>,
     pub values: HashMap<Rc<Point>, f64>,
 }
+
+/**
+ * Stefan-Boltzmann constant, W / (m^2 K^4)
+ */
+pub const STEFAN_BOLTZMANN: f64 = 5.670_374_419E-8;
+
+/**
+ * Radiative heat loss at a boundary edge: emissivity * STEFAN_BOLTZMANN * (u^4 - u_amb^4),
+ * the ambient temperature given at both edge ends
+ */
+pub struct RadiationConstraint {
+    pub element: Rc<TriangleElementL1>,
+    pub boundary_edge: Rc<Edge>,
+    pub emissivity: f64,
+    pub ambient: HashMap<Rc<Point>, f64>,
+}
@@ -1,23 +1,17 @@
 use crate::common::edge::Edge;
+use crate::common::error::Error;
 use crate::common::point::Point;
 
 use std::fmt;
 use std::rc::Rc;
 
-#[derive(Hash)]
+#[derive(Hash, PartialEq, Eq)]
 pub struct TriangleElementL1 {
     pub p1: Rc<Point>,
     pub p2: Rc<Point>,
     pub p3: Rc<Point>,
 }
 
-/* Equality implementation */
-impl PartialEq for TriangleElementL1 {
-    fn eq(&self, other: &Self) -> bool {
-        return self.p1 == other.p1 && self.p2 == other.p2 && self.p3 == other.p3;
-    }
-}
-
 impl fmt::Display for TriangleElementL1 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} {} {}", self.p1, self.p2, self.p3)
@@ -25,7 +19,7 @@ impl fmt::Display for TriangleElementL1 {
 }
 
 impl TriangleElementL1 {
-    pub fn opposite_vertex(&self, edge: &Edge) -> Result<Rc<Point>, ()> {
+    pub fn opposite_vertex(&self, edge: &Edge) -> Result<Rc<Point>, Error> {
         if self.p1 == edge.p1 {
             return Ok(Rc::clone(&self.p3));
         } else if self.p2 == edge.p1 {
@@ -33,13 +27,11 @@ impl TriangleElementL1 {
         } else if self.p3 == edge.p1 {
             return Ok(Rc::clone(&self.p2));
         } else {
-            return Err(());
+            return Err(Error::MissingEdge);
         }
     }
 }
 
-impl Eq for TriangleElementL1 {}
-
 impl TriangleElementL1 {
     pub fn new(p1: &Rc<Point>, p2: &Rc<Point>, p3: &Rc<Point>) -> Self {
         Self {
@@ -56,7 +48,7 @@ impl TriangleElementL1 {
         return (e1, e2, e3);
     }
 
-    pub fn edge_index(&self, edge: &Edge) -> Result<usize, ()> {
+    pub fn edge_index(&self, edge: &Edge) -> Result<usize, Error> {
         if edge.p1 == self.p1 && edge.p2 == self.p2 {
             return Ok(0);
         } else if edge.p1 == self.p2 && edge.p2 == self.p3 {
@@ -64,7 +56,7 @@ impl TriangleElementL1 {
         } else if edge.p1 == self.p3 && edge.p2 == self.p1 {
             return Ok(2);
         }
-        return Err(());
+        return Err(Error::MissingEdge);
     }
 }
 
@@ -0,0 +1,99 @@
+use crate::triangle::euler::flux::{self, State, GAMMA};
+
+/**
+ * Boundary conditions, imposed through the outer state given to the numerical flux
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoundaryCondition {
+    /* Impermeable wall: normal velocity is mirrored */
+    SlipWall,
+    /* Every characteristic enters: outer state is prescribed */
+    SupersonicInflow(State),
+    /* Every characteristic leaves: outer state is the inner one */
+    SupersonicOutflow,
+    /* Free stream state, taken along the incoming characteristics only */
+    FarField(State),
+}
+
+impl BoundaryCondition {
+    /**
+     * Outer state at the boundary, given the inner state and the outer normal versor
+     */
+    pub fn outer_state(&self, inner: &State, normal: (f64, f64)) -> State {
+        match self {
+            BoundaryCondition::SlipWall => slip_wall(inner, normal),
+            BoundaryCondition::SupersonicInflow(state) => *state,
+            BoundaryCondition::SupersonicOutflow => *inner,
+            BoundaryCondition::FarField(state) => far_field(inner, state, normal),
+        }
+    }
+}
+
+fn slip_wall(inner: &State, (nx, ny): (f64, f64)) -> State {
+    let qn = (inner[1] * nx + inner[2] * ny) / inner[0];
+    return State::new(
+        inner[0],
+        inner[1] - 2.0 * inner[0] * qn * nx,
+        inner[2] - 2.0 * inner[0] * qn * ny,
+        inner[3],
+    );
+}
+
+/**
+ * Riemann invariants along the normal: the outgoing one comes from the inner state,
+ * the incoming one from the free stream. Entropy and tangential velocity are
+ * taken from the side the flow comes from
+ */
+fn far_field(inner: &State, free_stream: &State, (nx, ny): (f64, f64)) -> State {
+    let (rho_i, u_i, v_i, p_i) = flux::primitive(inner);
+    let (rho_f, u_f, v_f, p_f) = flux::primitive(free_stream);
+    let (c_i, c_f) = (flux::sound_speed(inner), flux::sound_speed(free_stream));
+    let (qn_i, qn_f) = (u_i * nx + v_i * ny, u_f * nx + v_f * ny);
+
+    if qn_i.abs() >= c_i {
+        if qn_i < 0.0 {
+            return *free_stream;
+        }
+        return *inner;
+    }
+
+    let outgoing = qn_i + 2.0 * c_i / (GAMMA - 1.0);
+    let incoming = qn_f - 2.0 * c_f / (GAMMA - 1.0);
+    let qn = 0.5 * (outgoing + incoming);
+    let c = 0.25 * (GAMMA - 1.0) * (outgoing - incoming);
+
+    let (entropy, u_t, v_t) = if qn < 0.0 {
+        (p_f / rho_f.powf(GAMMA), u_f - qn_f * nx, v_f - qn_f * ny)
+    } else {
+        (p_i / rho_i.powf(GAMMA), u_i - qn_i * nx, v_i - qn_i * ny)
+    };
+    let rho = (c * c / (GAMMA * entropy)).powf(1.0 / (GAMMA - 1.0));
+    let p = rho * c * c / GAMMA;
+    return flux::conservative(rho, u_t + qn * nx, v_t + qn * ny, p);
+}
+
+#[cfg(test)]
+mod outer_state {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /* Slip wall: no mass crosses the wall and pressure acts along the normal */
+        let inner = flux::conservative(1.0, 0.3, 0.4, 1.0);
+        let normal = (0.6, 0.8);
+        let outer = BoundaryCondition::SlipWall.outer_state(&inner, normal);
+        let wall_flux = flux::NumericalFlux::Roe.evaluate(&inner, &outer, normal);
+        assert!(wall_flux[0].abs() < 1.0E-14);
+        assert!(wall_flux[3].abs() < 1.0E-14);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Far field at the free stream state keeps it */
+        let free_stream = flux::conservative(1.0, 0.5, 0.1, 1.0 / GAMMA);
+        for normal in [(1.0, 0.0), (-0.6, 0.8), (0.0, -1.0)].iter() {
+            let outer = BoundaryCondition::FarField(free_stream).outer_state(&free_stream, *normal);
+            assert!((outer - free_stream).norm() < 1.0E-14);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use nalgebra::Vector4;
+
+/**
+ * Conservative state (density, x momentum, y momentum, total energy)
+ */
+pub type State = Vector4<f64>;
+
+/* Heat capacity ratio of air */
+pub const GAMMA: f64 = 1.4;
+
+/**
+ * State from primitive variables (density, x velocity, y velocity, pressure)
+ */
+pub fn conservative(rho: f64, u: f64, v: f64, p: f64) -> State {
+    return State::new(
+        rho,
+        rho * u,
+        rho * v,
+        p / (GAMMA - 1.0) + 0.5 * rho * (u * u + v * v),
+    );
+}
+
+/**
+ * Primitive variables (density, x velocity, y velocity, pressure) of the state
+ */
+pub fn primitive(state: &State) -> (f64, f64, f64, f64) {
+    let rho = state[0];
+    let (u, v) = (state[1] / rho, state[2] / rho);
+    let p = (GAMMA - 1.0) * (state[3] - 0.5 * rho * (u * u + v * v));
+    return (rho, u, v, p);
+}
+
+pub fn pressure(state: &State) -> f64 {
+    return primitive(state).3;
+}
+
+pub fn sound_speed(state: &State) -> f64 {
+    let (rho, _, _, p) = primitive(state);
+    return (GAMMA * p / rho).sqrt();
+}
+
+/**
+ * Physical fluxes (F, G) along x and y
+ */
+pub fn physical_flux(state: &State) -> (State, State) {
+    let (_, u, v, p) = primitive(state);
+    let f = State::new(state[1], state[1] * u + p, state[1] * v, (state[3] + p) * u);
+    let g = State::new(state[2], state[2] * u, state[2] * v + p, (state[3] + p) * v);
+    return (f, g);
+}
+
+/**
+ * Physical flux along the versor n: F nx + G ny
+ */
+pub fn normal_flux(state: &State, (nx, ny): (f64, f64)) -> State {
+    let (f, g) = physical_flux(state);
+    return f * nx + g * ny;
+}
+
+/**
+ * Greatest wave speed |v . n| + c along the versor n
+ */
+pub fn max_wave_speed(state: &State, (nx, ny): (f64, f64)) -> f64 {
+    let (_, u, v, _) = primitive(state);
+    return (u * nx + v * ny).abs() + sound_speed(state);
+}
+
+/**
+ * Numerical flux at faces, from the inner state towards the outer state
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumericalFlux {
+    /* Local Lax-Friedrichs */
+    Rusanov,
+    Roe,
+    Hllc,
+}
+
+impl NumericalFlux {
+    pub fn evaluate(&self, inner: &State, outer: &State, normal: (f64, f64)) -> State {
+        match self {
+            NumericalFlux::Rusanov => rusanov(inner, outer, normal),
+            NumericalFlux::Roe => roe(inner, outer, normal),
+            NumericalFlux::Hllc => hllc(inner, outer, normal),
+        }
+    }
+}
+
+fn rusanov(inner: &State, outer: &State, normal: (f64, f64)) -> State {
+    let speed = max_wave_speed(inner, normal).max(max_wave_speed(outer, normal));
+    return (normal_flux(inner, normal) + normal_flux(outer, normal)) * 0.5
+        - (outer - inner) * (0.5 * speed);
+}
+
+/**
+ * Roe averages: density, x velocity, y velocity, enthalpy and sound speed
+ */
+fn roe_average(inner: &State, outer: &State) -> (f64, f64, f64, f64, f64) {
+    let (rho_l, u_l, v_l, p_l) = primitive(inner);
+    let (rho_r, u_r, v_r, p_r) = primitive(outer);
+    let (h_l, h_r) = ((inner[3] + p_l) / rho_l, (outer[3] + p_r) / rho_r);
+    let (w_l, w_r) = (rho_l.sqrt(), rho_r.sqrt());
+
+    let u = (w_l * u_l + w_r * u_r) / (w_l + w_r);
+    let v = (w_l * v_l + w_r * v_r) / (w_l + w_r);
+    let h = (w_l * h_l + w_r * h_r) / (w_l + w_r);
+    let c = ((GAMMA - 1.0) * (h - 0.5 * (u * u + v * v))).sqrt();
+    return (w_l * w_r, u, v, h, c);
+}
+
+/**
+ *  Reference:
+ *      Toro, E.F. Riemann Solvers and Numerical Methods for Fluid Dynamics.
+ *      Springer 2009, 3rd Edition. Chapter 11.
+ */
+fn roe(inner: &State, outer: &State, (nx, ny): (f64, f64)) -> State {
+    let (rho_l, u_l, v_l, p_l) = primitive(inner);
+    let (rho_r, u_r, v_r, p_r) = primitive(outer);
+    let (rho, u, v, h, c) = roe_average(inner, outer);
+    let qn = u * nx + v * ny;
+
+    let (d_rho, d_p) = (rho_r - rho_l, p_r - p_l);
+    let (d_u, d_v) = (u_r - u_l, v_r - v_l);
+    let d_qn = d_u * nx + d_v * ny;
+
+    /* Harten entropy fix at acoustic waves */
+    let fix = |lambda: f64| -> f64 {
+        let delta = 0.1 * c;
+        if lambda.abs() < delta {
+            (lambda * lambda + delta * delta) / (2.0 * delta)
+        } else {
+            lambda.abs()
+        }
+    };
+
+    let alpha_1 = (d_p - rho * c * d_qn) / (2.0 * c * c);
+    let alpha_2 = d_rho - d_p / (c * c);
+    let alpha_3 = (d_p + rho * c * d_qn) / (2.0 * c * c);
+
+    let r_1 = State::new(1.0, u - c * nx, v - c * ny, h - qn * c);
+    let r_2 = State::new(1.0, u, v, 0.5 * (u * u + v * v));
+    let r_3 = State::new(1.0, u + c * nx, v + c * ny, h + qn * c);
+    let r_shear = State::new(
+        0.0,
+        d_u - d_qn * nx,
+        d_v - d_qn * ny,
+        u * d_u + v * d_v - qn * d_qn,
+    ) * rho;
+
+    let dissipation = r_1 * (fix(qn - c) * alpha_1)
+        + r_2 * (qn.abs() * alpha_2)
+        + r_3 * (fix(qn + c) * alpha_3)
+        + r_shear * qn.abs();
+
+    return (normal_flux(inner, (nx, ny)) + normal_flux(outer, (nx, ny))) * 0.5 - dissipation * 0.5;
+}
+
+/**
+ *  Reference:
+ *      Toro, E.F. Riemann Solvers and Numerical Methods for Fluid Dynamics.
+ *      Springer 2009, 3rd Edition. Chapter 10.
+ */
+fn hllc(inner: &State, outer: &State, (nx, ny): (f64, f64)) -> State {
+    let (rho_l, u_l, v_l, p_l) = primitive(inner);
+    let (rho_r, u_r, v_r, p_r) = primitive(outer);
+    let (qn_l, qn_r) = (u_l * nx + v_l * ny, u_r * nx + v_r * ny);
+    let (_, u, v, _, c) = roe_average(inner, outer);
+    let qn = u * nx + v * ny;
+
+    /* Einfeldt wave speed estimates */
+    let s_l = (qn_l - sound_speed(inner)).min(qn - c);
+    let s_r = (qn_r + sound_speed(outer)).max(qn + c);
+    let s_star = (p_r - p_l + rho_l * qn_l * (s_l - qn_l) - rho_r * qn_r * (s_r - qn_r))
+        / (rho_l * (s_l - qn_l) - rho_r * (s_r - qn_r));
+
+    let star = |state: &State, s: f64, q: f64, p: f64| -> State {
+        let (rho, u, v, _) = primitive(state);
+        let factor = rho * (s - q) / (s - s_star);
+        State::new(
+            1.0,
+            u + (s_star - q) * nx,
+            v + (s_star - q) * ny,
+            state[3] / rho + (s_star - q) * (s_star + p / (rho * (s - q))),
+        ) * factor
+    };
+
+    if s_l >= 0.0 {
+        return normal_flux(inner, (nx, ny));
+    } else if s_star >= 0.0 {
+        return normal_flux(inner, (nx, ny)) + (star(inner, s_l, qn_l, p_l) - inner) * s_l;
+    } else if s_r >= 0.0 {
+        return normal_flux(outer, (nx, ny)) + (star(outer, s_r, qn_r, p_r) - outer) * s_r;
+    } else {
+        return normal_flux(outer, (nx, ny));
+    }
+}
+
+#[cfg(test)]
+mod numerical_flux {
+    use super::*;
+
+    const FLUXES: [NumericalFlux; 3] = [
+        NumericalFlux::Rusanov,
+        NumericalFlux::Roe,
+        NumericalFlux::Hllc,
+    ];
+
+    #[test]
+    fn sample_1() {
+        /* Consistency: equal states give the physical flux */
+        let state = conservative(1.2, 0.3, -0.4, 0.9);
+        let normal = (0.6, 0.8);
+        for flux in FLUXES.iter() {
+            let error = flux.evaluate(&state, &state, normal) - normal_flux(&state, normal);
+            assert!(error.norm() < 1.0E-14);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Conservation: swapping sides and normal changes the sign */
+        let inner = conservative(1.0, 0.75, 0.1, 1.0);
+        let outer = conservative(0.125, -0.2, 0.3, 0.1);
+        let normal = (0.6, -0.8);
+        for flux in FLUXES.iter() {
+            let forward = flux.evaluate(&inner, &outer, normal);
+            let backward = flux.evaluate(&outer, &inner, (-normal.0, -normal.1));
+            assert!((forward + backward).norm() < 1.0E-14);
+        }
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Roe and HLLC resolve a stationary contact exactly */
+        let inner = conservative(1.0, 0.0, 0.0, 1.0);
+        let outer = conservative(0.5, 0.0, 0.0, 1.0);
+        let expected = State::new(0.0, 1.0, 0.0, 0.0);
+        for flux in [NumericalFlux::Roe, NumericalFlux::Hllc].iter() {
+            let error = flux.evaluate(&inner, &outer, (1.0, 0.0)) - expected;
+            assert!(error.norm() < 1.0E-14);
+        }
+    }
+}
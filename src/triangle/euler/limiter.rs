@@ -0,0 +1,250 @@
+use crate::triangle::euler::flux::{self, State};
+
+/**
+ * Slope limiters, acting on the vertex values of the linear part of element fields
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SlopeLimiter {
+    /* TVB minmod at edge midpoints: deviations below M h^2 are kept, M given */
+    Minmod(f64),
+    /* Vertex values bounded by the averages of the element and its neighbours */
+    BarthJespersen,
+}
+
+/**
+ * Elements where the slope limiter is applied
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TroubledCells {
+    Everywhere,
+    /* Jumps of density across inflow edges, as in KXRCF */
+    Kxrcf,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limiting {
+    pub slope: Option<SlopeLimiter>,
+    pub troubled_cells: TroubledCells,
+    /* Scales element fields so that density and pressure remain positive */
+    pub positivity: bool,
+}
+
+impl Limiting {
+    pub fn none() -> Self {
+        return Limiting {
+            slope: None,
+            troubled_cells: TroubledCells::Everywhere,
+            positivity: false,
+        };
+    }
+}
+
+/* Lower bound on density and pressure kept by the positivity scaling */
+pub const POSITIVITY_EPSILON: f64 = 1.0E-13;
+
+/* Cockburn-Shu factor over neighbour differences */
+pub const MINMOD_FACTOR: f64 = 1.5;
+
+pub fn minmod(a: f64, b: f64) -> f64 {
+    if a * b <= 0.0 {
+        return 0.0;
+    }
+    return a.signum() * a.abs().min(b.abs());
+}
+
+/**
+ * Minmod that keeps the first argument whenever it is below the bound
+ */
+pub fn tvb_minmod(a: f64, b: f64, bound: f64) -> f64 {
+    if a.abs() <= bound {
+        return a;
+    }
+    return minmod(a, b);
+}
+
+/**
+ * TVB limited vertex values of a linear field.
+ *  The deviation from the average at the midpoint of each edge (p1p2, p2p3, p3p1)
+ *  is compared against the neighbour difference across that edge,
+ *  already scaled to the midpoint. Boundary edges have no neighbour difference.
+ *  Returns None when the field is kept
+ *
+ *  Reference:
+ *      Cockburn, B.; Shu, C.W. The Runge-Kutta discontinuous Galerkin method for
+ *      conservation laws V: multidimensional systems. JCP, 1998. Vol 141, 199-224.
+ */
+pub fn minmod_vertices(
+    average: f64,
+    (v1, v2, v3): (f64, f64, f64),
+    neighbour_differences: [Option<f64>; 3],
+    bound: f64,
+) -> Option<(f64, f64, f64)> {
+    let deviations = [
+        (v1 + v2) / 2.0 - average,
+        (v2 + v3) / 2.0 - average,
+        (v3 + v1) / 2.0 - average,
+    ];
+
+    let mut limited = [0.0; 3];
+    let mut changed = false;
+    for n in 0..3 {
+        limited[n] = match neighbour_differences[n] {
+            Some(difference) => tvb_minmod(deviations[n], difference, bound),
+            None => deviations[n],
+        };
+        changed = changed || limited[n] != deviations[n];
+    }
+    if !changed {
+        return None;
+    }
+
+    /* Midpoint deviations of a linear field sum up to zero */
+    let positive: f64 = limited.iter().map(|d| d.max(0.0)).sum();
+    let negative: f64 = limited.iter().map(|d| (-d).max(0.0)).sum();
+    if positive > 0.0 && negative > 0.0 {
+        let (theta_plus, theta_minus) = (
+            (negative / positive).min(1.0),
+            (positive / negative).min(1.0),
+        );
+        for deviation in limited.iter_mut() {
+            *deviation = theta_plus * deviation.max(0.0) - theta_minus * (-*deviation).max(0.0);
+        }
+    } else {
+        limited = [0.0; 3];
+    }
+
+    let (m1, m2, m3) = (limited[0], limited[1], limited[2]);
+    return Some((
+        average + m1 + m3 - m2,
+        average + m1 + m2 - m3,
+        average + m2 + m3 - m1,
+    ));
+}
+
+/**
+ * Barth-Jespersen limited vertex values: the linear part is scaled so that
+ * no vertex value exceeds the range of the element and neighbour averages.
+ *  Returns None when the field is kept
+ *
+ *  Reference:
+ *      Barth, T.J.; Jespersen, D.C. The design and application of upwind schemes
+ *      on unstructured meshes. AIAA paper 89-0366.
+ */
+pub fn barth_jespersen_vertices(
+    average: f64,
+    (v1, v2, v3): (f64, f64, f64),
+    neighbour_averages: &[f64],
+) -> Option<(f64, f64, f64)> {
+    let upper = neighbour_averages.iter().fold(average, |a, b| a.max(*b));
+    let lower = neighbour_averages.iter().fold(average, |a, b| a.min(*b));
+
+    let mut phi: f64 = 1.0;
+    for vertex in [v1, v2, v3].iter() {
+        let deviation = vertex - average;
+        if deviation > 0.0 {
+            phi = phi.min((upper - average) / deviation);
+        } else if deviation < 0.0 {
+            phi = phi.min((lower - average) / deviation);
+        }
+    }
+    if phi >= 1.0 {
+        return None;
+    }
+    return Some((
+        average + phi * (v1 - average),
+        average + phi * (v2 - average),
+        average + phi * (v3 - average),
+    ));
+}
+
+/**
+ * Greatest theta in [0, 1] such that average + theta (value - average)
+ * remains above epsilon, given the least value over the element
+ */
+pub fn density_theta(average: f64, least: f64, epsilon: f64) -> f64 {
+    if least >= epsilon {
+        return 1.0;
+    }
+    return ((average - epsilon) / (average - least)).clamp(0.0, 1.0);
+}
+
+/**
+ * Greatest theta in [0, 1] such that the pressure of average + theta (state - average)
+ * remains above epsilon. Pressure is concave on states of positive density,
+ * so bisection finds it
+ *
+ *  Reference:
+ *      Zhang, X.; Shu, C.W. On positivity-preserving high order discontinuous
+ *      Galerkin schemes for compressible Euler equations on rectangular meshes.
+ *      JCP, 2010. Vol 229, 8918-8934.
+ */
+pub fn pressure_theta(average: &State, state: &State, epsilon: f64) -> f64 {
+    if flux::pressure(state) >= epsilon {
+        return 1.0;
+    }
+    let (mut lower, mut upper) = (0.0, 1.0);
+    for _ in 0..50 {
+        let middle = 0.5 * (lower + upper);
+        if flux::pressure(&(average + (state - average) * middle)) >= epsilon {
+            lower = middle;
+        } else {
+            upper = middle;
+        }
+    }
+    return lower;
+}
+
+#[cfg(test)]
+mod limiters {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        assert_eq!(minmod(1.0, 2.0), 1.0);
+        assert_eq!(minmod(-3.0, -2.0), -2.0);
+        assert_eq!(minmod(1.0, -2.0), 0.0);
+        assert_eq!(tvb_minmod(1.0, -2.0, 1.5), 1.0);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Local extremum is flattened, smooth slopes are kept */
+        let flat = Some(0.0);
+        assert_eq!(
+            minmod_vertices(1.0, (2.0, 0.5, 0.5), [flat, flat, flat], 0.0),
+            Some((1.0, 1.0, 1.0))
+        );
+        let kept = minmod_vertices(
+            1.0,
+            (0.0, 2.0, 1.0),
+            [Some(1.0E3), Some(1.0E3), Some(-1.0E3)],
+            0.0,
+        );
+        assert_eq!(kept, None);
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Vertices are scaled into the range of neighbour averages */
+        let limited = barth_jespersen_vertices(1.0, (3.0, 0.0, 0.0), &[0.0, 2.0]).unwrap();
+        assert!((limited.0 - 2.0).abs() < 1.0E-14);
+        assert!((limited.1 - 0.5).abs() < 1.0E-14);
+        assert_eq!(
+            barth_jespersen_vertices(1.0, (1.5, 0.5, 1.0), &[0.0, 2.0]),
+            None
+        );
+    }
+
+    #[test]
+    fn sample_4() {
+        /* Scaled states keep positive density and pressure */
+        assert!((density_theta(1.0, -1.0, 0.0) - 0.5).abs() < 1.0E-14);
+
+        let average = flux::conservative(1.0, 0.0, 0.0, 1.0);
+        let state = State::new(1.0, 3.0, 0.0, 2.5);
+        let theta = pressure_theta(&average, &state, 1.0E-13);
+        assert!(theta > 0.0 && theta < 1.0);
+        let scaled = average + (state - average) * theta;
+        assert!(flux::pressure(&scaled) >= 0.0 && flux::pressure(&scaled) < 1.0E-10);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::common::point::Point;
+use crate::triangle::{
+    basis::shape::Basis,
+    integrands::utils,
+    quadrature::{dunavant, gauss_legendre},
+};
+use nalgebra::{DMatrix, DVector, Matrix3x1};
+
+/**
+ * Velocity field b(x, y) evaluated at physical points
+ */
+pub type Velocity<'a> = &'a dyn Fn(&Point) -> (f64, f64);
+
+/**
+ * Normal velocity b . n at the physical point of edge n of triangle (p1, p2, p3)
+ */
+fn normal_velocity(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    n: usize,
+    point: &Point,
+    velocity: Velocity,
+) -> f64 {
+    let normal = utils::outer_normal(p1, p2, p3, n);
+    let (bx, by) = velocity(point);
+    return bx * normal[0] + by * normal[1];
+}
+
+/**
+ * Advection over the triangle, integrated by parts:
+ *  - integral of u_j * b . grad(phi_i)
+ */
+pub fn volume_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    velocity: Velocity,
+) -> DMatrix<f64> {
+    let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+
+    let rule = dunavant::rule((2 * basis.degree() + 1).min(dunavant::MAX_DEGREE));
+    let mut matrix = DMatrix::<f64>::zeros(basis.size(), basis.size());
+    for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let point = utils::physical_point(p1, p2, p3, *x, *y);
+        let (bx, by) = velocity(&point);
+        let gradient = basis.physical_gradient(p1, p2, p3, *x, *y);
+        let directional = gradient.column(0) * bx + gradient.column(1) * by;
+        matrix -= directional * basis.evaluate(*x, *y).transpose() * (weight * determinant);
+    }
+    return matrix;
+}
+
+/**
+ * Upwind flux at the edge of triangle (p1, p2, p3): integral of b . n u* phi_i,
+ * where u* is the trace from the side the flow comes from.
+ * Returns the left-left and left-right blocks
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn upwind_flux_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    left: &Basis,
+    p4: &Point,
+    p5: &Point,
+    p6: &Point,
+    right: &Basis,
+    edge_index: usize,
+    velocity: Velocity,
+) -> (DMatrix<f64>, DMatrix<f64>) {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+
+    let rule = gauss_legendre::rule(left.degree() + right.degree() + 1);
+    let mut outflow = DMatrix::<f64>::zeros(left.size(), left.size());
+    let mut inflow = DMatrix::<f64>::zeros(left.size(), right.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let point = utils::physical_point(p1, p2, p3, x, y);
+        let bn = normal_velocity(p1, p2, p3, edge_index, &point, velocity);
+        let phi = left.evaluate(x, y);
+        if bn > 0.0 {
+            outflow += &phi * phi.transpose() * (bn * weight * length);
+        } else {
+            let psi = right.evaluate_at(p4, p5, p6, &point);
+            inflow += &phi * psi.transpose() * (bn * weight * length);
+        }
+    }
+    return (outflow, inflow);
+}
+
+/**
+ * Outflow at the boundary edge of triangle (p1, p2, p3):
+ *  integral of max(b . n, 0) phi_i phi_j
+ */
+pub fn outflow_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    edge_index: usize,
+    velocity: Velocity,
+) -> DMatrix<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+
+    let rule = gauss_legendre::rule(2 * basis.degree() + 1);
+    let mut integral = DMatrix::<f64>::zeros(basis.size(), basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let point = utils::physical_point(p1, p2, p3, x, y);
+        let bn = normal_velocity(p1, p2, p3, edge_index, &point, velocity);
+        if bn > 0.0 {
+            let phi = basis.evaluate(x, y);
+            integral += &phi * phi.transpose() * (bn * weight * length);
+        }
+    }
+    return integral;
+}
+
+/**
+ * Inflow of prescribed values at the boundary edge of triangle (p1, p2, p3):
+ *  integral of -min(b . n, 0) g phi_i
+ *  g is interpolated from the nodal values (u1, u2, u3)
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn inflow_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    u1: f64,
+    u2: f64,
+    u3: f64,
+    edge_index: usize,
+    velocity: Velocity,
+) -> DVector<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let values = Matrix3x1::new(u1, u2, u3);
+
+    let rule = gauss_legendre::rule(basis.degree() + 2);
+    let mut integral = DVector::<f64>::zeros(basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let point = utils::physical_point(p1, p2, p3, x, y);
+        let bn = normal_velocity(p1, p2, p3, edge_index, &point, velocity);
+        if bn < 0.0 {
+            let g = (utils::reference_basis(x, y) * values)[(0, 0)];
+            integral -= basis.evaluate(x, y) * (bn * g * weight * length);
+        }
+    }
+    return integral;
+}
+
+#[cfg(test)]
+mod upwind {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /*
+            Constant velocity: volume term plus boundary fluxes of a constant field
+            vanish, since the divergence of b is zero
+        */
+        let p1 = Point::new(2.0, 1.0);
+        let p2 = Point::new(3.0, 2.0);
+        let p3 = Point::new(2.0, 3.0);
+        let basis = Basis::Modal(2);
+        let velocity = |_: &Point| (1.0, 0.5);
+
+        let mut matrix = volume_basis(&p1, &p2, &p3, &basis, &velocity);
+        for edge_index in 0..3 {
+            let (outflow, inflow) = upwind_flux_basis(
+                &p1, &p2, &p3, &basis, &p1, &p2, &p3, &basis, edge_index, &velocity,
+            );
+            matrix += outflow + inflow;
+        }
+        let constant = crate::triangle::basis::dubiner::linear_to_modal(2, 1.0, 1.0, 1.0);
+        assert!((matrix * constant).norm() < 1.0E-13);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Edge 0 lies at y = 0, with outer normal (0, -1) */
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let p3 = Point::new(0.0, 1.0);
+        let downwards = |_: &Point| (0.0, -2.0);
+        let upwards = |_: &Point| (0.0, 2.0);
+
+        /* Outflow only where the flow leaves the triangle */
+        let outflow = outflow_basis(&p1, &p2, &p3, &Basis::Nodal, 0, &downwards);
+        assert!((outflow.sum() - 2.0).abs() < 1.0E-14);
+        let outflow = outflow_basis(&p1, &p2, &p3, &Basis::Nodal, 0, &upwards);
+        assert!(outflow.norm() < 1.0E-14);
+
+        /* Inflow carries the prescribed values into the triangle */
+        let inflow = inflow_basis(&p1, &p2, &p3, &Basis::Nodal, 1.0, 3.0, 0.0, 0, &upwards);
+        assert!((inflow.sum() - 4.0).abs() < 1.0E-14);
+        let inflow = inflow_basis(&p1, &p2, &p3, &Basis::Nodal, 1.0, 3.0, 0.0, 0, &downwards);
+        assert!(inflow.norm() < 1.0E-14);
+    }
+}
@@ -1,9 +1,11 @@
 use crate::common::point::Point;
-use nalgebra::{Matrix3, Matrix3x1};
+use crate::triangle::{basis::shape::Basis, integrands::utils, quadrature::gauss_legendre};
+use nalgebra::{DMatrix, DVector, Matrix3, Matrix3x1};
 
 /**
- * For the default L1 triangle, flux matrix only depends
- * on the points located at the interface
+ * Natural term of weakly imposed dirichlet values at the edge of triangle (p1, p2, p3):
+ *  integral of grad(phi_i) . n * u
+ *  u is interpolated from the nodal values (u1, u2, u3)
  */
 pub fn dirichlet_linear_natural(
     p1: &Point,
@@ -14,94 +16,75 @@ pub fn dirichlet_linear_natural(
     u3: f64,
     edge_index: usize,
 ) -> Matrix3x1<f64> {
-    let a = match edge_index {
-        /* 0-edge (0,0) -> (1,0) */
-        0 => Matrix3::new(
-            (-p1.x + p3.x - p1.y + p3.y) / 2.0,
-            (-p1.x + p3.x - p1.y + p3.y) / 2.0,
-            0.0,
-            (p1.x - p3.x) / 2.0,
-            (p1.x - p3.x) / 2.0,
-            0.0,
-            (p1.y - p3.y) / 2.0,
-            (p1.y - p3.y) / 2.0,
-            0.0,
-        ),
-        /* 1-edge (1,0) -> (0,1) */
-        1 => Matrix3::new(
-            0.0,
-            p1.x - p2.x / 2.0 - p3.x / 2.0 + p1.y - p2.y / 2.0 - p3.y / 2.0,
-            p1.x - p2.x / 2.0 - p3.x / 2.0 + p1.y - p2.y / 2.0 - p3.y / 2.0,
-            0.0,
-            -p1.x + p2.x / 2.0 + p3.x / 2.0,
-            -p1.x + p2.x / 2.0 + p3.x / 2.0,
-            0.0,
-            -p1.y + p2.y / 2.0 + p3.y / 2.0,
-            -p1.y + p2.y / 2.0 + p3.y / 2.0,
-        ),
-        /* 0-edge (0,1) -> (0,0) */
-        2 => Matrix3::new(
-            (p1.x - p2.x + p1.y - p2.y) / 2.0,
-            0.0,
-            (p1.x - p2.x + p1.y - p2.y) / 2.0,
-            (-p1.x + p2.x) / 2.0,
-            0.0,
-            (-p1.x + p2.x) / 2.0,
-            (-p1.y + p2.y) / 2.0,
-            0.0,
-            (-p1.y + p2.y) / 2.0,
-        ),
-        _ => panic!("Not expecting edge"),
-    };
-    return a * Matrix3x1::new(u1, u2, u3);
+    let integral =
+        dirichlet_linear_natural_basis(p1, p2, p3, &Basis::Nodal, u1, u2, u3, edge_index);
+    return Matrix3x1::from_iterator(integral.iter().cloned());
+}
+
+/**
+ * Natural term of weakly imposed dirichlet values for an arbitrary basis
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn dirichlet_linear_natural_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    u1: f64,
+    u2: f64,
+    u3: f64,
+    edge_index: usize,
+) -> DVector<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let normal = utils::outer_normal(p1, p2, p3, edge_index);
+    let normal = DMatrix::from_row_slice(2, 1, &[normal[0], normal[1]]);
+    let values = Matrix3x1::new(u1, u2, u3);
+
+    let rule = gauss_legendre::rule(basis.degree());
+    let mut integral = DVector::<f64>::zeros(basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let u = (utils::reference_basis(x, y) * values)[(0, 0)];
+        let normal_derivative = basis.physical_gradient(p1, p2, p3, x, y) * &normal;
+        integral += normal_derivative.column(0) * (u * weight * length);
+    }
+    return integral;
 }
 
+/**
+ * Penalty of weakly imposed dirichlet values at the edge of triangle (p1, p2, p3):
+ *  integral of phi_i * phi_j
+ */
 pub fn dirichlet_bilinear_penalty(
     p1: &Point,
     p2: &Point,
     p3: &Point,
     edge_index: usize,
 ) -> Matrix3<f64> {
-    let sqrt_2 = (2.0 as f64).sqrt();
-    match edge_index {
-        /* 0-edge (0,0) -> (1,0) */
-        0 => Matrix3::new(
-            -(p1.x / 3.0 + p2.x / 6.0 + p1.y / 3.0 + p2.y / 6.0) + 0.5,
-            -(p1.x / 6.0 + p2.x / 3.0 + p1.y / 6.0 + p2.y / 3.0) + 0.5,
-            0.0,
-            p1.x / 3.0 + p2.x / 6.0,
-            p1.x / 6.0 + p2.x / 3.0,
-            0.0,
-            p1.y / 3.0 + p2.y / 6.0,
-            p1.y / 6.0 + p2.y / 3.0,
-            0.0,
-        ),
-        /* 1-edge (1,0) -> (0,1) */
-        1 => Matrix3::new(
-            0.0,
-            (-2.0 * p2.x - p3.x - 2.0 * p2.y - p3.y + 3.0) * sqrt_2 / 6.0,
-            (-p2.x - 2.0 * p3.x - p2.y - 2.0 * p3.y + 3.0) * sqrt_2 / 6.0,
-            0.0,
-            (2.0 * p2.x + p3.x) * sqrt_2 / 6.0,
-            (p2.x + 2.0 * p3.x) * sqrt_2 / 6.0,
-            0.0,
-            (2.0 * p2.y + p3.y) * sqrt_2 / 6.0,
-            (p2.y + 2.0 * p3.y) * sqrt_2 / 6.0,
-        ),
-        /* 0-edge (0,1) -> (0,0) */
-        2 => Matrix3::new(
-            (p1.x / 3.0 + p3.x / 6.0 + p1.y / 3.0 + p3.y / 6.0) - 0.5,
-            0.0,
-            (p1.x / 6.0 + p3.x / 3.0 + p1.y / 6.0 + p3.y / 3.0) - 0.5,
-            -(p1.x / 3.0 + p3.x / 6.0),
-            0.0,
-            -(p1.x / 6.0 + p3.x / 3.0),
-            -(p1.y / 3.0 + p3.y / 6.0),
-            0.0,
-            -(p1.y / 6.0 + p3.y / 3.0),
-        ),
-        _ => panic!("Not expecting edge"),
+    let integral = dirichlet_bilinear_penalty_basis(p1, p2, p3, &Basis::Nodal, edge_index);
+    return Matrix3::from_iterator(integral.iter().cloned());
+}
+
+/**
+ * Penalty of weakly imposed dirichlet values for an arbitrary basis
+ */
+pub fn dirichlet_bilinear_penalty_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    edge_index: usize,
+) -> DMatrix<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+
+    let rule = gauss_legendre::rule(2 * basis.degree());
+    let mut integral = DMatrix::<f64>::zeros(basis.size(), basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let phi = basis.evaluate(x, y);
+        integral += &phi * phi.transpose() * (weight * length);
     }
+    return integral;
 }
 
 /**
@@ -120,6 +103,196 @@ pub fn dirichlet_linear_penalty(
     dirichlet_bilinear_penalty(p1, p2, p3, edge_index) * Matrix3x1::new(u1, u2, u3)
 }
 
+/**
+ * Penalty term of weakly imposed dirichlet values for an arbitrary basis:
+ *  integral of phi_i * u
+ *  u is interpolated from the nodal values (u1, u2, u3)
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn dirichlet_linear_penalty_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    u1: f64,
+    u2: f64,
+    u3: f64,
+    edge_index: usize,
+) -> DVector<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let values = Matrix3x1::new(u1, u2, u3);
+
+    let rule = gauss_legendre::rule(basis.degree() + 1);
+    let mut integral = DVector::<f64>::zeros(basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let u = (utils::reference_basis(x, y) * values)[(0, 0)];
+        integral += basis.evaluate(x, y) * (u * weight * length);
+    }
+    return integral;
+}
+
+/**
+ * Natural term of weakly imposed dirichlet values given as a function g of the edge points:
+ *  integral of grad(phi_i) . n * g
+ */
+pub fn dirichlet_function_natural_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    value: &dyn Fn(&Point) -> f64,
+    edge_index: usize,
+) -> DVector<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let normal = utils::outer_normal(p1, p2, p3, edge_index);
+    let normal = DMatrix::from_row_slice(2, 1, &[normal[0], normal[1]]);
+
+    /* Values are not polynomial: two degrees beyond twice the basis */
+    let rule = gauss_legendre::rule(2 * basis.degree() + 2);
+    let mut integral = DVector::<f64>::zeros(basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let g = value(&utils::physical_point(p1, p2, p3, x, y));
+        let normal_derivative = basis.physical_gradient(p1, p2, p3, x, y) * &normal;
+        integral += normal_derivative.column(0) * (g * weight * length);
+    }
+    return integral;
+}
+
+/**
+ * Penalty term of weakly imposed dirichlet values given as a function g of the edge points:
+ *  integral of phi_i * g
+ */
+pub fn dirichlet_function_penalty_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    value: &dyn Fn(&Point) -> f64,
+    edge_index: usize,
+) -> DVector<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+
+    /* Values are not polynomial: two degrees beyond twice the basis */
+    let rule = gauss_legendre::rule(2 * basis.degree() + 2);
+    let mut integral = DVector::<f64>::zeros(basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let g = value(&utils::physical_point(p1, p2, p3, x, y));
+        integral += basis.evaluate(x, y) * (g * weight * length);
+    }
+    return integral;
+}
+
+/**
+ * Former sympy generated closed forms (scripts/integrands/dirichlet_natural_linear.py
+ * and scripts/integrands/dirichlet_weakly_bilinear.py), kept as regression reference
+ */
+#[cfg(test)]
+mod closed_form {
+    use super::*;
+
+    pub fn dirichlet_linear_natural(
+        p1: &Point,
+        p2: &Point,
+        p3: &Point,
+        u1: f64,
+        u2: f64,
+        u3: f64,
+        edge_index: usize,
+    ) -> Matrix3x1<f64> {
+        let a = match edge_index {
+            /* 0-edge (0,0) -> (1,0) */
+            0 => Matrix3::new(
+                (-p1.x + p3.x - p1.y + p3.y) / 2.0,
+                (-p1.x + p3.x - p1.y + p3.y) / 2.0,
+                0.0,
+                (p1.x - p3.x) / 2.0,
+                (p1.x - p3.x) / 2.0,
+                0.0,
+                (p1.y - p3.y) / 2.0,
+                (p1.y - p3.y) / 2.0,
+                0.0,
+            ),
+            /* 1-edge (1,0) -> (0,1) */
+            1 => Matrix3::new(
+                0.0,
+                p1.x - p2.x / 2.0 - p3.x / 2.0 + p1.y - p2.y / 2.0 - p3.y / 2.0,
+                p1.x - p2.x / 2.0 - p3.x / 2.0 + p1.y - p2.y / 2.0 - p3.y / 2.0,
+                0.0,
+                -p1.x + p2.x / 2.0 + p3.x / 2.0,
+                -p1.x + p2.x / 2.0 + p3.x / 2.0,
+                0.0,
+                -p1.y + p2.y / 2.0 + p3.y / 2.0,
+                -p1.y + p2.y / 2.0 + p3.y / 2.0,
+            ),
+            /* 0-edge (0,1) -> (0,0) */
+            2 => Matrix3::new(
+                (p1.x - p2.x + p1.y - p2.y) / 2.0,
+                0.0,
+                (p1.x - p2.x + p1.y - p2.y) / 2.0,
+                (-p1.x + p2.x) / 2.0,
+                0.0,
+                (-p1.x + p2.x) / 2.0,
+                (-p1.y + p2.y) / 2.0,
+                0.0,
+                (-p1.y + p2.y) / 2.0,
+            ),
+            _ => panic!("Not expecting edge"),
+        };
+        a * Matrix3x1::new(u1, u2, u3)
+    }
+
+    pub fn dirichlet_bilinear_penalty(
+        p1: &Point,
+        p2: &Point,
+        p3: &Point,
+        edge_index: usize,
+    ) -> Matrix3<f64> {
+        let sqrt_2 = 2.0_f64.sqrt();
+        match edge_index {
+            /* 0-edge (0,0) -> (1,0) */
+            0 => Matrix3::new(
+                -(p1.x / 3.0 + p2.x / 6.0 + p1.y / 3.0 + p2.y / 6.0) + 0.5,
+                -(p1.x / 6.0 + p2.x / 3.0 + p1.y / 6.0 + p2.y / 3.0) + 0.5,
+                0.0,
+                p1.x / 3.0 + p2.x / 6.0,
+                p1.x / 6.0 + p2.x / 3.0,
+                0.0,
+                p1.y / 3.0 + p2.y / 6.0,
+                p1.y / 6.0 + p2.y / 3.0,
+                0.0,
+            ),
+            /* 1-edge (1,0) -> (0,1) */
+            1 => Matrix3::new(
+                0.0,
+                (-2.0 * p2.x - p3.x - 2.0 * p2.y - p3.y + 3.0) * sqrt_2 / 6.0,
+                (-p2.x - 2.0 * p3.x - p2.y - 2.0 * p3.y + 3.0) * sqrt_2 / 6.0,
+                0.0,
+                (2.0 * p2.x + p3.x) * sqrt_2 / 6.0,
+                (p2.x + 2.0 * p3.x) * sqrt_2 / 6.0,
+                0.0,
+                (2.0 * p2.y + p3.y) * sqrt_2 / 6.0,
+                (p2.y + 2.0 * p3.y) * sqrt_2 / 6.0,
+            ),
+            /* 0-edge (0,1) -> (0,0) */
+            2 => Matrix3::new(
+                (p1.x / 3.0 + p3.x / 6.0 + p1.y / 3.0 + p3.y / 6.0) - 0.5,
+                0.0,
+                (p1.x / 6.0 + p3.x / 3.0 + p1.y / 6.0 + p3.y / 3.0) - 0.5,
+                -(p1.x / 3.0 + p3.x / 6.0),
+                0.0,
+                -(p1.x / 6.0 + p3.x / 3.0),
+                -(p1.y / 3.0 + p3.y / 6.0),
+                0.0,
+                -(p1.y / 6.0 + p3.y / 3.0),
+            ),
+            _ => panic!("Not expecting edge"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod linear_natural {
     use super::*;
@@ -226,19 +399,19 @@ mod linear_natural {
         fn solve(p1: &Rc<Point>, p2: &Rc<Point>, p3: &Rc<Point>, sigma: f64) -> DMatrix<f64> {
             /* Assembling system */
             let mut system_matrix = DMatrix::<f64>::zeros(3, 3);
-            system_matrix += dirichlet_bilinear_penalty(&p1, &p2, &p3, 0) * sigma;
-            system_matrix += dirichlet_bilinear_penalty(&p1, &p2, &p3, 1) * sigma;
-            system_matrix += dirichlet_bilinear_penalty(&p1, &p2, &p3, 2) * sigma;
+            system_matrix += dirichlet_bilinear_penalty(p1, p2, p3, 0) * sigma;
+            system_matrix += dirichlet_bilinear_penalty(p1, p2, p3, 1) * sigma;
+            system_matrix += dirichlet_bilinear_penalty(p1, p2, p3, 2) * sigma;
 
             /* Extern matrix */
             let mut extern_matrix = DMatrix::<f64>::zeros(3, 1);
-            extern_matrix += dirichlet_linear_penalty(&p1, &p2, &p3, 2.0, 0.0, 0.0, 0) * sigma;
-            extern_matrix += dirichlet_linear_penalty(&p1, &p2, &p3, 2.0, 0.0, 0.0, 1) * sigma;
-            extern_matrix += dirichlet_linear_penalty(&p1, &p2, &p3, 2.0, 0.0, 0.0, 2) * sigma;
+            extern_matrix += dirichlet_linear_penalty(p1, p2, p3, 2.0, 0.0, 0.0, 0) * sigma;
+            extern_matrix += dirichlet_linear_penalty(p1, p2, p3, 2.0, 0.0, 0.0, 1) * sigma;
+            extern_matrix += dirichlet_linear_penalty(p1, p2, p3, 2.0, 0.0, 0.0, 2) * sigma;
 
-            extern_matrix += dirichlet_linear_natural(&p1, &p2, &p3, 2.0, 0.0, 0.0, 0);
-            extern_matrix += dirichlet_linear_natural(&p1, &p2, &p3, 2.0, 0.0, 0.0, 1);
-            extern_matrix += dirichlet_linear_natural(&p1, &p2, &p3, 2.0, 0.0, 0.0, 2);
+            extern_matrix += dirichlet_linear_natural(p1, p2, p3, 2.0, 0.0, 0.0, 0);
+            extern_matrix += dirichlet_linear_natural(p1, p2, p3, 2.0, 0.0, 0.0, 1);
+            extern_matrix += dirichlet_linear_natural(p1, p2, p3, 2.0, 0.0, 0.0, 2);
 
             return system_matrix.try_inverse().unwrap() * extern_matrix;
         }
@@ -256,3 +429,32 @@ mod linear_natural {
         assert!(delta_3 < delta_2);
     }
 }
+
+#[cfg(test)]
+mod regression {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /*
+            Closed form matches at the default triangle edges 0 and 1.
+            Edge 2 was integrated from (0,1) to (0,0) with negative measure.
+        */
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let p3 = Point::new(0.0, 1.0);
+
+        for edge in 0..3 {
+            let sign = if edge == 2 { -1.0 } else { 1.0 };
+
+            let numerical = dirichlet_bilinear_penalty(&p1, &p2, &p3, edge);
+            let reference = closed_form::dirichlet_bilinear_penalty(&p1, &p2, &p3, edge);
+            assert!((numerical - sign * reference).norm() < 1.0E-14);
+
+            let numerical = dirichlet_linear_natural(&p1, &p2, &p3, 2.0, -1.0, 0.5, edge);
+            let reference =
+                closed_form::dirichlet_linear_natural(&p1, &p2, &p3, 2.0, -1.0, 0.5, edge);
+            assert!((numerical - sign * reference).norm() < 1.0E-14);
+        }
+    }
+}
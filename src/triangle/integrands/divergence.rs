@@ -0,0 +1,187 @@
+use crate::common::point::Point;
+use crate::triangle::{
+    basis::shape::Basis,
+    integrands::utils,
+    quadrature::{dunavant, gauss_legendre},
+};
+use nalgebra::{DMatrix, DVector, Matrix3x1};
+
+/**
+ * Divergence over the triangle: - integral of q div(v).
+ * Returns the blocks of the x and y velocity components,
+ * with rows on the pressure basis and columns on the velocity basis
+ */
+pub fn volume_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    velocity: &Basis,
+    pressure: &Basis,
+) -> (DMatrix<f64>, DMatrix<f64>) {
+    let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+
+    let rule = dunavant::rule(velocity.degree() + pressure.degree());
+    let mut bx = DMatrix::<f64>::zeros(pressure.size(), velocity.size());
+    let mut by = DMatrix::<f64>::zeros(pressure.size(), velocity.size());
+    for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let psi = pressure.evaluate(*x, *y);
+        let gradient = velocity.physical_gradient(p1, p2, p3, *x, *y);
+        bx -= &psi * gradient.column(0).transpose() * (weight * determinant);
+        by -= &psi * gradient.column(1).transpose() * (weight * determinant);
+    }
+    return (bx, by);
+}
+
+/**
+ * Normal velocity at the edge of triangle (p1, p2, p3): integral of q v . n,
+ * with q on the pressure basis of (p1, p2, p3) and v on the velocity basis
+ * of triangle (p4, p5, p6), evaluated at the same physical points.
+ * Returns the blocks of the x and y velocity components
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn normal_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    pressure: &Basis,
+    p4: &Point,
+    p5: &Point,
+    p6: &Point,
+    velocity: &Basis,
+    edge_index: usize,
+) -> (DMatrix<f64>, DMatrix<f64>) {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let normal = utils::outer_normal(p1, p2, p3, edge_index);
+
+    let rule = gauss_legendre::rule(pressure.degree() + velocity.degree());
+    let mut nx = DMatrix::<f64>::zeros(pressure.size(), velocity.size());
+    let mut ny = DMatrix::<f64>::zeros(pressure.size(), velocity.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let point = utils::physical_point(p1, p2, p3, x, y);
+        let product =
+            pressure.evaluate(x, y) * velocity.evaluate_at(p4, p5, p6, &point).transpose();
+        nx += &product * (normal[0] * weight * length);
+        ny += &product * (normal[1] * weight * length);
+    }
+    return (nx, ny);
+}
+
+/**
+ * Prescribed normal velocity at the edge of triangle (p1, p2, p3): integral of q g . n
+ *  g is interpolated from the nodal values of each component
+ */
+pub fn normal_linear_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    pressure: &Basis,
+    (gx1, gx2, gx3): (f64, f64, f64),
+    (gy1, gy2, gy3): (f64, f64, f64),
+    edge_index: usize,
+) -> DVector<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let normal = utils::outer_normal(p1, p2, p3, edge_index);
+    let (gx, gy) = (Matrix3x1::new(gx1, gx2, gx3), Matrix3x1::new(gy1, gy2, gy3));
+
+    let rule = gauss_legendre::rule(pressure.degree() + 1);
+    let mut integral = DVector::<f64>::zeros(pressure.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let linear = utils::reference_basis(x, y);
+        let gn = (linear * gx)[(0, 0)] * normal[0] + (linear * gy)[(0, 0)] * normal[1];
+        integral += pressure.evaluate(x, y) * (gn * weight * length);
+    }
+    return integral;
+}
+
+/**
+ * Prescribed normal velocity at the edge of triangle (p1, p2, p3): integral of q g . n
+ *  g is a function of the edge points
+ */
+pub fn normal_function_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    pressure: &Basis,
+    value: &dyn Fn(&Point) -> (f64, f64),
+    edge_index: usize,
+) -> DVector<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let normal = utils::outer_normal(p1, p2, p3, edge_index);
+
+    /* Values are not polynomial: two degrees beyond twice the basis */
+    let rule = gauss_legendre::rule(2 * pressure.degree() + 2);
+    let mut integral = DVector::<f64>::zeros(pressure.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let (gx, gy) = value(&utils::physical_point(p1, p2, p3, x, y));
+        let gn = gx * normal[0] + gy * normal[1];
+        integral += pressure.evaluate(x, y) * (gn * weight * length);
+    }
+    return integral;
+}
+
+#[cfg(test)]
+mod divergence_theorem {
+    use super::*;
+    use crate::triangle::basis::dubiner;
+
+    #[test]
+    fn sample_1() {
+        /*
+            Volume term plus the normal velocity over the edges vanish,
+            since the integral of grad(q) . v is zero for constant q
+            and divergence free v = (y, x)
+        */
+        let p1 = Point::new(2.0, 1.0);
+        let p2 = Point::new(3.0, 2.0);
+        let p3 = Point::new(2.0, 3.0);
+        let (velocity, pressure) = (Basis::Modal(2), Basis::Modal(0));
+        let vx = dubiner::linear_to_modal(2, p1.y, p2.y, p3.y);
+        let vy = dubiner::linear_to_modal(2, p1.x, p2.x, p3.x);
+
+        let (mut bx, mut by) = volume_basis(&p1, &p2, &p3, &velocity, &pressure);
+        for edge_index in 0..3 {
+            let (nx, ny) = normal_basis(
+                &p1, &p2, &p3, &pressure, &p1, &p2, &p3, &velocity, edge_index,
+            );
+            bx += nx;
+            by += ny;
+        }
+        assert!((bx * &vx + by * &vy).norm() < 1.0E-13);
+
+        /* Divergence of (x, 0) is one: - integral of q over the triangle */
+        let (bx, _) = volume_basis(&p1, &p2, &p3, &velocity, &pressure);
+        let ux = dubiner::linear_to_modal(2, p1.x, p2.x, p3.x);
+        let constant = (bx * ux)[0] / 2.0_f64.sqrt();
+        assert!((constant + 1.0).abs() < 1.0E-13);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Normal flux of the interpolated field equals the one of the basis field */
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let p3 = Point::new(0.0, 1.0);
+        let pressure = Basis::Modal(1);
+        for edge_index in 0..3 {
+            let (nx, ny) = normal_basis(
+                &p1,
+                &p2,
+                &p3,
+                &pressure,
+                &p1,
+                &p2,
+                &p3,
+                &Basis::Nodal,
+                edge_index,
+            );
+            let (gx, gy) = ((1.0, 2.0, 0.5), (0.0, -1.0, 3.0));
+            let expected = nx * DVector::from_vec(vec![1.0, 2.0, 0.5])
+                + ny * DVector::from_vec(vec![0.0, -1.0, 3.0]);
+            let integral = normal_linear_basis(&p1, &p2, &p3, &pressure, gx, gy, edge_index);
+            assert!((integral - expected).norm() < 1.0E-14);
+        }
+    }
+}
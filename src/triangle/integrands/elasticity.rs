@@ -0,0 +1,271 @@
+use crate::common::point::Point;
+use crate::triangle::{
+    basis::shape::Basis,
+    integrands::utils,
+    quadrature::{dunavant, gauss_legendre},
+};
+use nalgebra::{DMatrix, DVector, Matrix1x2, Matrix2x3, Matrix3, Matrix3x1};
+
+/*
+    Vector fields take the basis twice: the x component on the first
+    half of the local degrees of freedom, the y component on the second.
+    Strains and stresses use Voigt notation (xx, yy, xy), with the
+    engineering shear strain 2 eps_xy.
+*/
+
+/**
+ * Isotropic constitutive matrix: sigma = D eps, from Lamé parameters
+ */
+pub fn constitutive((lambda, mu): (f64, f64)) -> Matrix3<f64> {
+    return Matrix3::new(
+        lambda + 2.0 * mu,
+        lambda,
+        0.0,
+        lambda,
+        lambda + 2.0 * mu,
+        0.0,
+        0.0,
+        0.0,
+        mu,
+    );
+}
+
+/**
+ * Strain of every vector shape function, from the physical gradients (n x 2): 3 x 2n
+ */
+pub fn strain_basis(gradient: &DMatrix<f64>) -> DMatrix<f64> {
+    let size = gradient.nrows();
+    let mut strain = DMatrix::<f64>::zeros(3, 2 * size);
+    for i in 0..size {
+        strain[(0, i)] = gradient[(i, 0)];
+        strain[(1, size + i)] = gradient[(i, 1)];
+        strain[(2, i)] = gradient[(i, 1)];
+        strain[(2, size + i)] = gradient[(i, 0)];
+    }
+    return strain;
+}
+
+/**
+ * Values of every vector shape function: 2 x 2n
+ */
+fn value_basis(values: &DVector<f64>) -> DMatrix<f64> {
+    let size = values.len();
+    let mut value = DMatrix::<f64>::zeros(2, 2 * size);
+    for i in 0..size {
+        value[(0, i)] = values[i];
+        value[(1, size + i)] = values[i];
+    }
+    return value;
+}
+
+/**
+ * Maps Voigt stresses into the traction sigma n
+ */
+fn normal_projection(normal: &Matrix1x2<f64>) -> Matrix2x3<f64> {
+    return Matrix2x3::new(normal[0], 0.0, normal[1], 0.0, normal[1], normal[0]);
+}
+
+/**
+ * Strain energy over the triangle: integral of sigma(u) : eps(v)
+ */
+pub fn stiffness_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    lame: (f64, f64),
+) -> DMatrix<f64> {
+    let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+    let constitutive = constitutive(lame);
+
+    let rule = dunavant::rule(2 * basis.degree());
+    let mut integral = DMatrix::<f64>::zeros(2 * basis.size(), 2 * basis.size());
+    for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let strain = strain_basis(&basis.physical_gradient(p1, p2, p3, *x, *y));
+        integral += strain.transpose() * constitutive * &strain * (weight * determinant);
+    }
+    return integral;
+}
+
+/**
+ * Traction at the edge of triangle (p1, p2, p3): integral of v . sigma(u) n,
+ * with v on the basis of (p1, p2, p3), n its outer normal, and u on the basis
+ * of triangle (p4, p5, p6), evaluated at the same physical points
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn traction_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    left: &Basis,
+    p4: &Point,
+    p5: &Point,
+    p6: &Point,
+    right: &Basis,
+    edge_index: usize,
+    lame: (f64, f64),
+) -> DMatrix<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let traction =
+        normal_projection(&utils::outer_normal(p1, p2, p3, edge_index)) * constitutive(lame);
+
+    let rule = gauss_legendre::rule(left.degree() + right.degree());
+    let mut integral = DMatrix::<f64>::zeros(2 * left.size(), 2 * right.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let point = utils::physical_point(p1, p2, p3, x, y);
+        let value = value_basis(&left.evaluate(x, y));
+        let strain = strain_basis(&right.physical_gradient_at(p4, p5, p6, &point));
+        integral += value.transpose() * traction * strain * (weight * length);
+    }
+    return integral;
+}
+
+/**
+ * Traction of the test functions against prescribed displacements at the edge:
+ * integral of sigma(v) n . g
+ *  g is interpolated from the nodal values of each component
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn traction_linear_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    (gx1, gx2, gx3): (f64, f64, f64),
+    (gy1, gy2, gy3): (f64, f64, f64),
+    edge_index: usize,
+    lame: (f64, f64),
+) -> DVector<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let traction =
+        normal_projection(&utils::outer_normal(p1, p2, p3, edge_index)) * constitutive(lame);
+    let (gx, gy) = (Matrix3x1::new(gx1, gx2, gx3), Matrix3x1::new(gy1, gy2, gy3));
+
+    let rule = gauss_legendre::rule(basis.degree() + 1);
+    let mut integral = DVector::<f64>::zeros(2 * basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let linear = utils::reference_basis(x, y);
+        let g = DVector::from_vec(vec![(linear * gx)[(0, 0)], (linear * gy)[(0, 0)]]);
+        let strain = strain_basis(&basis.physical_gradient(p1, p2, p3, x, y));
+        integral += (traction * strain).transpose() * g * (weight * length);
+    }
+    return integral;
+}
+
+/**
+ * Mean stress over the triangle of the vector field of given coefficients
+ */
+pub fn mean_stress(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    coefficients: &DVector<f64>,
+    lame: (f64, f64),
+) -> Matrix3x1<f64> {
+    let constitutive = constitutive(lame);
+
+    let rule = dunavant::rule(basis.degree().max(1));
+    let mut stress = Matrix3x1::<f64>::zeros();
+    for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let strain = strain_basis(&basis.physical_gradient(p1, p2, p3, *x, *y)) * coefficients;
+        stress += constitutive * Matrix3x1::new(strain[0], strain[1], strain[2]) * (2.0 * weight);
+    }
+    return stress;
+}
+
+#[cfg(test)]
+mod rigid_motions {
+    use super::*;
+    use crate::triangle::basis::dubiner;
+
+    fn linear(basis: &Basis, u1: f64, u2: f64, u3: f64) -> DVector<f64> {
+        return match basis {
+            Basis::Nodal => DVector::from_vec(vec![u1, u2, u3]),
+            Basis::Modal(degree) => dubiner::linear_to_modal(*degree, u1, u2, u3),
+        };
+    }
+
+    fn stack(u: &DVector<f64>, v: &DVector<f64>) -> DVector<f64> {
+        return DVector::from_iterator(2 * u.len(), u.iter().chain(v.iter()).cloned());
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Translations and the infinitesimal rotation (-y, x) store no energy */
+        let p1 = Point::new(0.5, 0.0);
+        let p2 = Point::new(2.0, 1.0);
+        let p3 = Point::new(0.0, 1.5);
+        let lame = (2.0, 0.7);
+        for basis in [Basis::Nodal, Basis::Modal(2)].iter() {
+            let x = linear(basis, p1.x, p2.x, p3.x);
+            let y = linear(basis, p1.y, p2.y, p3.y);
+            let ones = linear(basis, 1.0, 1.0, 1.0);
+            let zeros = linear(basis, 0.0, 0.0, 0.0);
+
+            let stiffness = stiffness_basis(&p1, &p2, &p3, basis, lame);
+            for motion in [
+                stack(&ones, &zeros),
+                stack(&zeros, &ones),
+                stack(&(-&y), &x),
+            ]
+            .iter()
+            {
+                assert!((&stiffness * motion).norm() < 1.0E-13);
+            }
+
+            /* Uniform strain eps_xx = 1: energy is (lambda + 2 mu) times the area */
+            let stretch = stack(&x, &zeros);
+            let energy = (stretch.transpose() * &stiffness * &stretch)[(0, 0)];
+            assert!((energy - 3.4 * 1.375).abs() < 1.0E-12);
+
+            let stress = mean_stress(&p1, &p2, &p3, basis, &stretch, lame);
+            assert!((stress - Matrix3x1::new(3.4, 2.0, 0.0)).norm() < 1.0E-12);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            The field u = (y, x) has the pure shear stress sigma_xy = 2 mu:
+            its traction is 2 mu (ny, nx), and prescribing its values
+            matches the traction of the test functions against it
+        */
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let p3 = Point::new(0.0, 1.0);
+        let lame = (1.5, 0.5);
+        let basis = Basis::Modal(1);
+        let ux = linear(&basis, p1.y, p2.y, p3.y);
+        let uy = linear(&basis, p1.x, p2.x, p3.x);
+        let u = stack(&ux, &uy);
+        let constant = linear(&basis, 1.0, 1.0, 1.0);
+        let zeros = linear(&basis, 0.0, 0.0, 0.0);
+        for edge_index in 0..3 {
+            let normal = utils::outer_normal(&p1, &p2, &p3, edge_index);
+            let length = utils::edge_length(&p1, &p2, &p3, edge_index);
+            let traction = traction_basis(
+                &p1, &p2, &p3, &basis, &p1, &p2, &p3, &basis, edge_index, lame,
+            );
+
+            let tx = (stack(&constant, &zeros).transpose() * &traction * &u)[(0, 0)];
+            let ty = (stack(&zeros, &constant).transpose() * &traction * &u)[(0, 0)];
+            assert!((tx - normal[1] * length).abs() < 1.0E-14);
+            assert!((ty - normal[0] * length).abs() < 1.0E-14);
+
+            let prescribed = traction_linear_basis(
+                &p1,
+                &p2,
+                &p3,
+                &basis,
+                (p1.y, p2.y, p3.y),
+                (p1.x, p2.x, p3.x),
+                edge_index,
+                lame,
+            );
+            assert!((prescribed - traction.transpose() * &u).norm() < 1.0E-14);
+        }
+    }
+}
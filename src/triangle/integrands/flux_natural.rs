@@ -1,10 +1,11 @@
 use crate::common::point::Point;
-use crate::triangle::integrands::utils;
-use nalgebra::Matrix3;
+use crate::triangle::{basis::shape::Basis, integrands::utils, quadrature::gauss_legendre};
+use nalgebra::{DMatrix, Matrix3};
 
 /**
- * For the default L1 triangle, flux matrix only depends
- * on the points located at the interface
+ * Half of the natural flux at the edge of triangle (p1, p2, p3):
+ *  integral of phi_i * grad(psi_j) . n
+ *  phi_i are the basis of (p1, p2, p3), psi_j are the basis of (p4, p5, p6)
  */
 pub fn half_flux(
     p1: &Point,
@@ -15,51 +16,115 @@ pub fn half_flux(
     p6: &Point,
     edge_index: usize,
 ) -> Matrix3<f64> {
-    let foo = match edge_index {
-        0 => Matrix3::new(
-            0.0,
-            0.0,
-            (p1.x + p2.x + p1.y + p2.y) / 2.0 - 1.0,
-            0.0,
-            0.0,
-            -(p1.x + p2.x) / 2.0,
-            0.0,
-            0.0,
-            -(p1.y + p2.y) / 2.0,
-        ),
-        1 => Matrix3::new(
-            0.0,
-            -(p2.x + p3.x + p2.y + p3.y) / 2.0 + 1.0,
-            -(p2.x + p3.x + p2.y + p3.y) / 2.0 + 1.0,
-            0.0,
-            (p2.x + p3.x) / 2.0,
-            (p2.x + p3.x) / 2.0,
-            0.0,
-            (p2.y + p3.y) / 2.0,
-            (p2.y + p3.y) / 2.0,
-        ),
-        2 => Matrix3::new(
-            0.0,
-            -(p1.x + p3.x + p1.y + p3.y) / 2.0 + 1.0,
-            0.0,
-            0.0,
-            (p1.x + p3.x) / 2.0,
-            0.0,
-            0.0,
-            (p1.y + p3.y) / 2.0,
-            0.0,
-        ),
-        _ => panic!("Not expected to have edge index greater than 2 for triangle"),
-    };
-    return foo / 2.0
-        * utils::coordinante_transformation(p1, p2, p3).transpose()
-        * utils::field_transformation(p4, p5, p6)
-            .try_inverse()
-            .unwrap();
+    let integral = half_flux_basis(
+        p1,
+        p2,
+        p3,
+        &Basis::Nodal,
+        p4,
+        p5,
+        p6,
+        &Basis::Nodal,
+        edge_index,
+    );
+    return Matrix3::from_iterator(integral.iter().cloned());
+}
+
+/**
+ * Half of the natural flux between arbitrary bases of each triangle
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn half_flux_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    left: &Basis,
+    p4: &Point,
+    p5: &Point,
+    p6: &Point,
+    right: &Basis,
+    edge_index: usize,
+) -> DMatrix<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let normal = utils::outer_normal(p1, p2, p3, edge_index);
+    let normal = DMatrix::from_row_slice(2, 1, &[normal[0], normal[1]]);
+
+    let rule = gauss_legendre::rule((left.degree() + right.degree()).saturating_sub(1));
+    let mut integral = DMatrix::<f64>::zeros(left.size(), right.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let point = utils::physical_point(p1, p2, p3, x, y);
+        let phi = left.evaluate(x, y);
+        let normal_derivative = right.physical_gradient_at(p4, p5, p6, &point) * &normal;
+        integral += phi * normal_derivative.transpose() * (weight * length);
+    }
+    return integral / 2.0;
+}
+
+/**
+ * Former sympy generated closed form (scripts/integrands/flux_natural.py),
+ * kept as regression reference
+ */
+#[cfg(test)]
+mod closed_form {
+    use super::*;
+
+    pub fn half_flux(
+        p1: &Point,
+        p2: &Point,
+        p3: &Point,
+        p4: &Point,
+        p5: &Point,
+        p6: &Point,
+        edge_index: usize,
+    ) -> Matrix3<f64> {
+        let integral = match edge_index {
+            0 => Matrix3::new(
+                0.0,
+                0.0,
+                (p1.x + p2.x + p1.y + p2.y) / 2.0 - 1.0,
+                0.0,
+                0.0,
+                -(p1.x + p2.x) / 2.0,
+                0.0,
+                0.0,
+                -(p1.y + p2.y) / 2.0,
+            ),
+            1 => Matrix3::new(
+                0.0,
+                -(p2.x + p3.x + p2.y + p3.y) / 2.0 + 1.0,
+                -(p2.x + p3.x + p2.y + p3.y) / 2.0 + 1.0,
+                0.0,
+                (p2.x + p3.x) / 2.0,
+                (p2.x + p3.x) / 2.0,
+                0.0,
+                (p2.y + p3.y) / 2.0,
+                (p2.y + p3.y) / 2.0,
+            ),
+            2 => Matrix3::new(
+                0.0,
+                -(p1.x + p3.x + p1.y + p3.y) / 2.0 + 1.0,
+                0.0,
+                0.0,
+                (p1.x + p3.x) / 2.0,
+                0.0,
+                0.0,
+                (p1.y + p3.y) / 2.0,
+                0.0,
+            ),
+            _ => panic!("Not expected to have edge index greater than 2 for triangle"),
+        };
+        return integral / 2.0
+            * utils::coordinante_transformation(p1, p2, p3).transpose()
+            * utils::field_transformation(p4, p5, p6)
+                .try_inverse()
+                .unwrap();
+    }
 }
 
 #[cfg(test)]
 mod flux_matrix {
+    use super::closed_form::half_flux;
     use super::*;
 
     #[test]
@@ -108,3 +173,52 @@ mod flux_matrix {
         assert_eq!(edge_3[(2, 2)], 0.0);
     }
 }
+
+#[cfg(test)]
+mod regression {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /*
+            Closed form matches at the default triangle edges 0 and 1.
+            Edge 2 was integrated from (0,1) to (0,0) with negative measure.
+        */
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let p3 = Point::new(0.0, 1.0);
+
+        let p4 = Point::new(1.0, 1.0);
+        let p5 = Point::new(0.0, 1.0);
+        let p6 = Point::new(1.0, 0.0);
+
+        for (q1, q2, q3) in [(&p1, &p2, &p3), (&p4, &p5, &p6)] {
+            for edge in 0..2 {
+                let numerical = half_flux(&p1, &p2, &p3, q1, q2, q3, edge);
+                let reference = closed_form::half_flux(&p1, &p2, &p3, q1, q2, q3, edge);
+                assert!((numerical - reference).norm() < 1.0E-14);
+            }
+            let numerical = half_flux(&p1, &p2, &p3, q1, q2, q3, 2);
+            let reference = closed_form::half_flux(&p1, &p2, &p3, q1, q2, q3, 2);
+            assert!((numerical + reference).norm() < 1.0E-14);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Linear field u = x + 2y has normal derivative n . (1, 2) */
+        let p1 = Point::new(2.0, 1.0);
+        let p2 = Point::new(3.0, 2.0);
+        let p3 = Point::new(2.0, 3.0);
+
+        let u = nalgebra::Matrix3x1::new(p1.x + 2.0 * p1.y, p2.x + 2.0 * p2.y, p3.x + 2.0 * p3.y);
+        for edge in 0..3 {
+            let normal = utils::outer_normal(&p1, &p2, &p3, edge);
+            let length = utils::edge_length(&p1, &p2, &p3, edge);
+            let flux = half_flux(&p1, &p2, &p3, &p1, &p2, &p3, edge) * u;
+            let expected = (normal[0] + 2.0 * normal[1]) * length / 2.0;
+            /* Test functions sum up to one */
+            assert!((flux.sum() - expected).abs() < 1.0E-14);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::common::point::Point;
+use crate::triangle::{basis::shape::Basis, integrands::utils, quadrature::dunavant};
+
+use nalgebra::{DMatrix, Matrix3};
+
+/**
+ * Mass matrix: integral of phi_i * phi_j over the triangle
+ */
+pub fn matrix(p1: &Point, p2: &Point, p3: &Point) -> Matrix3<f64> {
+    return Matrix3::from_iterator(matrix_basis(p1, p2, p3, &Basis::Nodal).iter().cloned());
+}
+
+/**
+ * Mass matrix of an arbitrary basis over the triangle
+ */
+pub fn matrix_basis(p1: &Point, p2: &Point, p3: &Point, basis: &Basis) -> DMatrix<f64> {
+    let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+
+    let rule = dunavant::rule(2 * basis.degree());
+    let mut matrix = DMatrix::<f64>::zeros(basis.size(), basis.size());
+    for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let phi = basis.evaluate(*x, *y);
+        matrix += &phi * phi.transpose() * (weight * determinant);
+    }
+    return matrix;
+}
+
+/**
+ * Load vector of a source over the triangle: integral of f * phi_i
+ */
+pub fn source_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    source: &dyn Fn(&Point) -> f64,
+) -> DMatrix<f64> {
+    let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+
+    /* The source is not polynomial: two degrees beyond the basis */
+    let rule = dunavant::rule((basis.degree() + 2).min(dunavant::MAX_DEGREE));
+    let mut vector = DMatrix::<f64>::zeros(basis.size(), 1);
+    for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let f = source(&utils::physical_point(p1, p2, p3, *x, *y));
+        vector
+            .column_mut(0)
+            .axpy(f * weight * determinant, &basis.evaluate(*x, *y), 1.0);
+    }
+    return vector;
+}
+
+#[cfg(test)]
+mod l2_mass_matrix {
+    use super::*;
+    use crate::triangle::basis::dubiner;
+
+    #[test]
+    fn sample_1() {
+        /* Linear lagrangian mass: area / 12 * [2 1 1; 1 2 1; 1 1 2] */
+        let p1 = Point::new(2.0, 1.0);
+        let p2 = Point::new(3.0, 2.0);
+        let p3 = Point::new(2.0, 3.0);
+        let expected = Matrix3::new(2.0, 1.0, 1.0, 1.0, 2.0, 1.0, 1.0, 1.0, 2.0) / 12.0;
+        assert!((matrix(&p1, &p2, &p3) - expected).norm() < 1.0E-14);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Modal mass agrees with the diagonal Dubiner mass */
+        let p1 = Point::new(2.0, 1.0);
+        let p2 = Point::new(3.0, 2.0);
+        let p3 = Point::new(2.0, 3.0);
+        let numerical = matrix_basis(&p1, &p2, &p3, &Basis::Modal(3));
+        assert!((numerical - dubiner::mass_matrix(&p1, &p2, &p3, 3)).norm() < 1.0E-12);
+    }
+}
@@ -1,68 +1,100 @@
-extern crate nalgebra;
-
 use crate::common::point::Point;
+use crate::triangle::{basis::shape::Basis, integrands::utils, quadrature::dunavant};
 
-use nalgebra::{Matrix2, Matrix2x1, Matrix3};
+use nalgebra::{DMatrix, Matrix3};
 
+/**
+ * Stiffness matrix: integral of grad(phi_i) . grad(phi_j) over the triangle
+ */
 pub fn matrix(p1: &Point, p2: &Point, p3: &Point) -> Matrix3<f64> {
-    let jacobian = jacobian(&p1, &p2, &p3);
-    return Matrix3::new(
-        mass_ij(&jacobian, 0, 0),
-        mass_ij(&jacobian, 0, 1),
-        mass_ij(&jacobian, 0, 2),
-        mass_ij(&jacobian, 1, 0),
-        mass_ij(&jacobian, 1, 1),
-        mass_ij(&jacobian, 1, 2),
-        mass_ij(&jacobian, 2, 0),
-        mass_ij(&jacobian, 2, 1),
-        mass_ij(&jacobian, 2, 2),
-    );
+    return Matrix3::from_iterator(matrix_basis(p1, p2, p3, &Basis::Nodal).iter().cloned());
 }
 
-fn jacobian(p1: &Point, p2: &Point, p3: &Point) -> Matrix2<f64> {
-    return Matrix2::new(p2.x - p1.x, p3.x - p1.x, p2.y - p1.y, p3.y - p1.y);
+/**
+ * Stiffness matrix of an arbitrary basis over the triangle
+ */
+pub fn matrix_basis(p1: &Point, p2: &Point, p3: &Point, basis: &Basis) -> DMatrix<f64> {
+    let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+
+    let rule = dunavant::rule(2 * (basis.degree().max(1) - 1));
+    let mut matrix = DMatrix::<f64>::zeros(basis.size(), basis.size());
+    for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let gradient = basis.physical_gradient(p1, p2, p3, *x, *y);
+        matrix += &gradient * gradient.transpose() * (weight * determinant);
+    }
+    return matrix;
 }
 
-fn dphi(k: usize) -> Matrix2x1<f64> {
-    /*
-        Warning(!): hardcoded derivatives
-    */
-    match k {
-        0 /* phi(x,y) = 1-x-y */ => Matrix2x1::new( -1.0, -1.0),
-        1 /* phi(x,y) = x     */ => Matrix2x1::new( 1.0, 0.0),
-        2 /* phi(x,y) = y     */ => Matrix2x1::new( 0.0, 1.0),
-        _ => panic!("Not expected to request later polynomial"),
+/**
+ * Former sympy generated closed form (scripts/integrands/mass_matrix.py),
+ * kept as regression reference. It integrates over the unit square.
+ */
+#[cfg(test)]
+mod closed_form {
+    use super::*;
+    use nalgebra::{Matrix2, Matrix2x1};
+
+    pub fn matrix(p1: &Point, p2: &Point, p3: &Point) -> Matrix3<f64> {
+        let jacobian = jacobian(p1, p2, p3);
+        return Matrix3::new(
+            mass_ij(&jacobian, 0, 0),
+            mass_ij(&jacobian, 0, 1),
+            mass_ij(&jacobian, 0, 2),
+            mass_ij(&jacobian, 1, 0),
+            mass_ij(&jacobian, 1, 1),
+            mass_ij(&jacobian, 1, 2),
+            mass_ij(&jacobian, 2, 0),
+            mass_ij(&jacobian, 2, 1),
+            mass_ij(&jacobian, 2, 2),
+        );
+    }
+
+    pub fn jacobian(p1: &Point, p2: &Point, p3: &Point) -> Matrix2<f64> {
+        return Matrix2::new(p2.x - p1.x, p3.x - p1.x, p2.y - p1.y, p3.y - p1.y);
+    }
+
+    fn dphi(k: usize) -> Matrix2x1<f64> {
+        /*
+            Warning(!): hardcoded derivatives
+        */
+        match k {
+            0 /* phi(x,y) = 1-x-y */ => Matrix2x1::new( -1.0, -1.0),
+            1 /* phi(x,y) = x     */ => Matrix2x1::new( 1.0, 0.0),
+            2 /* phi(x,y) = y     */ => Matrix2x1::new( 0.0, 1.0),
+            _ => panic!("Not expected to request later polynomial"),
+        }
     }
-}
 
-fn mass_ij(jaco: &Matrix2<f64>, i: usize, j: usize) -> f64 {
-    let jaco_inv = jaco.try_inverse().unwrap();
+    pub fn mass_ij(jaco: &Matrix2<f64>, i: usize, j: usize) -> f64 {
+        let jaco_inv = jaco.try_inverse().unwrap();
 
-    let dphi_i = dphi(i);
-    let dphi_j = dphi(j);
+        let dphi_i = dphi(i);
+        let dphi_j = dphi(j);
 
-    let di_dx = dphi_i[0];
-    let di_dy = dphi_i[1];
-    let dj_dx = dphi_j[0];
-    let dj_dy = dphi_j[1];
+        let di_dx = dphi_i[0];
+        let di_dy = dphi_i[1];
+        let dj_dx = dphi_j[0];
+        let dj_dy = dphi_j[1];
 
-    let j11 = jaco_inv[(0, 0)]; /* 1,1 */
-    let j12 = jaco_inv[(0, 1)]; /* 1,2 */
-    let j21 = jaco_inv[(1, 0)]; /* 2,1 */
-    let j22 = jaco_inv[(1, 1)]; /* 2,2 */
+        let j11 = jaco_inv[(0, 0)]; /* 1,1 */
+        let j12 = jaco_inv[(0, 1)]; /* 1,2 */
+        let j21 = jaco_inv[(1, 0)]; /* 2,1 */
+        let j22 = jaco_inv[(1, 1)]; /* 2,2 */
 
-    let coef = j11 * j11 * di_dx * dj_dx
-        + j11 * j12 * (di_dy * dj_dx + di_dx * dj_dy)
-        + j12 * j12 * di_dy * dj_dy
-        + j21 * j21 * di_dx * dj_dx
-        + j21 * j22 * (di_dy * dj_dx + di_dx * dj_dy)
-        + j22 * j22 * di_dy * dj_dy;
+        let coef = j11 * j11 * di_dx * dj_dx
+            + j11 * j12 * (di_dy * dj_dx + di_dx * dj_dy)
+            + j12 * j12 * di_dy * dj_dy
+            + j21 * j21 * di_dx * dj_dx
+            + j21 * j22 * (di_dy * dj_dx + di_dx * dj_dy)
+            + j22 * j22 * di_dy * dj_dy;
 
-    return coef * jaco.determinant();
+        return coef * jaco.determinant();
+    }
 }
 
 #[cfg(test)]
 mod mass_matrix {
+    use super::closed_form::{jacobian, mass_ij};
     use super::*;
 
     #[test]
@@ -148,3 +180,48 @@ mod mass_matrix {
         ));
     }
 }
+
+#[cfg(test)]
+mod regression {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /* Closed form integrates over the unit square, twice the default triangle */
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let p3 = Point::new(0.0, 1.0);
+
+        let numerical = matrix(&p1, &p2, &p3);
+        let reference = closed_form::matrix(&p1, &p2, &p3);
+        assert!((reference - 2.0 * numerical).norm() < 1.0E-14);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Scaling and translation do not change the P1 stiffness */
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let p3 = Point::new(0.0, 1.0);
+
+        let q1 = Point::new(2.0, 3.0);
+        let q2 = Point::new(2.5, 3.0);
+        let q3 = Point::new(2.0, 3.5);
+
+        assert!((matrix(&p1, &p2, &p3) - matrix(&q1, &q2, &q3)).norm() < 1.0E-14);
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Rows sum up to zero, since constants are in the kernel */
+        let p1 = Point::new(2.0, 1.0);
+        let p2 = Point::new(3.0, 2.0);
+        let p3 = Point::new(2.0, 3.0);
+
+        let stiffness = matrix(&p1, &p2, &p3);
+        for row in 0..3 {
+            assert!(stiffness.row(row).sum().abs() < 1.0E-14);
+        }
+        assert!((stiffness - stiffness.transpose()).norm() < 1.0E-14);
+    }
+}
@@ -1,9 +1,11 @@
 use crate::common::point::Point;
-use nalgebra::{Matrix3, Matrix3x1};
+use crate::triangle::{basis::shape::Basis, integrands::utils, quadrature::gauss_legendre};
+use nalgebra::{DVector, Matrix3x1};
 
 /**
- * For the default L1 triangle, flux matrix only depends
- * on the points located at the interface
+ * Prescribed normal flux at the edge of triangle (p1, p2, p3):
+ *  integral of phi_i * g
+ *  g is interpolated from the nodal values (u1, u2, u3)
  */
 pub fn neumann(
     p1: &Point,
@@ -14,51 +16,102 @@ pub fn neumann(
     u3: f64,
     edge_index: usize,
 ) -> Matrix3x1<f64> {
-    let sqrt_2 = (2.0 as f64).sqrt();
-    let foo = match edge_index {
-        /* 0-edge (0,0) -> (1,0) */
-        0 => Matrix3::new(
-            -(p1.x / 3.0 + p2.x / 6.0 + p1.y / 3.0 + p2.y / 6.0) + 0.5,
-            -(p1.x / 6.0 + p2.x / 3.0 + p1.y / 6.0 + p2.y / 3.0) + 0.5,
-            0.0,
-            p1.x / 3.0 + p2.x / 6.0,
-            p1.x / 6.0 + p2.x / 3.0,
-            0.0,
-            p1.y / 3.0 + p2.y / 6.0,
-            p1.y / 6.0 + p2.y / 3.0,
-            0.0,
-        ),
-        /* 1-edge (1,0) -> (0,1) */
-        1 => Matrix3::new(
-            0.0,
-            (-2.0 * p2.x - p3.x - 2.0 * p2.y - p3.y + 3.0) * sqrt_2 / 6.0,
-            (-p2.x - 2.0 * p3.x - p2.y - 2.0 * p3.y + 3.0) * sqrt_2 / 6.0,
-            0.0,
-            (2.0 * p2.x + p3.x) * sqrt_2 / 6.0,
-            (p2.x + 2.0 * p3.x) * sqrt_2 / 6.0,
-            0.0,
-            (2.0 * p2.y + p3.y) * sqrt_2 / 6.0,
-            (p2.y + 2.0 * p3.y) * sqrt_2 / 6.0,
-        ),
-        /* 0-edge (0,1) -> (0,0) */
-        2 => Matrix3::new(
-            (p1.x / 3.0 + p3.x / 6.0 + p1.y / 3.0 + p3.y / 6.0) - 0.5,
-            0.0,
-            (p1.x / 6.0 + p3.x / 3.0 + p1.y / 6.0 + p3.y / 3.0) - 0.5,
-            -(p1.x / 3.0 + p3.x / 6.0),
-            0.0,
-            -(p1.x / 6.0 + p3.x / 3.0),
-            -(p1.y / 3.0 + p3.y / 6.0),
-            0.0,
-            -(p1.y / 6.0 + p3.y / 3.0),
-        ),
-        _ => panic!("Not expecting edge"),
-    };
-    return foo * Matrix3x1::new(u1, u2, u3);
+    let integral = neumann_basis(p1, p2, p3, &Basis::Nodal, u1, u2, u3, edge_index);
+    return Matrix3x1::from_iterator(integral.iter().cloned());
+}
+
+/**
+ * Prescribed normal flux for an arbitrary basis
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn neumann_basis(
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    basis: &Basis,
+    u1: f64,
+    u2: f64,
+    u3: f64,
+    edge_index: usize,
+) -> DVector<f64> {
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let values = Matrix3x1::new(u1, u2, u3);
+
+    let rule = gauss_legendre::rule(basis.degree() + 1);
+    let mut integral = DVector::<f64>::zeros(basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let g = (utils::reference_basis(x, y) * values)[(0, 0)];
+        integral += basis.evaluate(x, y) * (g * weight * length);
+    }
+    return integral;
+}
+
+/**
+ * Former sympy generated closed form (scripts/integrands/neumann_constraint.py),
+ * kept as regression reference
+ */
+#[cfg(test)]
+mod closed_form {
+    use super::*;
+    use nalgebra::Matrix3;
+
+    pub fn neumann(
+        p1: &Point,
+        p2: &Point,
+        p3: &Point,
+        u1: f64,
+        u2: f64,
+        u3: f64,
+        edge_index: usize,
+    ) -> Matrix3x1<f64> {
+        let sqrt_2 = 2.0_f64.sqrt();
+        let integral = match edge_index {
+            /* 0-edge (0,0) -> (1,0) */
+            0 => Matrix3::new(
+                -(p1.x / 3.0 + p2.x / 6.0 + p1.y / 3.0 + p2.y / 6.0) + 0.5,
+                -(p1.x / 6.0 + p2.x / 3.0 + p1.y / 6.0 + p2.y / 3.0) + 0.5,
+                0.0,
+                p1.x / 3.0 + p2.x / 6.0,
+                p1.x / 6.0 + p2.x / 3.0,
+                0.0,
+                p1.y / 3.0 + p2.y / 6.0,
+                p1.y / 6.0 + p2.y / 3.0,
+                0.0,
+            ),
+            /* 1-edge (1,0) -> (0,1) */
+            1 => Matrix3::new(
+                0.0,
+                (-2.0 * p2.x - p3.x - 2.0 * p2.y - p3.y + 3.0) * sqrt_2 / 6.0,
+                (-p2.x - 2.0 * p3.x - p2.y - 2.0 * p3.y + 3.0) * sqrt_2 / 6.0,
+                0.0,
+                (2.0 * p2.x + p3.x) * sqrt_2 / 6.0,
+                (p2.x + 2.0 * p3.x) * sqrt_2 / 6.0,
+                0.0,
+                (2.0 * p2.y + p3.y) * sqrt_2 / 6.0,
+                (p2.y + 2.0 * p3.y) * sqrt_2 / 6.0,
+            ),
+            /* 0-edge (0,1) -> (0,0) */
+            2 => Matrix3::new(
+                (p1.x / 3.0 + p3.x / 6.0 + p1.y / 3.0 + p3.y / 6.0) - 0.5,
+                0.0,
+                (p1.x / 6.0 + p3.x / 3.0 + p1.y / 6.0 + p3.y / 3.0) - 0.5,
+                -(p1.x / 3.0 + p3.x / 6.0),
+                0.0,
+                -(p1.x / 6.0 + p3.x / 3.0),
+                -(p1.y / 3.0 + p3.y / 6.0),
+                0.0,
+                -(p1.y / 6.0 + p3.y / 3.0),
+            ),
+            _ => panic!("Not expecting edge"),
+        };
+        integral * Matrix3x1::new(u1, u2, u3)
+    }
 }
 
 #[cfg(test)]
 mod neumann {
+    use super::closed_form::neumann;
     use super::*;
 
     #[test]
@@ -100,19 +153,19 @@ mod neumann {
         assert!(float_cmp::approx_eq!(
             f64,
             n2[(0, 0)],
-            -0.7071067811865476,
+            -std::f64::consts::FRAC_1_SQRT_2,
             epsilon = 1.0E-14f64
         ));
         assert!(float_cmp::approx_eq!(
             f64,
             n2[(1, 0)],
-            1.4142135623730951,
+            std::f64::consts::SQRT_2,
             epsilon = 1.0E-14f64
         ));
         assert!(float_cmp::approx_eq!(
             f64,
             n2[(2, 0)],
-            0.7071067811865476,
+            std::f64::consts::FRAC_1_SQRT_2,
             epsilon = 1.0E-14f64
         ));
 
@@ -136,3 +189,40 @@ mod neumann {
         ));
     }
 }
+
+#[cfg(test)]
+mod regression {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /*
+            Closed form matches at the default triangle edges 0 and 1.
+            Edge 2 was integrated from (0,1) to (0,0) with negative measure.
+        */
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let p3 = Point::new(0.0, 1.0);
+
+        for edge in 0..3 {
+            let sign = if edge == 2 { -1.0 } else { 1.0 };
+            let numerical = neumann(&p1, &p2, &p3, 2.0, -1.0, 0.5, edge);
+            let reference = closed_form::neumann(&p1, &p2, &p3, 2.0, -1.0, 0.5, edge);
+            assert!((numerical - sign * reference).norm() < 1.0E-14);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Unit flux integrates to the edge length */
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let p3 = Point::new(1.0, 1.0);
+
+        for edge in 0..3 {
+            let length = utils::edge_length(&p1, &p2, &p3, edge);
+            let flux = neumann(&p1, &p2, &p3, 1.0, 1.0, 1.0, edge);
+            assert!((flux.sum() - length).abs() < 1.0E-14);
+        }
+    }
+}
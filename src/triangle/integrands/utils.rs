@@ -1,5 +1,5 @@
 use crate::common::point::Point;
-use nalgebra::{Matrix1x2, Matrix3, Matrix3x1, Matrix3x2};
+use nalgebra::{Matrix1x2, Matrix1x3, Matrix2, Matrix3, Matrix3x1, Matrix3x2};
 
 /**
  * Calculates integrals:
@@ -11,9 +11,9 @@ pub fn elementary_intergral(n: usize) -> Matrix3x1<f64> {
     match n {
         0 => Matrix3x1::new(1.0, 0.5, 0.0),
         1 => Matrix3x1::new(
-            (2.0 as f64).sqrt(),
-            (2.0 as f64).sqrt() / 2.0,
-            (2.0 as f64).sqrt() / 2.0,
+            2.0_f64.sqrt(),
+            2.0_f64.sqrt() / 2.0,
+            2.0_f64.sqrt() / 2.0,
         ),
         2 => Matrix3x1::new(1.0, 0.0, -0.5),
         _ => panic!("Not expected integral definition greater than 2"),
@@ -71,8 +71,92 @@ pub fn grad() -> Matrix3x2<f64> {
 pub fn normal(n: usize) -> Matrix1x2<f64> {
     match n {
         0 => Matrix1x2::new(0.0, -1.0),
-        1 => Matrix1x2::new(1.0 / (2.0 as f64).sqrt(), 1.0 / (2.0 as f64).sqrt()),
+        1 => Matrix1x2::new(1.0 / 2.0_f64.sqrt(), 1.0 / 2.0_f64.sqrt()),
         2 => Matrix1x2::new(-1.0, 0.0),
         _ => panic!("Not expected normal with index greater than 2"),
     }
-}
\ No newline at end of file
+}
+/**
+ * Reference coordinates of the point with parameter l in [0, 1]
+ * along the edge n of the default triangle ((0,0),(1,0), (0,1))
+ *  0: (0,0) -> (1,0)
+ *  1: (1,0) -> (0,1)
+ *  2: (0,1) -> (0,0)
+ */
+pub fn edge_coordinates(n: usize, l: f64) -> (f64, f64) {
+    match n {
+        0 => (l, 0.0),
+        1 => (1.0 - l, l),
+        2 => (0.0, 1.0 - l),
+        _ => panic!("Not expected edge index greater than 2"),
+    }
+}
+
+/**
+ * Jacobian of the mapping from default triangle ((0,0),(1,0), (0,1))
+ */
+pub fn jacobian(p1: &Point, p2: &Point, p3: &Point) -> Matrix2<f64> {
+    Matrix2::new(p2.x - p1.x, p3.x - p1.x, p2.y - p1.y, p3.y - p1.y)
+}
+
+/**
+ * Maps reference coordinates into the triangle (p1, p2, p3)
+ */
+pub fn physical_point(p1: &Point, p2: &Point, p3: &Point, x: f64, y: f64) -> Point {
+    Point::new(
+        p1.x + (p2.x - p1.x) * x + (p3.x - p1.x) * y,
+        p1.y + (p2.y - p1.y) * x + (p3.y - p1.y) * y,
+    )
+}
+
+/**
+ * Reference coordinates of a physical point, given triangle (p1, p2, p3)
+ */
+pub fn reference_coordinates(p1: &Point, p2: &Point, p3: &Point, point: &Point) -> (f64, f64) {
+    let inverse = jacobian(p1, p2, p3).try_inverse().unwrap();
+    let reference = inverse * nalgebra::Vector2::new(point.x - p1.x, point.y - p1.y);
+    return (reference[0], reference[1]);
+}
+
+/**
+ * Nodal basis [phi_1, phi_2, phi_3] over default triangle at reference coordinates
+ */
+pub fn reference_basis(x: f64, y: f64) -> Matrix1x3<f64> {
+    (base_transformation() * Matrix3x1::new(1.0, x, y)).transpose()
+}
+
+/**
+ * Nodal basis [phi_1, phi_2, phi_3] of triangle (p1, p2, p3) at a physical point,
+ * which is not required to lie inside the triangle
+ */
+pub fn nodal_basis(p1: &Point, p2: &Point, p3: &Point, point: &Point) -> Matrix1x3<f64> {
+    Matrix1x3::new(1.0, point.x, point.y) * field_transformation(p1, p2, p3).try_inverse().unwrap()
+}
+
+/**
+ * Physical gradients of the nodal basis of triangle (p1, p2, p3), one per row
+ */
+pub fn nodal_gradient(p1: &Point, p2: &Point, p3: &Point) -> Matrix3x2<f64> {
+    grad() * jacobian(p1, p2, p3).try_inverse().unwrap()
+}
+
+/**
+ * Outer normal versor at edge n of triangle (p1, p2, p3)
+ */
+pub fn outer_normal(p1: &Point, p2: &Point, p3: &Point, n: usize) -> Matrix1x2<f64> {
+    let inverse = jacobian(p1, p2, p3).try_inverse().unwrap();
+    return (normal(n) * inverse).normalize();
+}
+
+/**
+ * Length of edge n of triangle (p1, p2, p3)
+ */
+pub fn edge_length(p1: &Point, p2: &Point, p3: &Point, n: usize) -> f64 {
+    let (a, b) = match n {
+        0 => (p1, p2),
+        1 => (p2, p3),
+        2 => (p3, p1),
+        _ => panic!("Not expected edge index greater than 2"),
+    };
+    return ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+}
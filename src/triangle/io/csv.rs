@@ -0,0 +1,53 @@
+use crate::triangle::post_processing::dg_solution::DgSolution;
+
+use std::io::{self, Write};
+
+/**
+ * Writes the solution as comma separated values, one row per vertex of each element,
+ * with the value of the element polynomial there: element, x, y, name
+ */
+pub fn write(writer: &mut dyn Write, solution: &DgSolution, name: &str) -> io::Result<()> {
+    writeln!(writer, "element,x,y,{}", name)?;
+    for (index, element) in solution.domain.elements.iter().enumerate() {
+        for point in [&element.p1, &element.p2, &element.p3].iter() {
+            writeln!(
+                writer,
+                "{},{:e},{:e},{:e}",
+                index,
+                point.x,
+                point.y,
+                solution.evaluate_in(element, point)
+            )?;
+        }
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod comma_separated {
+    use super::*;
+    use crate::triangle::{mesh, post_processing::projection, system_builder::domain::Domain};
+
+    #[test]
+    fn sample_1() {
+        /* Header and three rows per element, values read back */
+        let mut domain = Domain::new_empty();
+        mesh::rectangle((0.0, 0.0), (2.0, 1.0), 2, 1, &mut domain);
+        let coefficients = projection::interpolation(&domain, &|point| point.x * point.y);
+        let solution = DgSolution::new(&domain, &coefficients);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write(&mut buffer, &solution, "u").unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "element,x,y,u");
+        assert_eq!(lines.len(), 1 + 3 * 4);
+        for line in lines.iter().skip(1) {
+            let row: Vec<f64> = line
+                .split(',')
+                .map(|value| value.parse().unwrap())
+                .collect();
+            assert!((row[3] - row[1] * row[2]).abs() < 1.0E-12);
+        }
+    }
+}
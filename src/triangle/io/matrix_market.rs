@@ -0,0 +1,297 @@
+use nalgebra::DMatrix;
+
+use crate::common::error::Error;
+use crate::triangle::{basis::shape::Basis, system_builder::domain::Domain};
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+/**
+ * Origin of a global row: the element, by its position in the domain,
+ * and the local vertex for nodal bases, the local mode for modal ones
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RowOrigin {
+    pub element: usize,
+    pub local: usize,
+}
+
+/**
+ * Origin of every row of the systems built over the domain. Nodal rows follow
+ * the index mapping of (element, point), modal rows the offset of their element
+ */
+pub fn row_origins(domain: &Domain) -> Vec<RowOrigin> {
+    let positions: HashMap<_, usize> = domain
+        .elements
+        .iter()
+        .enumerate()
+        .map(|(position, element)| (element, position))
+        .collect();
+
+    let mut origins = vec![
+        RowOrigin {
+            element: 0,
+            local: 0
+        };
+        domain.size()
+    ];
+    for ((element, point), index) in domain.index_mapping.iter() {
+        let local = [&element.p1, &element.p2, &element.p3]
+            .iter()
+            .position(|vertex| *vertex == point)
+            .expect("Not expected point outside its element");
+        origins[*index] = RowOrigin {
+            element: positions[element],
+            local,
+        };
+    }
+    for element in domain.elements.iter() {
+        if domain.element_basis(element) != Basis::Nodal {
+            for (local, index) in domain.element_indexes(element).iter().enumerate() {
+                origins[*index] = RowOrigin {
+                    element: positions[element],
+                    local,
+                };
+            }
+        }
+    }
+    return origins;
+}
+
+/**
+ * Writes the matrix in Matrix Market coordinate format, nonzero entries only,
+ * with one comment line per row origin when given:
+ *  % row <row> element <element> local <local>
+ *  rows being one based, as the entries
+ */
+pub fn write(
+    writer: &mut dyn Write,
+    matrix: &DMatrix<f64>,
+    origins: Option<&[RowOrigin]>,
+) -> io::Result<()> {
+    writeln!(writer, "%%MatrixMarket matrix coordinate real general")?;
+    if let Some(origins) = origins {
+        for (row, origin) in origins.iter().enumerate() {
+            writeln!(
+                writer,
+                "% row {} element {} local {}",
+                row + 1,
+                origin.element,
+                origin.local
+            )?;
+        }
+    }
+
+    let entries: Vec<(usize, usize, f64)> = (0..matrix.ncols())
+        .flat_map(|col| (0..matrix.nrows()).map(move |row| (row, col)))
+        .map(|(row, col)| (row, col, matrix[(row, col)]))
+        .filter(|(_, _, value)| *value != 0.0)
+        .collect();
+    writeln!(
+        writer,
+        "{} {} {}",
+        matrix.nrows(),
+        matrix.ncols(),
+        entries.len()
+    )?;
+    for (row, col, value) in entries.iter() {
+        writeln!(writer, "{} {} {:e}", row + 1, col + 1, value)?;
+    }
+    return Ok(());
+}
+
+/**
+ * Reads a real or integer matrix in Matrix Market coordinate format, general
+ * or symmetric, with the row origins found in its comments. Repeated entries are summed.
+ *  Row origins, when present, must cover every row exactly once
+ */
+pub fn read(reader: &mut dyn BufRead) -> Result<(DMatrix<f64>, Vec<RowOrigin>), Error> {
+    let mut lines = reader.lines();
+    let header = lines
+        .next()
+        .ok_or(Error::Format)?
+        .map_err(|_| Error::Format)?
+        .to_lowercase();
+    let header: Vec<&str> = header.split_whitespace().collect();
+    match header.as_slice() {
+        ["%%matrixmarket", "matrix", "coordinate", "real", _]
+        | ["%%matrixmarket", "matrix", "coordinate", "integer", _] => {}
+        _ => return Err(Error::Format),
+    }
+    let symmetric = match header[4] {
+        "general" => false,
+        "symmetric" => true,
+        _ => return Err(Error::Format),
+    };
+
+    let mut origins: Vec<(usize, RowOrigin)> = Vec::new();
+    let mut matrix: Option<DMatrix<f64>> = None;
+    let mut remaining = 0;
+    for line in lines {
+        let line = line.map_err(|_| Error::Format)?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+        if words[0].starts_with('%') {
+            if let ["%", "row", row, "element", element, "local", local] = words.as_slice() {
+                let row: usize = parse(row)?;
+                origins.push((
+                    row.checked_sub(1).ok_or(Error::Format)?,
+                    RowOrigin {
+                        element: parse(element)?,
+                        local: parse(local)?,
+                    },
+                ));
+            }
+            continue;
+        }
+
+        match matrix.as_mut() {
+            None => {
+                if let [rows, cols, entries] = words.as_slice() {
+                    matrix = Some(DMatrix::zeros(parse(rows)?, parse(cols)?));
+                    remaining = parse(entries)?;
+                } else {
+                    return Err(Error::Format);
+                }
+            }
+            Some(matrix) => {
+                if remaining == 0 {
+                    return Err(Error::Format);
+                }
+                let (row, col, value): (usize, usize, f64) = match words.as_slice() {
+                    [row, col, value] => (parse(row)?, parse(col)?, parse(value)?),
+                    _ => return Err(Error::Format),
+                };
+                if row == 0 || col == 0 || row > matrix.nrows() || col > matrix.ncols() {
+                    return Err(Error::Format);
+                }
+                matrix[(row - 1, col - 1)] += value;
+                if symmetric && row != col {
+                    matrix[(col - 1, row - 1)] += value;
+                }
+                remaining -= 1;
+            }
+        }
+    }
+
+    let matrix = matrix.ok_or(Error::Format)?;
+    if remaining != 0 {
+        return Err(Error::Format);
+    }
+    origins.sort_by_key(|(row, _)| *row);
+    let complete = origins.len() == matrix.nrows()
+        && origins.iter().enumerate().all(|(k, (row, _))| *row == k);
+    if !origins.is_empty() && !complete {
+        return Err(Error::Format);
+    }
+    return Ok((
+        matrix,
+        origins.into_iter().map(|(_, origin)| origin).collect(),
+    ));
+}
+
+fn parse<T: std::str::FromStr>(word: &str) -> Result<T, Error> {
+    return word.parse().map_err(|_| Error::Format);
+}
+
+#[cfg(test)]
+mod coordinate_format {
+    use super::*;
+    use crate::triangle::{mesh, system_builder::builder};
+
+    #[test]
+    fn sample_1() {
+        /* Nodal system and right hand side round trip exactly, with their row origins */
+        let mut domain = Domain::new_empty();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 2, 2, &mut domain);
+        for edge in boundary.left.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![1.0, 0.5]);
+        }
+        let (system_matrix, extern_matrix) = builder::build(10.0, &domain);
+        let origins = row_origins(&domain);
+        assert_eq!(
+            origins[5],
+            RowOrigin {
+                element: 1,
+                local: 2
+            }
+        );
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write(&mut buffer, &system_matrix, Some(&origins)).unwrap();
+        let (matrix, read_origins) = read(&mut buffer.as_slice()).unwrap();
+        assert!(matrix == system_matrix);
+        assert_eq!(read_origins, origins);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write(&mut buffer, &extern_matrix, None).unwrap();
+        let (rhs, read_origins) = read(&mut buffer.as_slice()).unwrap();
+        assert!(rhs == extern_matrix);
+        assert!(read_origins.is_empty());
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Modal rows point to element modes; symmetric files fill both triangles */
+        let mut domain = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 1, 1, &mut domain);
+        let origins = row_origins(&domain);
+        assert_eq!(origins.len(), 12);
+        assert_eq!(
+            origins[7],
+            RowOrigin {
+                element: 1,
+                local: 1
+            }
+        );
+
+        let text = "%%MatrixMarket matrix coordinate real symmetric\n\
+                    % comment\n\
+                    3 3 4\n\
+                    1 1 2.0\n\
+                    2 1 -1.0\n\
+                    3 2 -1.5\n\
+                    3 3 4\n";
+        let (matrix, _) = read(&mut text.as_bytes()).unwrap();
+        assert!((matrix[(0, 1)] + 1.0).abs() < 1.0E-15);
+        assert!((matrix[(1, 2)] + 1.5).abs() < 1.0E-15);
+        assert!(matrix[(1, 1)].abs() < 1.0E-15);
+        assert!((matrix[(2, 2)] - 4.0).abs() < 1.0E-15);
+
+        assert!(
+            read(&mut "%%MatrixMarket matrix array real general\n1 1\n1.0\n".as_bytes()).is_err()
+        );
+        assert!(read(
+            &mut "%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1.0\n".as_bytes()
+        )
+        .is_err());
+        assert!(read(
+            &mut "%%MatrixMarket matrix coordinate real general\n2 2 1\n3 1 1.0\n".as_bytes()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Row origins with a gap or a duplicate are rejected */
+        let text = |rows: &[usize]| {
+            let mut text = String::from("%%MatrixMarket matrix coordinate real general\n");
+            for row in rows.iter() {
+                text.push_str(&format!("% row {} element 0 local {}\n", row, row - 1));
+            }
+            text.push_str("3 3 1\n1 1 1.0\n");
+            text
+        };
+        assert_eq!(read(&mut text(&[1, 2, 3]).as_bytes()).unwrap().1.len(), 3);
+        assert_eq!(
+            read(&mut text(&[3, 1, 2]).as_bytes()).unwrap().1[2].local,
+            2
+        );
+        assert!(read(&mut text(&[1, 3]).as_bytes()).is_err());
+        assert!(read(&mut text(&[1, 2, 2]).as_bytes()).is_err());
+        assert!(read(&mut text(&[1, 2, 2, 3]).as_bytes()).is_err());
+        assert!(read(&mut text(&[1, 2, 3, 4]).as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,432 @@
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{edge::Edge, error::Error, point::Point};
+use crate::triangle::{
+    basis::shape::Basis,
+    boundary_constraint::{BoundaryConstraint, RadiationConstraint},
+    element::TriangleElementL1,
+    system_builder::domain::Domain,
+};
+
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+/**
+ * Serializable form of a domain, its boundary tags and a solution vector.
+ *  The Rc graph is flattened into indexes: points follow the order of first appearance
+ *  over the elements, and elements keep the domain order, so that degrees of freedom
+ *  are numbered the same once restored
+ */
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct DomainArchive {
+    pub points: Vec<(f64, f64)>,
+    pub elements: Vec<ElementRecord>,
+    pub basis: Basis,
+    pub dirichlet_constraints: Vec<ConstraintRecord>,
+    pub neumann_constraints: Vec<ConstraintRecord>,
+    pub radiation_constraints: Vec<RadiationRecord>,
+    /* Named sets of boundary edges, each edge as its pair of point indexes */
+    pub tags: BTreeMap<String, Vec<(usize, usize)>>,
+    pub solution: Option<Vec<f64>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ElementRecord {
+    pub points: (usize, usize, usize),
+    pub basis: Basis,
+}
+
+/**
+ * Constraint over the edge of an element, values given at point indexes
+ */
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ConstraintRecord {
+    pub element: usize,
+    pub edge: (usize, usize),
+    pub values: Vec<(usize, f64)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct RadiationRecord {
+    pub element: usize,
+    pub edge: (usize, usize),
+    pub emissivity: f64,
+    pub ambient: Vec<(usize, f64)>,
+}
+
+impl DomainArchive {
+    pub fn new(domain: &Domain) -> Self {
+        let mut points: Vec<(f64, f64)> = Vec::new();
+        let mut point_mapping: HashMap<Rc<Point>, usize> = HashMap::new();
+        let mut element_mapping: HashMap<Rc<TriangleElementL1>, usize> = HashMap::new();
+        let mut elements: Vec<ElementRecord> = Vec::new();
+
+        for (index, element) in domain.elements.iter().enumerate() {
+            let mut point_index = |point: &Rc<Point>| {
+                *point_mapping.entry(Rc::clone(point)).or_insert_with(|| {
+                    points.push((point.x, point.y));
+                    points.len() - 1
+                })
+            };
+            elements.push(ElementRecord {
+                points: (
+                    point_index(&element.p1),
+                    point_index(&element.p2),
+                    point_index(&element.p3),
+                ),
+                basis: domain.element_basis(element),
+            });
+            element_mapping.insert(Rc::clone(element), index);
+        }
+
+        let edge_record = |edge: &Edge| (point_mapping[&edge.p1], point_mapping[&edge.p2]);
+        let values_record = |values: &HashMap<Rc<Point>, f64>| {
+            let mut values: Vec<(usize, f64)> = values
+                .iter()
+                .map(|(point, value)| (point_mapping[point], *value))
+                .collect();
+            values.sort_by_key(|(index, _)| *index);
+            values
+        };
+        let constraint_record = |constraint: &Rc<BoundaryConstraint>| ConstraintRecord {
+            element: element_mapping[&constraint.element],
+            edge: edge_record(&constraint.boundary_edge),
+            values: values_record(&constraint.values),
+        };
+
+        return DomainArchive {
+            dirichlet_constraints: domain
+                .dirichlet_constraints
+                .iter()
+                .map(constraint_record)
+                .collect(),
+            neumann_constraints: domain
+                .neumann_constraints
+                .iter()
+                .map(constraint_record)
+                .collect(),
+            radiation_constraints: domain
+                .radiation_constraints
+                .iter()
+                .map(|constraint| RadiationRecord {
+                    element: element_mapping[&constraint.element],
+                    edge: edge_record(&constraint.boundary_edge),
+                    emissivity: constraint.emissivity,
+                    ambient: values_record(&constraint.ambient),
+                })
+                .collect(),
+            points,
+            elements,
+            basis: domain.basis(),
+            tags: BTreeMap::new(),
+            solution: None,
+        };
+    }
+
+    /**
+     * Names a set of edges of the domain. Fails if an edge point is not a domain point
+     */
+    pub fn with_tag(mut self, name: &str, edges: &[Rc<Edge>]) -> Result<Self, Error> {
+        let point_mapping: HashMap<(u64, u64), usize> = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(index, (x, y))| ((x.to_bits(), y.to_bits()), index))
+            .collect();
+        let point_index =
+            |point: &Point| point_mapping.get(&(point.x.to_bits(), point.y.to_bits()));
+
+        let mut records = Vec::new();
+        for edge in edges.iter() {
+            match (point_index(&edge.p1), point_index(&edge.p2)) {
+                (Some(i), Some(j)) => records.push((*i, *j)),
+                _ => return Err(Error::Format),
+            }
+        }
+        self.tags.insert(name.to_string(), records);
+        return Ok(self);
+    }
+
+    pub fn with_solution(mut self, solution: &DMatrix<f64>) -> Self {
+        self.solution = Some(solution.column(0).iter().cloned().collect());
+        return self;
+    }
+
+    /**
+     * Restores the domain, its elements sharing the points of the archive.
+     * Fails on indexes out of range, constraint edges outside their element
+     * or a solution of size different from the domain
+     */
+    pub fn domain(&self) -> Result<Domain, Error> {
+        let points = self.shared_points();
+        let point = |index: usize| points.get(index).map(Rc::clone).ok_or(Error::Format);
+
+        let mut domain = match self.basis {
+            Basis::Modal(degree) => Domain::new_modal(degree)?,
+            Basis::Nodal => Domain::new_empty(),
+        };
+        for record in self.elements.iter() {
+            let (i, j, k) = record.points;
+            let element = Rc::new(TriangleElementL1::new(&point(i)?, &point(j)?, &point(k)?));
+            domain.insert_element_with_basis(&element, record.basis)?;
+        }
+
+        let element = |index: usize| {
+            domain
+                .elements
+                .get(index)
+                .map(Rc::clone)
+                .ok_or(Error::Format)
+        };
+        /* Edge of the element, as the constraint builders expect */
+        let edge = |element: &TriangleElementL1, (i, j): (usize, usize)| {
+            let edge = Edge::new(&point(i)?, &point(j)?);
+            element.edge_index(&edge).map_err(|_| Error::Format)?;
+            return Ok(Rc::new(edge));
+        };
+        let values = |records: &[(usize, f64)]| -> Result<HashMap<Rc<Point>, f64>, Error> {
+            let mut values = HashMap::new();
+            for (index, value) in records.iter() {
+                values.insert(point(*index)?, *value);
+            }
+            return Ok(values);
+        };
+        let constraint = |record: &ConstraintRecord| -> Result<Rc<BoundaryConstraint>, Error> {
+            let element = element(record.element)?;
+            return Ok(Rc::new(BoundaryConstraint {
+                boundary_edge: edge(&element, record.edge)?,
+                element,
+                values: values(&record.values)?,
+            }));
+        };
+
+        let dirichlet_constraints = self
+            .dirichlet_constraints
+            .iter()
+            .map(constraint)
+            .collect::<Result<Vec<_>, Error>>()?;
+        let neumann_constraints = self
+            .neumann_constraints
+            .iter()
+            .map(constraint)
+            .collect::<Result<Vec<_>, Error>>()?;
+        let radiation_constraints = self
+            .radiation_constraints
+            .iter()
+            .map(|record| {
+                let element = element(record.element)?;
+                Ok(Rc::new(RadiationConstraint {
+                    boundary_edge: edge(&element, record.edge)?,
+                    element,
+                    emissivity: record.emissivity,
+                    ambient: values(&record.ambient)?,
+                }))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        if let Some(solution) = self.solution.as_ref() {
+            if solution.len() != domain.size() {
+                return Err(Error::Format);
+            }
+        }
+
+        domain.dirichlet_constraints = dirichlet_constraints;
+        domain.neumann_constraints = neumann_constraints;
+        domain.radiation_constraints = radiation_constraints;
+        return Ok(domain);
+    }
+
+    /**
+     * Edges of the named tag, over the points of the restored domain.
+     * None if the tag is missing or an edge point is not a domain point
+     */
+    pub fn tag(&self, domain: &Domain, name: &str) -> Option<Vec<Rc<Edge>>> {
+        let mut points: HashMap<(u64, u64), Rc<Point>> = HashMap::new();
+        for element in domain.elements.iter() {
+            for point in [&element.p1, &element.p2, &element.p3].iter() {
+                points.insert((point.x.to_bits(), point.y.to_bits()), Rc::clone(point));
+            }
+        }
+        let point = |index: usize| {
+            self.points
+                .get(index)
+                .and_then(|(x, y)| points.get(&(x.to_bits(), y.to_bits())))
+        };
+        return self.tags.get(name).and_then(|edges| {
+            edges
+                .iter()
+                .map(|(i, j)| match (point(*i), point(*j)) {
+                    (Some(p1), Some(p2)) => Some(Rc::new(Edge::new(p1, p2))),
+                    _ => None,
+                })
+                .collect()
+        });
+    }
+
+    /**
+     * Solution vector as a column matrix
+     */
+    pub fn solution(&self) -> Option<DMatrix<f64>> {
+        return self
+            .solution
+            .as_ref()
+            .map(|values| DMatrix::from_column_slice(values.len(), 1, values));
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        return serde_json::to_string(self).map_err(|_| Error::Format);
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        return serde_json::from_str(json).map_err(|_| Error::Format);
+    }
+
+    /**
+     * Compact binary form, little endian with fixed size integers
+     */
+    pub fn to_binary(&self) -> Result<Vec<u8>, Error> {
+        return bincode::serialize(self).map_err(|_| Error::Format);
+    }
+
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, Error> {
+        return bincode::deserialize(bytes).map_err(|_| Error::Format);
+    }
+
+    fn shared_points(&self) -> Vec<Rc<Point>> {
+        return self
+            .points
+            .iter()
+            .map(|(x, y)| Rc::new(Point::new(*x, *y)))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod round_trip {
+    use super::*;
+    use crate::triangle::{mesh, system_builder::builder};
+
+    fn sample_domain() -> (Domain, mesh::RectangleBoundary) {
+        let mut domain = Domain::new_modal(1).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (2.0, 1.0), 3, 2, &mut domain);
+        let element = Rc::clone(&domain.elements[4]);
+        domain.set_element_degree(&element, 3).unwrap();
+        for edge in boundary.left.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![1.0, 2.0]);
+        }
+        for edge in boundary.right.iter() {
+            domain.insert_neumann_constraint(edge, vec![-0.5, 0.25]);
+        }
+        for edge in boundary.top.iter() {
+            domain.insert_radiation_constraint(edge, 0.8, vec![300.0, 310.0]);
+        }
+        return (domain, boundary);
+    }
+
+    #[test]
+    fn sample_1() {
+        /* JSON and binary forms restore the archive, tags and solution included */
+        let (domain, boundary) = sample_domain();
+        let (system_matrix, extern_matrix) = builder::build(10.0, &domain);
+        let solution = system_matrix.lu().solve(&extern_matrix).unwrap();
+        let archive = DomainArchive::new(&domain)
+            .with_tag("inlet", &boundary.left)
+            .unwrap()
+            .with_tag("wall", &boundary.top)
+            .unwrap()
+            .with_solution(&solution);
+        assert_eq!(archive.points.len(), 12);
+
+        let json = DomainArchive::from_json(&archive.to_json().unwrap()).unwrap();
+        let binary = DomainArchive::from_binary(&archive.to_binary().unwrap()).unwrap();
+        assert!(json == archive);
+        assert!(binary == archive);
+        assert!(DomainArchive::from_json("{\"points\": []}").is_err());
+        assert!(DomainArchive::from_binary(&[1, 2, 3]).is_err());
+
+        /* Edges made of points outside the domain are not tagged */
+        let outer = Rc::new(Edge::new(
+            &Rc::new(Point::new(5.0, 5.0)),
+            &Rc::new(Point::new(6.0, 5.0)),
+        ));
+        assert!(DomainArchive::new(&domain)
+            .with_tag("outer", &[outer])
+            .is_err());
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Restored domain numbers its degrees of freedom alike and assembles the same system */
+        let (domain, boundary) = sample_domain();
+        let (system_matrix, extern_matrix) = builder::build(10.0, &domain);
+        let solution = system_matrix.clone().lu().solve(&extern_matrix).unwrap();
+        let archive = DomainArchive::new(&domain)
+            .with_tag("inlet", &boundary.left)
+            .unwrap()
+            .with_solution(&solution);
+        let archive = DomainArchive::from_binary(&archive.to_binary().unwrap()).unwrap();
+
+        let restored = archive.domain().unwrap();
+        assert_eq!(restored.size(), domain.size());
+        assert_eq!(restored.dirichlet_constraints.len(), 2);
+        assert_eq!(restored.neumann_constraints.len(), 2);
+        assert_eq!(restored.radiation_constraints.len(), 3);
+        assert!((restored.radiation_constraints[1].emissivity - 0.8).abs() < 1.0E-15);
+        for (element, original) in restored.elements.iter().zip(domain.elements.iter()) {
+            assert!(element == original);
+            assert_eq!(
+                restored.element_indexes(element),
+                domain.element_indexes(original)
+            );
+            assert_eq!(
+                restored.element_basis(element),
+                domain.element_basis(original)
+            );
+        }
+
+        let (restored_matrix, restored_extern) = builder::build(10.0, &restored);
+        assert!((&restored_matrix - &system_matrix).amax() < 1.0E-14);
+        assert!((&restored_extern - &extern_matrix).amax() < 1.0E-14);
+        assert!((archive.solution().unwrap() - &solution).amax() < 1.0E-15);
+
+        let inlet = archive.tag(&restored, "inlet").unwrap();
+        assert_eq!(inlet.len(), 2);
+        for (edge, original) in inlet.iter().zip(boundary.left.iter()) {
+            assert!(**edge == **original);
+            assert!(restored.adjacency.contains_key(edge));
+        }
+        assert!(archive.tag(&restored, "outlet").is_none());
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Malformed archives are rejected rather than restored */
+        let (domain, boundary) = sample_domain();
+        let archive = || {
+            DomainArchive::new(&domain)
+                .with_tag("inlet", &boundary.left)
+                .unwrap()
+        };
+
+        let mut outside = archive();
+        outside.tags.insert("outside".to_string(), vec![(0, 99)]);
+        let restored = outside.domain().unwrap();
+        assert!(outside.tag(&restored, "outside").is_none());
+
+        let mut moved = archive();
+        let record = &mut moved.dirichlet_constraints[0];
+        record.element = (record.element + 1) % domain.elements.len();
+        assert_eq!(moved.domain().err(), Some(Error::Format));
+
+        let mut reversed = archive();
+        let record = &mut reversed.radiation_constraints[0];
+        record.edge = (record.edge.1, record.edge.0);
+        assert_eq!(reversed.domain().err(), Some(Error::Format));
+
+        let short = archive().with_solution(&DMatrix::zeros(domain.size() - 1, 1));
+        assert_eq!(short.domain().err(), Some(Error::Format));
+        let exact = archive().with_solution(&DMatrix::zeros(domain.size(), 1));
+        assert!(exact.domain().is_ok());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::triangle::post_processing::dg_solution::DgSolution;
+
+use std::io::{self, Write};
+
+/* VTK cell type of the linear triangle */
+const VTK_TRIANGLE: u8 = 5;
+
+/**
+ * Writes the solution as an ASCII VTK unstructured grid. Every element takes its own
+ * copy of its vertices, so that the field keeps its jumps across edges, with the value
+ * of the element polynomial at each of them. The element degree goes as cell data
+ */
+pub fn write(writer: &mut dyn Write, solution: &DgSolution, name: &str) -> io::Result<()> {
+    let domain = solution.domain;
+    let cells = domain.elements.len();
+
+    writeln!(writer, "<?xml version=\"1.0\"?>")?;
+    writeln!(
+        writer,
+        "<VTKFile type=\"UnstructuredGrid\" version=\"0.1\" byte_order=\"LittleEndian\">"
+    )?;
+    writeln!(writer, "  <UnstructuredGrid>")?;
+    writeln!(
+        writer,
+        "    <Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">",
+        3 * cells,
+        cells
+    )?;
+
+    writeln!(writer, "      <Points>")?;
+    writeln!(
+        writer,
+        "        <DataArray type=\"Float64\" NumberOfComponents=\"3\" format=\"ascii\">"
+    )?;
+    for element in domain.elements.iter() {
+        for point in [&element.p1, &element.p2, &element.p3].iter() {
+            writeln!(writer, "          {:e} {:e} 0", point.x, point.y)?;
+        }
+    }
+    writeln!(writer, "        </DataArray>")?;
+    writeln!(writer, "      </Points>")?;
+
+    writeln!(writer, "      <Cells>")?;
+    writeln!(
+        writer,
+        "        <DataArray type=\"Int64\" Name=\"connectivity\" format=\"ascii\">"
+    )?;
+    for index in 0..cells {
+        writeln!(
+            writer,
+            "          {} {} {}",
+            3 * index,
+            3 * index + 1,
+            3 * index + 2
+        )?;
+    }
+    writeln!(writer, "        </DataArray>")?;
+    writeln!(
+        writer,
+        "        <DataArray type=\"Int64\" Name=\"offsets\" format=\"ascii\">"
+    )?;
+    for index in 0..cells {
+        writeln!(writer, "          {}", 3 * (index + 1))?;
+    }
+    writeln!(writer, "        </DataArray>")?;
+    writeln!(
+        writer,
+        "        <DataArray type=\"UInt8\" Name=\"types\" format=\"ascii\">"
+    )?;
+    for _ in 0..cells {
+        writeln!(writer, "          {}", VTK_TRIANGLE)?;
+    }
+    writeln!(writer, "        </DataArray>")?;
+    writeln!(writer, "      </Cells>")?;
+
+    writeln!(writer, "      <PointData Scalars=\"{}\">", name)?;
+    writeln!(
+        writer,
+        "        <DataArray type=\"Float64\" Name=\"{}\" format=\"ascii\">",
+        name
+    )?;
+    for element in domain.elements.iter() {
+        for point in [&element.p1, &element.p2, &element.p3].iter() {
+            writeln!(
+                writer,
+                "          {:e}",
+                solution.evaluate_in(element, point)
+            )?;
+        }
+    }
+    writeln!(writer, "        </DataArray>")?;
+    writeln!(writer, "      </PointData>")?;
+
+    writeln!(writer, "      <CellData>")?;
+    writeln!(
+        writer,
+        "        <DataArray type=\"Int32\" Name=\"degree\" format=\"ascii\">"
+    )?;
+    for element in domain.elements.iter() {
+        writeln!(
+            writer,
+            "          {}",
+            domain.element_basis(element).degree()
+        )?;
+    }
+    writeln!(writer, "        </DataArray>")?;
+    writeln!(writer, "      </CellData>")?;
+
+    writeln!(writer, "    </Piece>")?;
+    writeln!(writer, "  </UnstructuredGrid>")?;
+    writeln!(writer, "</VTKFile>")?;
+    return Ok(());
+}
+
+#[cfg(test)]
+mod unstructured_grid {
+    use super::*;
+    use crate::triangle::{mesh, post_processing::projection, system_builder::domain::Domain};
+
+    #[test]
+    fn sample_1() {
+        /* Two triangles of the unit square: six point copies, with values at each */
+        let mut domain = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 1, 1, &mut domain);
+        let coefficients = projection::interpolation(&domain, &|point| point.x + 2.0 * point.y);
+        let solution = DgSolution::new(&domain, &coefficients);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write(&mut buffer, &solution, "temperature").unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(text.contains("<Piece NumberOfPoints=\"6\" NumberOfCells=\"2\">"));
+        assert!(text.contains("Name=\"temperature\""));
+
+        /* Second element (0,1), (1,0), (1,1): values 2, 1 and 3 */
+        let start = text.find("Name=\"temperature\"").unwrap();
+        let values: Vec<f64> = text[start..]
+            .lines()
+            .skip(1)
+            .take(6)
+            .map(|line| line.trim().parse().unwrap())
+            .collect();
+        for (value, expected) in values[3..].iter().zip([2.0, 1.0, 3.0].iter()) {
+            assert!((value - expected).abs() < 1.0E-12);
+        }
+        assert_eq!(text.matches("          5\n").count(), 2);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::common::{edge::Edge, point::Point};
+use crate::triangle::{element::TriangleElementL1, system_builder::domain::Domain};
+
+use std::rc::Rc;
+
+/**
+ * Boundary edges of a rectangle, oriented counterclockwise,
+ * as found at the adjacency of its elements
+ */
+pub struct RectangleBoundary {
+    pub left: Vec<Rc<Edge>>,
+    pub right: Vec<Rc<Edge>>,
+    pub bottom: Vec<Rc<Edge>>,
+    pub top: Vec<Rc<Edge>>,
+}
+
+/**
+ * Inserts into the domain the rectangle (x0, y0) - (x1, y1)
+ * split into nx by ny cells of two triangles each
+ */
+pub fn rectangle(
+    (x0, y0): (f64, f64),
+    (x1, y1): (f64, f64),
+    nx: usize,
+    ny: usize,
+    domain: &mut Domain,
+) -> RectangleBoundary {
+    let (hx, hy) = ((x1 - x0) / nx as f64, (y1 - y0) / ny as f64);
+    let mut points: Vec<Vec<Rc<Point>>> = Vec::new();
+    for i in 0..=nx {
+        points.push(
+            (0..=ny)
+                .map(|j| Rc::new(Point::new(x0 + i as f64 * hx, y0 + j as f64 * hy)))
+                .collect(),
+        );
+    }
+
+    for i in 0..nx {
+        for j in 0..ny {
+            let (a, b) = (&points[i][j], &points[i + 1][j]);
+            let (c, d) = (&points[i + 1][j + 1], &points[i][j + 1]);
+            domain.insert_element(&Rc::new(TriangleElementL1::new(a, b, d)));
+            domain.insert_element(&Rc::new(TriangleElementL1::new(d, b, c)));
+        }
+    }
+
+    return RectangleBoundary {
+        left: (0..ny)
+            .map(|j| Rc::new(Edge::new(&points[0][j + 1], &points[0][j])))
+            .collect(),
+        right: (0..ny)
+            .map(|j| Rc::new(Edge::new(&points[nx][j], &points[nx][j + 1])))
+            .collect(),
+        bottom: (0..nx)
+            .map(|i| Rc::new(Edge::new(&points[i][0], &points[i + 1][0])))
+            .collect(),
+        top: (0..nx)
+            .map(|i| Rc::new(Edge::new(&points[i + 1][ny], &points[i][ny])))
+            .collect(),
+    };
+}
+
+/**
+ * Boundary edges of a square with a circular hole: the hole edges are oriented
+ * clockwise about its center, the outer ones counterclockwise
+ */
+pub struct CylinderBoundary {
+    pub cylinder: Vec<Rc<Edge>>,
+    pub outer: RectangleBoundary,
+}
+
+/**
+ * Inserts into the domain the square of given half width around center,
+ * with a hole of given radius at the center. The ring between them is split
+ * into 4 n sectors and nr layers, layers thickening quadratically away from
+ * the hole, each cell made of two triangles. Every square side takes n edges.
+ *  For even n, the mesh is symmetric about the horizontal line through the center
+ */
+pub fn cylinder(
+    (cx, cy): (f64, f64),
+    radius: f64,
+    half_width: f64,
+    n: usize,
+    nr: usize,
+    domain: &mut Domain,
+) -> CylinderBoundary {
+    let sectors = 4 * n;
+    let mut points: Vec<Vec<Rc<Point>>> = Vec::new();
+    for k in 0..sectors {
+        let theta =
+            -std::f64::consts::FRAC_PI_4 + 2.0 * std::f64::consts::PI * k as f64 / sectors as f64;
+        let f = 2.0 * half_width * (k % n) as f64 / n as f64;
+        let (sx, sy) = match k / n {
+            0 => (half_width, f - half_width),
+            1 => (half_width - f, half_width),
+            2 => (-half_width, half_width - f),
+            _ => (f - half_width, -half_width),
+        };
+        points.push(
+            (0..=nr)
+                .map(|j| {
+                    let t = (j as f64 / nr as f64).powi(2);
+                    Rc::new(Point::new(
+                        cx + (1.0 - t) * radius * theta.cos() + t * sx,
+                        cy + (1.0 - t) * radius * theta.sin() + t * sy,
+                    ))
+                })
+                .collect(),
+        );
+    }
+
+    /* Diagonals mirror across the horizontal line through the center */
+    for k in 0..sectors {
+        let middle = -std::f64::consts::FRAC_PI_4
+            + 2.0 * std::f64::consts::PI * (k as f64 + 0.5) / sectors as f64;
+        for j in 0..nr {
+            let (a, b) = (&points[k][j], &points[(k + 1) % sectors][j]);
+            let (c, d) = (&points[(k + 1) % sectors][j + 1], &points[k][j + 1]);
+            if middle.sin() >= 0.0 {
+                domain.insert_element(&Rc::new(TriangleElementL1::new(a, d, b)));
+                domain.insert_element(&Rc::new(TriangleElementL1::new(b, d, c)));
+            } else {
+                domain.insert_element(&Rc::new(TriangleElementL1::new(a, d, c)));
+                domain.insert_element(&Rc::new(TriangleElementL1::new(a, c, b)));
+            }
+        }
+    }
+
+    let outer_edges = |side: usize| -> Vec<Rc<Edge>> {
+        return (side * n..(side + 1) * n)
+            .map(|k| Rc::new(Edge::new(&points[k][nr], &points[(k + 1) % sectors][nr])))
+            .collect();
+    };
+    return CylinderBoundary {
+        cylinder: (0..sectors)
+            .map(|k| Rc::new(Edge::new(&points[(k + 1) % sectors][0], &points[k][0])))
+            .collect(),
+        outer: RectangleBoundary {
+            right: outer_edges(0),
+            top: outer_edges(1),
+            left: outer_edges(2),
+            bottom: outer_edges(3),
+        },
+    };
+}
+
+/**
+ * Inserts into the domain the disc of given radius around center, as the
+ * square [-1, 1]^2 split into 2 n by 2 n cells mapped by
+ *      (s, t) -> (s sqrt(1 - t^2 / 2), t sqrt(1 - s^2 / 2))
+ * so that the square sides fall on the circle. Cell diagonals point to the
+ * square corners, which would otherwise give flat triangles there.
+ * Returns the boundary edges, counterclockwise from the bottom of the square
+ */
+pub fn disc((cx, cy): (f64, f64), radius: f64, n: usize, domain: &mut Domain) -> Vec<Rc<Edge>> {
+    let cells = 2 * n;
+    let mut points: Vec<Vec<Rc<Point>>> = Vec::new();
+    for i in 0..=cells {
+        let s = -1.0 + i as f64 / n as f64;
+        points.push(
+            (0..=cells)
+                .map(|j| {
+                    let t = -1.0 + j as f64 / n as f64;
+                    Rc::new(Point::new(
+                        cx + radius * s * (1.0 - t * t / 2.0).sqrt(),
+                        cy + radius * t * (1.0 - s * s / 2.0).sqrt(),
+                    ))
+                })
+                .collect(),
+        );
+    }
+
+    for i in 0..cells {
+        for j in 0..cells {
+            let (a, b) = (&points[i][j], &points[i + 1][j]);
+            let (c, d) = (&points[i + 1][j + 1], &points[i][j + 1]);
+            if (i < n) == (j < n) {
+                domain.insert_element(&Rc::new(TriangleElementL1::new(a, b, c)));
+                domain.insert_element(&Rc::new(TriangleElementL1::new(a, c, d)));
+            } else {
+                domain.insert_element(&Rc::new(TriangleElementL1::new(a, b, d)));
+                domain.insert_element(&Rc::new(TriangleElementL1::new(d, b, c)));
+            }
+        }
+    }
+
+    let bottom = (0..cells).map(|i| Edge::new(&points[i][0], &points[i + 1][0]));
+    let right = (0..cells).map(|j| Edge::new(&points[cells][j], &points[cells][j + 1]));
+    let top =
+        (0..cells).map(|i| Edge::new(&points[cells - i][cells], &points[cells - i - 1][cells]));
+    let left = (0..cells).map(|j| Edge::new(&points[0][cells - j], &points[0][cells - j - 1]));
+    return bottom
+        .chain(right)
+        .chain(top)
+        .chain(left)
+        .map(Rc::new)
+        .collect();
+}
+
+#[cfg(test)]
+mod structured {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        let mut domain = Domain::new_empty();
+        let boundary = rectangle((0.0, 0.0), (2.0, 1.0), 4, 3, &mut domain);
+        assert_eq!(domain.elements.len(), 24);
+        assert_eq!(boundary.left.len(), 3);
+        assert_eq!(boundary.top.len(), 4);
+
+        /* Boundary edges belong to elements and have no neighbour */
+        let boundary_edges = boundary
+            .left
+            .iter()
+            .chain(boundary.right.iter())
+            .chain(boundary.bottom.iter())
+            .chain(boundary.top.iter());
+        for edge in boundary_edges {
+            assert!(domain.adjacency.contains_key(edge));
+            assert!(!domain.adjacency.contains_key(&edge.opposed()));
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        let mut domain = Domain::new_empty();
+        let boundary = cylinder((1.0, 0.5), 0.5, 2.0, 3, 4, &mut domain);
+        assert_eq!(domain.elements.len(), 96);
+        assert_eq!(boundary.cylinder.len(), 12);
+        assert_eq!(boundary.outer.left.len(), 3);
+
+        /* Elements are counterclockwise and fill the square minus the inscribed polygon */
+        let mut area = 0.0;
+        for element in domain.elements.iter() {
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let signed = (p2.x - p1.x) * (p3.y - p1.y) - (p3.x - p1.x) * (p2.y - p1.y);
+            assert!(signed > 0.0);
+            area += signed / 2.0;
+        }
+        let polygon = 6.0 * 0.25 * (std::f64::consts::PI / 6.0).sin();
+        assert!((area - (16.0 - polygon)).abs() < 1.0E-12);
+
+        let boundary_edges = boundary
+            .cylinder
+            .iter()
+            .chain(boundary.outer.left.iter())
+            .chain(boundary.outer.right.iter())
+            .chain(boundary.outer.bottom.iter())
+            .chain(boundary.outer.top.iter());
+        for edge in boundary_edges {
+            assert!(domain.adjacency.contains_key(edge));
+            assert!(!domain.adjacency.contains_key(&edge.opposed()));
+        }
+    }
+
+    #[test]
+    fn sample_3() {
+        let mut domain = Domain::new_empty();
+        let boundary = disc((1.0, -1.0), 2.0, 3, &mut domain);
+        assert_eq!(domain.elements.len(), 72);
+        assert_eq!(boundary.len(), 24);
+
+        /* Counterclockwise elements filling the inscribed polygon */
+        let mut area = 0.0;
+        for element in domain.elements.iter() {
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let signed = (p2.x - p1.x) * (p3.y - p1.y) - (p3.x - p1.x) * (p2.y - p1.y);
+            assert!(signed > 1.0E-3);
+            area += signed / 2.0;
+        }
+        let mut polygon = 0.0;
+        for edge in boundary.iter() {
+            let radius = ((edge.p1.x - 1.0).powi(2) + (edge.p1.y + 1.0).powi(2)).sqrt();
+            assert!((radius - 2.0).abs() < 1.0E-12);
+            assert!(domain.adjacency.contains_key(edge));
+            assert!(!domain.adjacency.contains_key(&edge.opposed()));
+            polygon += ((edge.p1.x - 1.0) * (edge.p2.y + 1.0)
+                - (edge.p2.x - 1.0) * (edge.p1.y + 1.0))
+                / 2.0;
+        }
+        assert!((area - polygon).abs() < 1.0E-12);
+        assert!((polygon - 4.0 * std::f64::consts::PI).abs() < 0.05 * 4.0 * std::f64::consts::PI);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::common::{edge::Edge, error::Error, point::Point};
+use crate::triangle::{
+    element::TriangleElementL1,
+    integrands::utils,
+    post_processing::{dg_solution::DgSolution, error_norms},
+    quadrature::dunavant,
+};
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/* Quadrature exceeds twice the element degree, the source not being polynomial */
+const EXTRA_DEGREE: usize = 2;
+
+pub enum FluxKind {
+    /* Normal gradient of the element owning the edge: grad(u_h) . n */
+    Gradient,
+    /*
+        Numerical flux of the interior penalty method of builder::build:
+            dirichlet edges: grad(u_h) . n + sigma max(p, 1)^2 / |e| (g - u_h)
+            neumann edges: the prescribed values
+            remaining edges: zero, as the natural condition
+    */
+    Consistent { sigma: f64 },
+}
+
+/**
+ * Conservation balance of the whole domain: the outward flux through every boundary edge
+ * plus the integral of the source vanish for - laplacian(u) = f
+ */
+pub struct FluxBalance {
+    pub boundary: f64,
+    pub source: f64,
+    pub imbalance: f64,
+}
+
+/**
+ * Integral of the outward normal derivative of the solution over the boundary edges:
+ *  sum_e integral of grad(u) . n
+ *  Fails if an edge is not a boundary edge of the domain
+ */
+pub fn normal_flux(
+    solution: &DgSolution,
+    edges: &[Rc<Edge>],
+    kind: &FluxKind,
+) -> Result<f64, Error> {
+    let mut flux = 0.0;
+    for edge in edges.iter() {
+        flux += edge_flux(solution, edge, kind)?;
+    }
+    return Ok(flux);
+}
+
+/**
+ * Heat leaving the domain through the boundary edges: - k integral of grad(u) . n
+ */
+pub fn heat_rate(
+    solution: &DgSolution,
+    edges: &[Rc<Edge>],
+    conductivity: f64,
+    kind: &FluxKind,
+) -> Result<f64, Error> {
+    return Ok(-conductivity * normal_flux(solution, edges, kind)?);
+}
+
+/**
+ * Balance of the outward flux of the given kind through every boundary edge
+ * against the integral of the source
+ */
+pub fn balance(
+    solution: &DgSolution,
+    source: &dyn Fn(&Point) -> f64,
+    kind: &FluxKind,
+) -> FluxBalance {
+    let domain = solution.domain;
+    let edges: Vec<Rc<Edge>> = domain
+        .elements
+        .iter()
+        .flat_map(|element| {
+            let (e1, e2, e3) = element.inner_edges();
+            vec![e1, e2, e3]
+        })
+        .filter(|edge| !domain.adjacency.contains_key(&edge.opposed()))
+        .collect();
+    let boundary =
+        normal_flux(solution, &edges, kind).expect("Not expected boundary edge outside the domain");
+
+    let mut integral = 0.0;
+    for element in domain.elements.iter() {
+        let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+        let degree = domain.element_basis(element).degree() + EXTRA_DEGREE;
+        let rule = dunavant::rule(degree.min(dunavant::MAX_DEGREE));
+        let area = utils::jacobian(p1, p2, p3).determinant().abs();
+        integral += area * rule.integrate(|x, y| source(&utils::physical_point(p1, p2, p3, x, y)));
+    }
+
+    return FluxBalance {
+        boundary,
+        source: integral,
+        imbalance: boundary + integral,
+    };
+}
+
+fn edge_flux(solution: &DgSolution, edge: &Rc<Edge>, kind: &FluxKind) -> Result<f64, Error> {
+    let domain = solution.domain;
+    if domain.adjacency.contains_key(&edge.opposed()) {
+        return Err(Error::MissingEdge);
+    }
+    let element = domain.adjacency.get(edge).ok_or(Error::MissingEdge)?;
+    let edge_index = element.edge_index(edge)?;
+    let degree = domain.element_basis(element).degree();
+    let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+    let normal = utils::outer_normal(p1, p2, p3, edge_index);
+    let normal_derivative = |point: &Point| {
+        let (gx, gy) = solution.gradient_in(element, point);
+        gx * normal[0] + gy * normal[1]
+    };
+
+    let sigma = match kind {
+        FluxKind::Gradient => {
+            return Ok(error_norms::edge_integral(
+                element,
+                edge_index,
+                degree,
+                &normal_derivative,
+            ))
+        }
+        FluxKind::Consistent { sigma } => *sigma,
+    };
+
+    if let Some(constraint) = domain
+        .dirichlet_constraints
+        .iter()
+        .find(|constraint| constraint.boundary_edge == *edge)
+    {
+        let penalty =
+            sigma * (degree.max(1) as f64).powi(2) / utils::edge_length(p1, p2, p3, edge_index);
+        let g = |point: &Point| interpolate(element, &constraint.values, point);
+        return Ok(error_norms::edge_integral(
+            element,
+            edge_index,
+            degree,
+            &|point| {
+                normal_derivative(point)
+                    + penalty * (g(point) - solution.evaluate_in(element, point))
+            },
+        ));
+    }
+    if let Some(constraint) = domain
+        .neumann_constraints
+        .iter()
+        .find(|constraint| constraint.boundary_edge == *edge)
+    {
+        return Ok(error_norms::edge_integral(
+            element,
+            edge_index,
+            degree,
+            &|point| interpolate(element, &constraint.values, point),
+        ));
+    }
+    return Ok(0.0);
+}
+
+/**
+ * Linear interpolation of the constraint values at the element points, zero where missing
+ */
+fn interpolate(
+    element: &TriangleElementL1,
+    values: &HashMap<Rc<Point>, f64>,
+    point: &Point,
+) -> f64 {
+    let value = |vertex| *values.get(vertex).unwrap_or(&0.0);
+    let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+    let (x, y) = utils::reference_coordinates(p1, p2, p3, point);
+    return (1.0 - x - y) * value(p1) + x * value(p2) + y * value(p3);
+}
+
+#[cfg(test)]
+mod boundary_integrals {
+    use super::*;
+    use crate::triangle::{
+        mesh,
+        system_builder::{builder, domain::Domain},
+    };
+
+    #[test]
+    fn sample_1() {
+        /*
+            u = x over [0,2]x[0,1], dirichlet u = 0 at the left edge and
+            neumann du/dn = 1 at the right one: unit flux leaves through the right
+            and enters through the left, either kind, and nothing through top and bottom
+        */
+        let sigma = 10.0;
+        let mut domain = Domain::new_modal(2).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (2.0, 1.0), 4, 2, &mut domain);
+        for edge in boundary.left.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+        for edge in boundary.right.iter() {
+            domain.insert_neumann_constraint(edge, vec![1.0, 1.0]);
+        }
+        let (system_matrix, extern_matrix) = builder::build(sigma, &domain);
+        let coefficients = system_matrix.lu().solve(&extern_matrix).unwrap();
+        let solution = DgSolution::new(&domain, &coefficients);
+
+        for kind in [FluxKind::Gradient, FluxKind::Consistent { sigma }].iter() {
+            assert!((normal_flux(&solution, &boundary.left, kind).unwrap() + 1.0).abs() < 1.0E-10);
+            assert!((normal_flux(&solution, &boundary.right, kind).unwrap() - 1.0).abs() < 1.0E-10);
+            assert!(normal_flux(&solution, &boundary.top, kind).unwrap().abs() < 1.0E-10);
+            assert!(
+                (heat_rate(&solution, &boundary.left, 3.0, kind).unwrap() - 3.0).abs() < 1.0E-9
+            );
+            assert!(balance(&solution, &|_| 0.0, kind).imbalance.abs() < 1.0E-10);
+        }
+
+        /* Edges inside the domain are not boundary edges */
+        let element = &domain.elements[0];
+        let (e1, e2, e3) = element.inner_edges();
+        let inner: Vec<Rc<Edge>> = vec![e1, e2, e3]
+            .into_iter()
+            .filter(|edge| domain.adjacency.contains_key(&edge.opposed()))
+            .collect();
+        assert!(normal_flux(&solution, &inner, &FluxKind::Gradient).is_err());
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Unit source over the unit square, zero dirichlet values everywhere:
+            the consistent flux balances the source to round off, by symmetry a quarter
+            through each side, while the raw gradient does not
+        */
+        let sigma = 10.0;
+        let mut domain = Domain::new_modal(1).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 4, 4, &mut domain);
+        let edges: Vec<Rc<Edge>> = boundary
+            .left
+            .iter()
+            .chain(boundary.right.iter())
+            .chain(boundary.bottom.iter())
+            .chain(boundary.top.iter())
+            .cloned()
+            .collect();
+        for edge in edges.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+        let source = |_: &Point| 1.0;
+        let (system_matrix, extern_matrix) = builder::build_with_source(sigma, &source, &domain);
+        let coefficients = system_matrix.lu().solve(&extern_matrix).unwrap();
+        let solution = DgSolution::new(&domain, &coefficients);
+
+        let consistent = balance(&solution, &source, &FluxKind::Consistent { sigma });
+        assert!((consistent.source - 1.0).abs() < 1.0E-12);
+        assert!(consistent.imbalance.abs() < 1.0E-10);
+        let left = normal_flux(&solution, &boundary.left, &FluxKind::Consistent { sigma }).unwrap();
+        assert!((left + 0.25).abs() < 1.0E-10);
+
+        let gradient = balance(&solution, &source, &FluxKind::Gradient);
+        assert!(gradient.imbalance.abs() > 1.0E-3);
+    }
+}
@@ -0,0 +1,305 @@
+use crate::common::{error::Error, point::Point};
+use crate::triangle::{
+    mesh,
+    post_processing::{dg_solution::DgSolution, error_norms},
+    system_builder::{builder, domain::Domain},
+};
+
+use std::fmt;
+
+/**
+ * Gradient of the exact solution, as (du/dx, du/dy)
+ */
+pub type Gradient<'a> = &'a dyn Fn(&Point) -> (f64, f64);
+
+/**
+ * Exact solution of the method of manufactured solutions for Poisson:
+ * minus laplacian(u) = f. The gradient of u and the forcing f are derived from u
+ * by central differences with Richardson extrapolation, unless given in closed form.
+ *  Derived values take u slightly outside the domain, so u must extend smoothly
+ *  across the boundary
+ */
+pub struct ManufacturedSolution<'a> {
+    pub exact: &'a dyn Fn(&Point) -> f64,
+    pub gradient: Option<Gradient<'a>>,
+    pub forcing: Option<&'a dyn Fn(&Point) -> f64>,
+}
+
+/*
+    Step of the central differences. Extrapolation from the steps h and h / 2
+    leaves errors of order h^4, and round off of order 1e-16 / h^2
+*/
+const STEP: f64 = 5.0E-3;
+
+impl<'a> ManufacturedSolution<'a> {
+    pub fn new(exact: &'a dyn Fn(&Point) -> f64) -> Self {
+        ManufacturedSolution {
+            exact,
+            gradient: None,
+            forcing: None,
+        }
+    }
+
+    pub fn with_gradient(mut self, gradient: Gradient<'a>) -> Self {
+        self.gradient = Some(gradient);
+        return self;
+    }
+
+    pub fn with_forcing(mut self, forcing: &'a dyn Fn(&Point) -> f64) -> Self {
+        self.forcing = Some(forcing);
+        return self;
+    }
+
+    /**
+     * Gradient of u, in closed form if given
+     */
+    pub fn gradient_at(&self, point: &Point) -> (f64, f64) {
+        if let Some(gradient) = self.gradient {
+            return gradient(point);
+        }
+        let (dx, _) = self.derivatives_along(point, (1.0, 0.0));
+        let (dy, _) = self.derivatives_along(point, (0.0, 1.0));
+        return (dx, dy);
+    }
+
+    /**
+     * Forcing f = minus laplacian(u), in closed form if given
+     */
+    pub fn forcing_at(&self, point: &Point) -> f64 {
+        if let Some(forcing) = self.forcing {
+            return forcing(point);
+        }
+        let (_, dxx) = self.derivatives_along(point, (1.0, 0.0));
+        let (_, dyy) = self.derivatives_along(point, (0.0, 1.0));
+        return -(dxx + dyy);
+    }
+
+    /**
+     * First and second derivatives of u along the direction,
+     * extrapolated from central differences of steps h and h / 2
+     */
+    fn derivatives_along(&self, point: &Point, direction: (f64, f64)) -> (f64, f64) {
+        let exact = self.exact;
+        let central = |h: f64| {
+            let plus = exact(&Point::new(
+                point.x + h * direction.0,
+                point.y + h * direction.1,
+            ));
+            let minus = exact(&Point::new(
+                point.x - h * direction.0,
+                point.y - h * direction.1,
+            ));
+            let center = exact(point);
+            (
+                (plus - minus) / (2.0 * h),
+                (plus - 2.0 * center + minus) / (h * h),
+            )
+        };
+        let (coarse, fine) = (central(STEP), central(STEP / 2.0));
+        return (
+            (4.0 * fine.0 - coarse.0) / 3.0,
+            (4.0 * fine.1 - coarse.1) / 3.0,
+        );
+    }
+}
+
+/**
+ * Errors over one mesh of the sequence, with the observed orders against the previous mesh
+ */
+pub struct ConvergenceRow {
+    pub cells: usize,
+    pub size: f64,
+    pub dofs: usize,
+    pub l2: f64,
+    pub h1: f64,
+    pub energy: f64,
+    pub l2_order: Option<f64>,
+    pub h1_order: Option<f64>,
+    pub energy_order: Option<f64>,
+}
+
+pub struct ConvergenceStudy {
+    pub degree: usize,
+    pub rows: Vec<ConvergenceRow>,
+}
+
+/**
+ * Solves Poisson over the unit square with basis of given degree, on meshes
+ * of cells by cells squares, each split into two triangles. Forcing comes from
+ * the manufactured solution, and the dirichlet values at every boundary edge
+ * from its values at the edge quadrature points. Fails for degrees the domain
+ * does not support
+ */
+pub fn poisson(
+    manufactured: &ManufacturedSolution,
+    degree: usize,
+    sigma: f64,
+    meshes: &[usize],
+) -> Result<ConvergenceStudy, Error> {
+    let exact = manufactured.exact;
+    let gradient = |point: &Point| manufactured.gradient_at(point);
+    let forcing = |point: &Point| manufactured.forcing_at(point);
+
+    let mut rows: Vec<ConvergenceRow> = Vec::new();
+    for cells in meshes.iter() {
+        let mut domain = Domain::new_modal(degree)?;
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), *cells, *cells, &mut domain);
+        let edges = boundary
+            .left
+            .iter()
+            .chain(boundary.right.iter())
+            .chain(boundary.bottom.iter())
+            .chain(boundary.top.iter());
+        for edge in edges {
+            domain.insert_dirichlet_constraint(edge, vec![exact(&edge.p1), exact(&edge.p2)]);
+        }
+
+        let (system_matrix, extern_matrix) =
+            builder::build_with_boundary_function(sigma, &forcing, exact, &domain);
+        let coefficients = system_matrix
+            .lu()
+            .solve(&extern_matrix)
+            .expect("Not expected singular poisson system");
+        let solution = DgSolution::new(&domain, &coefficients);
+
+        let mut row = ConvergenceRow {
+            cells: *cells,
+            size: 1.0 / *cells as f64,
+            dofs: domain.size(),
+            l2: error_norms::l2(&solution, exact),
+            h1: error_norms::h1_seminorm(&solution, &gradient),
+            energy: error_norms::energy(sigma, &solution, exact, &gradient),
+            l2_order: None,
+            h1_order: None,
+            energy_order: None,
+        };
+        if let Some(previous) = rows.last() {
+            let ratio = (previous.size / row.size).ln();
+            row.l2_order = Some((previous.l2 / row.l2).ln() / ratio);
+            row.h1_order = Some((previous.h1 / row.h1).ln() / ratio);
+            row.energy_order = Some((previous.energy / row.energy).ln() / ratio);
+        }
+        rows.push(row);
+    }
+    return Ok(ConvergenceStudy { degree, rows });
+}
+
+impl ConvergenceStudy {
+    /**
+     * Checks the orders observed between the last two meshes against the expected
+     * rates of the L2 and broken H1 errors, within the tolerance
+     */
+    pub fn check(&self, l2_rate: f64, h1_rate: f64, tolerance: f64) -> Result<(), Error> {
+        let last = match self.rows.last() {
+            Some(last) => last,
+            None => return Err(Error::Convergence),
+        };
+        match (last.l2_order, last.h1_order) {
+            (Some(l2), Some(h1)) if l2 >= l2_rate - tolerance && h1 >= h1_rate - tolerance => {
+                return Ok(())
+            }
+            _ => return Err(Error::Convergence),
+        }
+    }
+}
+
+impl fmt::Display for ConvergenceStudy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let order = |value: Option<f64>| match value {
+            Some(value) => format!("{:>6.2}", value),
+            None => format!("{:>6}", "-"),
+        };
+        writeln!(f, "P{}", self.degree)?;
+        writeln!(
+            f,
+            "{:>6} {:>10} {:>7} {:>11} {:>6} {:>11} {:>6} {:>11} {:>6}",
+            "cells", "h", "dofs", "L2", "order", "H1", "order", "energy", "order"
+        )?;
+        for row in self.rows.iter() {
+            writeln!(
+                f,
+                "{:>6} {:>10.4e} {:>7} {:>11.4e} {} {:>11.4e} {} {:>11.4e} {}",
+                row.cells,
+                row.size,
+                row.dofs,
+                row.l2,
+                order(row.l2_order),
+                row.h1,
+                order(row.h1_order),
+                row.energy,
+                order(row.energy_order)
+            )?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod manufactured_solutions {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn sample_1() {
+        /*
+            Interior penalty P2, gradient and forcing derived from u: harmonic
+            u = x^2 - y^2 is reproduced to round off, and u = sin(pi x) sin(pi y)
+            converges at third order in L2 and second order in broken H1
+        */
+        let exact = |point: &Point| point.x * point.x - point.y * point.y;
+        let study = poisson(&ManufacturedSolution::new(&exact), 2, 10.0, &[2]).unwrap();
+        assert!(study.rows[0].l2 < 1.0E-12);
+        assert!(study.rows[0].h1 < 1.0E-11);
+
+        let exact = |point: &Point| (PI * point.x).sin() * (PI * point.y).sin();
+        let study = poisson(&ManufacturedSolution::new(&exact), 2, 10.0, &[2, 4, 8]).unwrap();
+        assert!(study.check(3.0, 2.0, 0.15).is_ok());
+        assert!(study.check(4.0, 2.0, 0.15).is_err());
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Interior penalty P1 over uniformly refined meshes, gradient and
+            forcing given in closed form: second order L2 and first order
+            broken H1 convergence
+        */
+        let exact = |point: &Point| 0.1 * (point.x + 2.0 * point.y).exp();
+        let gradient = |point: &Point| (exact(point), 2.0 * exact(point));
+        let forcing = |point: &Point| -5.0 * exact(point);
+        let manufactured = ManufacturedSolution::new(&exact)
+            .with_gradient(&gradient)
+            .with_forcing(&forcing);
+        let study = poisson(&manufactured, 1, 10.0, &[2, 4, 8]).unwrap();
+        assert!(study.check(2.0, 1.0, 0.15).is_ok());
+        assert!(study.check(3.0, 1.0, 0.15).is_err());
+        assert!(study
+            .rows
+            .windows(2)
+            .all(|rows| rows[1].energy < rows[0].energy));
+        assert_eq!(study.rows[2].dofs, 384);
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Derived gradient and forcing match the closed forms, boundary points included */
+        let exact = |point: &Point| (PI * point.x).sin() * (2.0 * point.y).exp();
+        let manufactured = ManufacturedSolution::new(&exact);
+        for point in [
+            Point::new(0.0, 0.0),
+            Point::new(0.3, 0.7),
+            Point::new(1.0, 0.5),
+        ]
+        .iter()
+        {
+            let (gx, gy) = manufactured.gradient_at(point);
+            let expected = (
+                PI * (PI * point.x).cos() * (2.0 * point.y).exp(),
+                2.0 * exact(point),
+            );
+            assert!((gx - expected.0).abs() < 1.0E-8 && (gy - expected.1).abs() < 1.0E-8);
+            let forcing = (PI * PI - 4.0) * exact(point);
+            assert!((manufactured.forcing_at(point) - forcing).abs() < 1.0E-8);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use nalgebra::{DMatrix, DVector, Matrix1x3, Matrix3x1};
+
+use crate::common::point::Point;
+use crate::triangle::{
+    basis::shape::Basis, element::TriangleElementL1, integrands::utils,
+    system_builder::domain::Domain,
+};
+
+use std::rc::Rc;
+
+/* Tolerance over reference coordinates for points lying on element boundaries */
+const CONTAINMENT: f64 = 1.0E-10;
+
+/**
+ * Discrete solution: the domain paired with its coefficient vector,
+ * evaluated at arbitrary physical points.
+ *  Elements are located through a uniform grid of buckets over the domain
+ *  bounding box, each bucket holding the elements whose bounding box overlaps it
+ */
+pub struct DgSolution<'a> {
+    pub domain: &'a Domain,
+    pub coefficients: DMatrix<f64>,
+    grid: BucketGrid,
+}
+
+/**
+ * Sample of a polyline: arc length from the first vertex, point and value,
+ * if the point lies inside the domain
+ */
+pub struct PolylineSample {
+    pub distance: f64,
+    pub point: Point,
+    pub value: Option<f64>,
+}
+
+struct BucketGrid {
+    origin: (f64, f64),
+    cell: (f64, f64),
+    shape: (usize, usize),
+    /* Element indexes of each bucket, row major */
+    buckets: Vec<Vec<usize>>,
+}
+
+impl BucketGrid {
+    fn new(elements: &[Rc<TriangleElementL1>]) -> Self {
+        let (mut x0, mut y0) = (f64::INFINITY, f64::INFINITY);
+        let (mut x1, mut y1) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for element in elements.iter() {
+            for point in [&element.p1, &element.p2, &element.p3].iter() {
+                x0 = x0.min(point.x);
+                y0 = y0.min(point.y);
+                x1 = x1.max(point.x);
+                y1 = y1.max(point.y);
+            }
+        }
+
+        let side = ((elements.len() as f64).sqrt().ceil() as usize).max(1);
+        let shape = (side, side);
+        let cell = (
+            ((x1 - x0) / side as f64).max(f64::MIN_POSITIVE),
+            ((y1 - y0) / side as f64).max(f64::MIN_POSITIVE),
+        );
+        let mut grid = BucketGrid {
+            origin: (x0, y0),
+            cell,
+            shape,
+            buckets: vec![Vec::new(); side * side],
+        };
+
+        for (index, element) in elements.iter().enumerate() {
+            let points = [&element.p1, &element.p2, &element.p3];
+            let xmin = points.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+            let ymin = points.iter().map(|p| p.y).fold(f64::INFINITY, f64::min);
+            let xmax = points.iter().map(|p| p.x).fold(f64::NEG_INFINITY, f64::max);
+            let ymax = points.iter().map(|p| p.y).fold(f64::NEG_INFINITY, f64::max);
+            let (i0, j0) = grid.cell_of(xmin, ymin);
+            let (i1, j1) = grid.cell_of(xmax, ymax);
+            for j in j0..=j1 {
+                for i in i0..=i1 {
+                    grid.buckets[j * shape.0 + i].push(index);
+                }
+            }
+        }
+        return grid;
+    }
+
+    /**
+     * Bucket of coordinates (x, y), clamped into the grid
+     */
+    fn cell_of(&self, x: f64, y: f64) -> (usize, usize) {
+        let clamp = |value: f64, size: usize| (value.max(0.0) as usize).min(size - 1);
+        return (
+            clamp((x - self.origin.0) / self.cell.0, self.shape.0),
+            clamp((y - self.origin.1) / self.cell.1, self.shape.1),
+        );
+    }
+
+    /**
+     * Candidate elements of a point, none if outside the grid
+     */
+    fn candidates(&self, point: &Point) -> &[usize] {
+        let x1 = self.origin.0 + self.cell.0 * self.shape.0 as f64;
+        let y1 = self.origin.1 + self.cell.1 * self.shape.1 as f64;
+        let tolerance = CONTAINMENT * (self.cell.0 + self.cell.1);
+        if point.x < self.origin.0 - tolerance
+            || point.y < self.origin.1 - tolerance
+            || point.x > x1 + tolerance
+            || point.y > y1 + tolerance
+        {
+            return &[];
+        }
+        let (i, j) = self.cell_of(point.x, point.y);
+        return &self.buckets[j * self.shape.0 + i];
+    }
+}
+
+impl<'a> DgSolution<'a> {
+    pub fn new(domain: &'a Domain, coefficients: &DMatrix<f64>) -> Self {
+        assert_eq!(
+            coefficients.nrows(),
+            domain.size(),
+            "Not expected coefficients of size different from the domain"
+        );
+        return DgSolution {
+            domain,
+            coefficients: coefficients.clone(),
+            grid: BucketGrid::new(&domain.elements),
+        };
+    }
+
+    /**
+     * Element containing the point. Points over shared edges or vertices
+     * belong to the first element found in domain order
+     */
+    pub fn locate(&self, point: &Point) -> Option<Rc<TriangleElementL1>> {
+        return self
+            .grid
+            .candidates(point)
+            .iter()
+            .map(|index| &self.domain.elements[*index])
+            .find(|element| {
+                let (x, y) =
+                    utils::reference_coordinates(&element.p1, &element.p2, &element.p3, point);
+                x >= -CONTAINMENT && y >= -CONTAINMENT && x + y <= 1.0 + CONTAINMENT
+            })
+            .map(Rc::clone);
+    }
+
+    /**
+     * Local coefficients of the element, first column of the solution
+     */
+    pub fn element_coefficients(&self, element: &Rc<TriangleElementL1>) -> DVector<f64> {
+        let indexes = self.domain.element_indexes(element);
+        return DVector::from_iterator(
+            indexes.len(),
+            indexes.iter().map(|index| self.coefficients[(*index, 0)]),
+        );
+    }
+
+    /**
+     * Element polynomial at a physical point, which is not required to lie inside the element
+     */
+    pub fn evaluate_in(&self, element: &Rc<TriangleElementL1>, point: &Point) -> f64 {
+        let local = self.element_coefficients(element);
+        match self.domain.element_basis(element) {
+            Basis::Nodal => {
+                /* u(x,y) = [1, x, y] * [1, xi, yi]^-1 * [ui] */
+                let inverse = utils::field_transformation(&element.p1, &element.p2, &element.p3)
+                    .try_inverse()
+                    .expect("Not expected degenerate element");
+                let value = Matrix1x3::new(1.0, point.x, point.y)
+                    * inverse
+                    * Matrix3x1::new(local[0], local[1], local[2]);
+                return value[(0, 0)];
+            }
+            basis => {
+                return basis
+                    .evaluate_at(&element.p1, &element.p2, &element.p3, point)
+                    .dot(&local);
+            }
+        }
+    }
+
+    /**
+     * Solution at a physical point, none if the point is outside the domain
+     */
+    pub fn evaluate(&self, point: &Point) -> Option<f64> {
+        return self
+            .locate(point)
+            .map(|element| self.evaluate_in(&element, point));
+    }
+
+    /**
+     * Solution gradient at a physical point, none if the point is outside the domain
+     */
+    pub fn gradient(&self, point: &Point) -> Option<(f64, f64)> {
+        return self
+            .locate(point)
+            .map(|element| self.gradient_in(&element, point));
+    }
+
+    /**
+     * Gradient of the element polynomial at a physical point
+     */
+    pub fn gradient_in(&self, element: &Rc<TriangleElementL1>, point: &Point) -> (f64, f64) {
+        let local = self.element_coefficients(element);
+        let gradient = self.domain.element_basis(element).physical_gradient_at(
+            &element.p1,
+            &element.p2,
+            &element.p3,
+            point,
+        );
+        let gradient = gradient.transpose() * local;
+        return (gradient[0], gradient[1]);
+    }
+
+    /**
+     * Samples along the polyline through the given vertices,
+     * each segment divided into the given number of intervals
+     */
+    pub fn evaluate_polyline(&self, vertices: &[Point], intervals: usize) -> Vec<PolylineSample> {
+        let mut samples = Vec::new();
+        let mut distance = 0.0;
+        if let Some(first) = vertices.first() {
+            let point = Point::new(first.x, first.y);
+            samples.push(PolylineSample {
+                distance,
+                value: self.evaluate(&point),
+                point,
+            });
+        }
+        for segment in vertices.windows(2) {
+            let (a, b) = (&segment[0], &segment[1]);
+            let length = ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+            for k in 1..=intervals.max(1) {
+                let l = k as f64 / intervals.max(1) as f64;
+                let point = Point::new(a.x + (b.x - a.x) * l, a.y + (b.y - a.y) * l);
+                samples.push(PolylineSample {
+                    distance: distance + length * l,
+                    value: self.evaluate(&point),
+                    point,
+                });
+            }
+            distance += length;
+        }
+        return samples;
+    }
+}
+
+#[cfg(test)]
+mod point_evaluation {
+    use super::*;
+    use crate::triangle::{basis::dubiner, mesh};
+
+    fn interpolate(domain: &Domain, field: &dyn Fn(f64, f64) -> f64) -> DMatrix<f64> {
+        let mut coefficients = DMatrix::<f64>::zeros(domain.size(), 1);
+        for element in domain.elements.iter() {
+            let (u1, u2, u3) = (
+                field(element.p1.x, element.p1.y),
+                field(element.p2.x, element.p2.y),
+                field(element.p3.x, element.p3.y),
+            );
+            let local = match domain.element_basis(element) {
+                Basis::Nodal => DVector::from_vec(vec![u1, u2, u3]),
+                basis => dubiner::linear_to_modal(basis.degree(), u1, u2, u3),
+            };
+            for (index, value) in domain.element_indexes(element).iter().zip(local.iter()) {
+                coefficients[(*index, 0)] = *value;
+            }
+        }
+        return coefficients;
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Linear fields are reproduced exactly by nodal and modal bases */
+        let field = |x: f64, y: f64| 1.0 + 2.0 * x - 3.0 * y;
+        for domain in [Domain::new_empty(), Domain::new_modal(2).unwrap()].iter_mut() {
+            mesh::rectangle((0.0, 0.0), (2.0, 1.0), 4, 3, domain);
+            let solution = DgSolution::new(domain, &interpolate(domain, &field));
+            for (x, y) in [(0.3, 0.7), (1.99, 0.01), (0.0, 0.0), (2.0, 1.0), (1.0, 0.5)].iter() {
+                let value = solution.evaluate(&Point::new(*x, *y)).unwrap();
+                assert!((value - field(*x, *y)).abs() < 1.0E-12);
+                let (gx, gy) = solution.gradient(&Point::new(*x, *y)).unwrap();
+                assert!((gx - 2.0).abs() < 1.0E-12 && (gy + 3.0).abs() < 1.0E-12);
+            }
+            assert!(solution.evaluate(&Point::new(-0.1, 0.5)).is_none());
+            assert!(solution.evaluate(&Point::new(1.0, 1.2)).is_none());
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Points of a disc are found in the element containing them, none outside the circle */
+        let mut domain = Domain::new_modal(1).unwrap();
+        mesh::disc((0.0, 0.0), 1.0, 3, &mut domain);
+        let field = |x: f64, y: f64| x * x + y;
+        let solution = DgSolution::new(&domain, &interpolate(&domain, &field));
+        for k in 0..20 {
+            let angle = k as f64 * 0.7;
+            let point = Point::new(0.9 * angle.cos(), 0.9 * angle.sin());
+            let element = solution.locate(&point).unwrap();
+            let (x, y) =
+                utils::reference_coordinates(&element.p1, &element.p2, &element.p3, &point);
+            assert!(x >= -1.0E-10 && y >= -1.0E-10 && x + y <= 1.0 + 1.0E-10);
+        }
+        assert!(solution.locate(&Point::new(0.8, 0.8)).is_none());
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Polyline leaving the domain: arc lengths accumulate and outer samples are none */
+        let mut domain = Domain::new_empty();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 3, 3, &mut domain);
+        let field = |x: f64, y: f64| x + y;
+        let solution = DgSolution::new(&domain, &interpolate(&domain, &field));
+        let vertices = [
+            Point::new(0.0, 0.5),
+            Point::new(1.0, 0.5),
+            Point::new(1.0, 1.5),
+        ];
+        let samples = solution.evaluate_polyline(&vertices, 4);
+        assert_eq!(samples.len(), 9);
+        assert!((samples[4].distance - 1.0).abs() < 1.0E-14);
+        assert!((samples[8].distance - 2.0).abs() < 1.0E-14);
+        for sample in samples.iter() {
+            match sample.value {
+                Some(value) => {
+                    assert!(sample.point.y <= 1.0);
+                    assert!((value - sample.point.x - sample.point.y).abs() < 1.0E-12);
+                }
+                None => assert!(sample.point.y > 1.0),
+            }
+        }
+        assert_eq!(samples.iter().filter(|s| s.value.is_none()).count(), 2);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::common::point::Point;
+use crate::triangle::{
+    element::TriangleElementL1,
+    integrands::utils,
+    post_processing::dg_solution::DgSolution,
+    quadrature::{dunavant, gauss_legendre},
+};
+
+use std::rc::Rc;
+
+/* Quadrature exceeds twice the element degree, the exact solution not being polynomial */
+const EXTRA_DEGREE: usize = 2;
+
+/**
+ * L2 norm of the error against the exact solution: ||u - u_h||
+ */
+pub fn l2(solution: &DgSolution, exact: &dyn Fn(&Point) -> f64) -> f64 {
+    return volume_integral(solution, &|element, point| {
+        (exact(point) - solution.evaluate_in(element, point)).powi(2)
+    })
+    .sqrt();
+}
+
+/**
+ * Broken H1 seminorm of the error, the gradient taken element by element:
+ *  sqrt( sum_K ||grad(u) - grad(u_h)||_K^2 )
+ */
+pub fn h1_seminorm(solution: &DgSolution, exact_gradient: &dyn Fn(&Point) -> (f64, f64)) -> f64 {
+    return broken_gradient_squared(solution, exact_gradient).sqrt();
+}
+
+/**
+ * Broken H1 norm of the error: sqrt( ||u - u_h||^2 + |u - u_h|_H1^2 )
+ */
+pub fn broken_h1(
+    solution: &DgSolution,
+    exact: &dyn Fn(&Point) -> f64,
+    exact_gradient: &dyn Fn(&Point) -> (f64, f64),
+) -> f64 {
+    return (l2(solution, exact).powi(2) + broken_gradient_squared(solution, exact_gradient))
+        .sqrt();
+}
+
+/**
+ * Energy norm of the interior penalty method of builder::build:
+ *  sqrt( sum_K ||grad(u - u_h)||_K^2
+ *      + sum_inner_e sigma max(p, 1)^2 / |e| ||[u_h]||_e^2
+ *      + sum_dirichlet_e sigma max(p, 1)^2 / |e| ||u - u_h||_e^2 )
+ *  p is the greatest degree among the neighbours of each edge,
+ *  as taken by the jump penalties
+ */
+pub fn energy(
+    sigma: f64,
+    solution: &DgSolution,
+    exact: &dyn Fn(&Point) -> f64,
+    exact_gradient: &dyn Fn(&Point) -> (f64, f64),
+) -> f64 {
+    let domain = solution.domain;
+    let mut squared = broken_gradient_squared(solution, exact_gradient);
+
+    for element in domain.elements.iter() {
+        let (e1, e2, e3) = element.inner_edges();
+        for edge in [e1, e2, e3].iter() {
+            if let Some(neighbour) = domain.adjacency.get(&edge.opposed()) {
+                let degree = domain
+                    .element_basis(element)
+                    .degree()
+                    .max(domain.element_basis(neighbour).degree());
+                let edge_index = element
+                    .edge_index(edge)
+                    .expect("Not expected edge outside its element");
+                let jump = edge_integral(element, edge_index, degree, &|point| {
+                    (solution.evaluate_in(element, point) - solution.evaluate_in(neighbour, point))
+                        .powi(2)
+                });
+                /* Each inner edge is visited from both sides */
+                squared += 0.5 * penalty(sigma, element, edge_index, degree) * jump;
+            }
+        }
+    }
+
+    for constraint in domain.dirichlet_constraints.iter() {
+        let element = &constraint.element;
+        let degree = domain.element_basis(element).degree();
+        let edge_index = element
+            .edge_index(&constraint.boundary_edge)
+            .expect("Not expected dirichlet edge outside its element");
+        let mismatch = edge_integral(element, edge_index, degree, &|point| {
+            (exact(point) - solution.evaluate_in(element, point)).powi(2)
+        });
+        squared += penalty(sigma, element, edge_index, degree) * mismatch;
+    }
+
+    return squared.sqrt();
+}
+
+fn broken_gradient_squared(
+    solution: &DgSolution,
+    exact_gradient: &dyn Fn(&Point) -> (f64, f64),
+) -> f64 {
+    let domain = solution.domain;
+    return volume_integral(solution, &|element, point| {
+        let local = solution.element_coefficients(element);
+        let gradient = domain
+            .element_basis(element)
+            .physical_gradient_at(&element.p1, &element.p2, &element.p3, point)
+            .transpose()
+            * local;
+        let (gx, gy) = exact_gradient(point);
+        (gx - gradient[0]).powi(2) + (gy - gradient[1]).powi(2)
+    });
+}
+
+fn penalty(sigma: f64, element: &TriangleElementL1, edge_index: usize, degree: usize) -> f64 {
+    return sigma * (degree.max(1) as f64).powi(2)
+        / utils::edge_length(&element.p1, &element.p2, &element.p3, edge_index);
+}
+
+/**
+ * Sum over elements of the integral of f(element, physical point)
+ */
+fn volume_integral(
+    solution: &DgSolution,
+    f: &dyn Fn(&Rc<TriangleElementL1>, &Point) -> f64,
+) -> f64 {
+    let domain = solution.domain;
+    let mut sum = 0.0;
+    for element in domain.elements.iter() {
+        let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+        let degree = 2 * domain.element_basis(element).degree() + EXTRA_DEGREE;
+        let rule = dunavant::rule(degree.min(dunavant::MAX_DEGREE));
+        let area = utils::jacobian(p1, p2, p3).determinant().abs();
+        sum += area * rule.integrate(|x, y| f(element, &utils::physical_point(p1, p2, p3, x, y)));
+    }
+    return sum;
+}
+
+/**
+ * Integral of f(physical point) along the edge of the element
+ */
+pub(crate) fn edge_integral(
+    element: &TriangleElementL1,
+    edge_index: usize,
+    degree: usize,
+    f: &dyn Fn(&Point) -> f64,
+) -> f64 {
+    let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+    let rule = gauss_legendre::rule(2 * degree + EXTRA_DEGREE);
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    return length
+        * rule.integrate(|l| {
+            let (x, y) = utils::edge_coordinates(edge_index, l);
+            f(&utils::physical_point(p1, p2, p3, x, y))
+        });
+}
+
+#[cfg(test)]
+mod function_norms {
+    use super::*;
+    use crate::triangle::{
+        basis::dubiner,
+        mesh,
+        system_builder::{builder, domain::Domain},
+    };
+    use nalgebra::DMatrix;
+
+    fn interpolate(domain: &Domain, field: &dyn Fn(f64, f64) -> f64) -> DMatrix<f64> {
+        let mut coefficients = DMatrix::<f64>::zeros(domain.size(), 1);
+        for element in domain.elements.iter() {
+            let degree = domain.element_basis(element).degree();
+            let local = dubiner::linear_to_modal(
+                degree,
+                field(element.p1.x, element.p1.y),
+                field(element.p2.x, element.p2.y),
+                field(element.p3.x, element.p3.y),
+            );
+            for (index, value) in domain.element_indexes(element).iter().zip(local.iter()) {
+                coefficients[(*index, 0)] = *value;
+            }
+        }
+        return coefficients;
+    }
+
+    #[test]
+    fn sample_1() {
+        /*
+            Linear interpolant of u = x^2 over the unit square:
+            the error norms decrease with the mesh size as h^2 and h
+        */
+        let exact = |point: &Point| point.x * point.x;
+        let gradient = |point: &Point| (2.0 * point.x, 0.0);
+        let mut errors = Vec::new();
+        for n in [4, 8].iter() {
+            let mut domain = Domain::new_modal(1).unwrap();
+            mesh::rectangle((0.0, 0.0), (1.0, 1.0), *n, *n, &mut domain);
+            let coefficients = interpolate(&domain, &|x, _| x * x);
+            let solution = DgSolution::new(&domain, &coefficients);
+            errors.push((l2(&solution, &exact), h1_seminorm(&solution, &gradient)));
+        }
+        let l2_order = (errors[0].0 / errors[1].0).log2();
+        let h1_order = (errors[0].1 / errors[1].1).log2();
+        assert!((l2_order - 2.0).abs() < 0.05);
+        assert!((h1_order - 1.0).abs() < 0.05);
+
+        /* On a single cell [0,1]^2 the gradient error is |2x - 1|, whose norm is sqrt(1/3) */
+        let mut domain = Domain::new_modal(1).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 1, 1, &mut domain);
+        let solution = DgSolution::new(&domain, &interpolate(&domain, &|x, _| x * x));
+        assert!((h1_seminorm(&solution, &gradient) - (1.0_f64 / 3.0).sqrt()).abs() < 1.0E-12);
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Unit step across x = 1 of the rectangle [0,2]x[0,1], against u = 0:
+            only the penalized jump along the unit inner edge contributes
+        */
+        let sigma = 10.0;
+        let mut domain = Domain::new_modal(1).unwrap();
+        mesh::rectangle((0.0, 0.0), (2.0, 1.0), 2, 1, &mut domain);
+        let coefficients = interpolate(&domain, &|_, _| 0.0);
+        let mut step = coefficients.clone();
+        for element in domain.elements.iter() {
+            if element.p1.x + element.p2.x + element.p3.x > 3.0 {
+                let local = dubiner::linear_to_modal(1, 1.0, 1.0, 1.0);
+                for (index, value) in domain.element_indexes(element).iter().zip(local.iter()) {
+                    step[(*index, 0)] = *value;
+                }
+            }
+        }
+        let solution = DgSolution::new(&domain, &step);
+        let zero = |_: &Point| 0.0;
+        let flat = |_: &Point| (0.0, 0.0);
+        assert!((l2(&solution, &zero) - 1.0).abs() < 1.0E-12);
+        assert!(h1_seminorm(&solution, &flat) < 1.0E-12);
+        assert!((broken_h1(&solution, &zero, &flat) - 1.0).abs() < 1.0E-12);
+        assert!((energy(sigma, &solution, &zero, &flat) - sigma.sqrt()).abs() < 1.0E-12);
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            Interior penalty solution of a linear harmonic field with dirichlet edges
+            is exact in every norm, dirichlet mismatches included
+        */
+        let sigma = 10.0;
+        let field = |x: f64, y: f64| 1.0 + x - 2.0 * y;
+        let mut domain = Domain::new_modal(2).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 3, 3, &mut domain);
+        let edges = boundary
+            .left
+            .iter()
+            .chain(boundary.right.iter())
+            .chain(boundary.bottom.iter())
+            .chain(boundary.top.iter());
+        for edge in edges {
+            let values = vec![field(edge.p1.x, edge.p1.y), field(edge.p2.x, edge.p2.y)];
+            domain.insert_dirichlet_constraint(edge, values);
+        }
+        let (system_matrix, extern_matrix) = builder::build(sigma, &domain);
+        let coefficients = system_matrix.lu().solve(&extern_matrix).unwrap();
+        let solution = DgSolution::new(&domain, &coefficients);
+
+        let exact = |point: &Point| field(point.x, point.y);
+        let gradient = |_: &Point| (1.0, -2.0);
+        assert!(l2(&solution, &exact) < 1.0E-10);
+        assert!(energy(sigma, &solution, &exact, &gradient) < 1.0E-10);
+
+        /* Shifted by a constant, only the L2 norm and the dirichlet mismatch grow */
+        let shifted = |point: &Point| exact(point) + 0.1;
+        assert!((l2(&solution, &shifted) - 0.1).abs() < 1.0E-10);
+        assert!(h1_seminorm(&solution, &gradient) < 1.0E-10);
+        let boundary = (4.0 * 3.0 * sigma * 4.0 / (1.0 / 3.0) * 0.01 / 3.0).sqrt();
+        assert!((energy(sigma, &solution, &shifted, &gradient) - boundary).abs() < 1.0E-8);
+    }
+}
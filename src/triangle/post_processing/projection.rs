@@ -0,0 +1,191 @@
+use nalgebra::{DMatrix, DVector};
+
+use crate::common::point::Point;
+use crate::triangle::{
+    basis::{dubiner, shape::Basis},
+    integrands::{l2_mass, utils},
+    post_processing::dg_solution::DgSolution,
+    quadrature::dunavant,
+    system_builder::domain::Domain,
+};
+
+/* Quadrature exceeds the element degree, the projected function not being polynomial */
+const EXTRA_DEGREE: usize = 2;
+
+/**
+ * Element local L2 projection of f into the domain bases:
+ *  M_K c_K = integral over K of f phi_i
+ */
+pub fn l2_projection(domain: &Domain, f: &dyn Fn(&Point) -> f64) -> DMatrix<f64> {
+    return project(domain, f, EXTRA_DEGREE);
+}
+
+/**
+ * Interpolation of f at the nodes of each element: the vertices for nodal bases,
+ * the equispaced lattice of dubiner::nodes for modal ones
+ */
+pub fn interpolation(domain: &Domain, f: &dyn Fn(&Point) -> f64) -> DMatrix<f64> {
+    let mut coefficients = DMatrix::<f64>::zeros(domain.size(), 1);
+    for element in domain.elements.iter() {
+        let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+        let local = match domain.element_basis(element) {
+            Basis::Nodal => DVector::from_vec(vec![f(p1), f(p2), f(p3)]),
+            Basis::Modal(degree) => {
+                let nodal = DVector::from_iterator(
+                    dubiner::size(degree),
+                    dubiner::nodes(degree)
+                        .iter()
+                        .map(|(x, y)| f(&utils::physical_point(p1, p2, p3, *x, *y))),
+                );
+                dubiner::nodal_to_modal(degree, &nodal)
+            }
+        };
+        for (index, value) in domain.element_indexes(element).iter().zip(local.iter()) {
+            coefficients[(*index, 0)] = *value;
+        }
+    }
+    return coefficients;
+}
+
+/**
+ * Transfer of a solution into another domain, refined or unrelated: each quadrature point
+ * of the target elements is located in the source domain, and the values are projected
+ * element by element. Points outside the source domain take zero
+ */
+pub fn transfer(source: &DgSolution, target: &Domain) -> DMatrix<f64> {
+    /* Exact for source polynomials within the target spaces, over nested meshes */
+    let degree = source
+        .domain
+        .elements
+        .iter()
+        .map(|element| source.domain.element_basis(element).degree())
+        .max()
+        .unwrap_or(0);
+    return project(
+        target,
+        &|point| source.evaluate(point).unwrap_or(0.0),
+        degree,
+    );
+}
+
+/**
+ * Element local L2 projection, quadrature exact for degree of the basis plus extra
+ */
+fn project(domain: &Domain, f: &dyn Fn(&Point) -> f64, extra: usize) -> DMatrix<f64> {
+    let mut coefficients = DMatrix::<f64>::zeros(domain.size(), 1);
+    for element in domain.elements.iter() {
+        let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+        let basis = domain.element_basis(element);
+        let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+
+        let rule = dunavant::rule((basis.degree() + extra).min(dunavant::MAX_DEGREE));
+        let mut load = DVector::<f64>::zeros(basis.size());
+        for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+            let value = f(&utils::physical_point(p1, p2, p3, *x, *y));
+            load.axpy(value * weight * determinant, &basis.evaluate(*x, *y), 1.0);
+        }
+        let local = l2_mass::matrix_basis(p1, p2, p3, &basis)
+            .cholesky()
+            .expect("Not expected singular element mass matrix")
+            .solve(&load);
+        for (index, value) in domain.element_indexes(element).iter().zip(local.iter()) {
+            coefficients[(*index, 0)] = *value;
+        }
+    }
+    return coefficients;
+}
+
+#[cfg(test)]
+mod local_projection {
+    use super::*;
+    use crate::triangle::{mesh, post_processing::error_norms};
+
+    #[test]
+    fn sample_1() {
+        /*
+            Quadratic field into modal bases of degree 2, linear one into nodal bases:
+            projection and interpolation reproduce them
+        */
+        let quadratic = |point: &Point| 1.0 + point.x * point.y - 2.0 * point.y * point.y;
+        let linear = |point: &Point| 2.0 - point.x + 3.0 * point.y;
+        let mut modal = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (2.0, 1.0), 3, 2, &mut modal);
+        let mut nodal = Domain::new_empty();
+        mesh::rectangle((0.0, 0.0), (2.0, 1.0), 3, 2, &mut nodal);
+
+        for (domain, field) in [
+            (&modal, &quadratic as &dyn Fn(&Point) -> f64),
+            (&nodal, &linear),
+        ]
+        .iter()
+        {
+            let projected = l2_projection(domain, *field);
+            let interpolated = interpolation(domain, *field);
+            assert!((&projected - &interpolated).amax() < 1.0E-12);
+            let solution = DgSolution::new(domain, &projected);
+            assert!(error_norms::l2(&solution, *field) < 1.0E-12);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Projection of u = exp(x + y) keeps the integral over the domain,
+            and converges with third order in degree 2
+        */
+        let exact = |point: &Point| (point.x + point.y).exp();
+        let mut errors = Vec::new();
+        for n in [4, 8].iter() {
+            let mut domain = Domain::new_modal(2).unwrap();
+            mesh::rectangle((0.0, 0.0), (1.0, 1.0), *n, *n, &mut domain);
+            let solution = DgSolution::new(&domain, &l2_projection(&domain, &exact));
+            errors.push(error_norms::l2(&solution, &exact));
+
+            /* The first mode carries the mean: the integral is kept to quadrature error */
+            let integral: f64 = domain
+                .elements
+                .iter()
+                .map(|element| {
+                    let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+                    let rule = dunavant::rule(4);
+                    let area = utils::jacobian(p1, p2, p3).determinant().abs();
+                    area * rule.integrate(|x, y| {
+                        solution.evaluate_in(element, &utils::physical_point(p1, p2, p3, x, y))
+                    })
+                })
+                .sum();
+            assert!((integral - (1.0_f64.exp() - 1.0).powi(2)).abs() < 1.0E-8);
+        }
+        assert!(((errors[0] / errors[1]).log2() - 3.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            Transfer of a quadratic field into the nested refinement of its mesh is exact,
+            as is the transfer of a linear field into an unrelated mesh
+        */
+        let quadratic = |point: &Point| point.x * point.x - point.x * point.y + 0.5;
+        let mut coarse = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 2, 2, &mut coarse);
+        let mut fine = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 4, 4, &mut fine);
+        let source = DgSolution::new(&coarse, &l2_projection(&coarse, &quadratic));
+        let transferred = DgSolution::new(&fine, &transfer(&source, &fine));
+        assert!(error_norms::l2(&transferred, &quadratic) < 1.0E-12);
+
+        let linear = |point: &Point| 1.0 + point.x - 2.0 * point.y;
+        let mut disc = Domain::new_modal(1).unwrap();
+        mesh::disc((0.0, 0.0), 1.0, 3, &mut disc);
+        let mut square = Domain::new_empty();
+        mesh::rectangle((-0.5, -0.5), (0.5, 0.5), 5, 4, &mut square);
+        let source = DgSolution::new(&disc, &interpolation(&disc, &linear));
+        let transferred = DgSolution::new(&square, &transfer(&source, &square));
+        assert!(error_norms::l2(&transferred, &linear) < 1.0E-12);
+
+        /* Target points outside the source domain take zero */
+        let mut outer = Domain::new_modal(0).unwrap();
+        mesh::rectangle((2.0, 2.0), (3.0, 3.0), 1, 1, &mut outer);
+        assert!(transfer(&source, &outer).amax() < 1.0E-14);
+    }
+}
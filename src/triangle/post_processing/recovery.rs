@@ -0,0 +1,269 @@
+use nalgebra::{DMatrix, DVector};
+
+use crate::common::point::Point;
+use crate::triangle::{
+    element::TriangleElementL1, integrands::utils, post_processing::dg_solution::DgSolution,
+    quadrature::dunavant,
+};
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/**
+ * Weight of each element value in the average at a vertex
+ */
+pub enum Weighting {
+    Uniform,
+    Area,
+}
+
+/**
+ * Continuous P1 field: one value per mesh vertex, linearly interpolated within elements.
+ *  Vertices follow the order of first appearance over the domain elements
+ */
+pub struct ContinuousField {
+    pub vertices: Vec<Rc<Point>>,
+    /* One row per vertex, one column per component */
+    pub values: DMatrix<f64>,
+    vertex_mapping: HashMap<Rc<Point>, usize>,
+}
+
+impl ContinuousField {
+    /**
+     * Values at the given vertex, none if it is not a mesh vertex
+     */
+    pub fn vertex_value(&self, vertex: &Point) -> Option<DVector<f64>> {
+        return self
+            .vertex_mapping
+            .get(vertex)
+            .map(|index| self.values.row(*index).transpose());
+    }
+
+    /**
+     * Linear interpolation of the vertex values of the element at a physical point
+     */
+    pub fn interpolate_in(&self, element: &TriangleElementL1, point: &Point) -> DVector<f64> {
+        let (x, y) = utils::reference_coordinates(&element.p1, &element.p2, &element.p3, point);
+        let value = |vertex: &Rc<Point>| {
+            let index = *self
+                .vertex_mapping
+                .get(vertex)
+                .expect("Not expected element outside the field");
+            self.values.row(index).transpose()
+        };
+        return value(&element.p1) * (1.0 - x - y)
+            + value(&element.p2) * x
+            + value(&element.p3) * y;
+    }
+}
+
+/**
+ * Continuous field averaging at each vertex the values of every element sharing it
+ */
+pub fn nodal_average(solution: &DgSolution, weighting: &Weighting) -> ContinuousField {
+    return vertex_average(solution, weighting, 1, &|element, vertex| {
+        DVector::from_element(1, solution.evaluate_in(element, vertex))
+    });
+}
+
+/**
+ * Zienkiewicz-Zhu recovered gradient: averages at each vertex the gradients
+ * of every element sharing it, two columns (du/dx, du/dy)
+ *
+ *  Reference:
+ *      Zienkiewicz, O.C.; Zhu, J.Z. A simple error estimator and adaptive procedure
+ *      for practical engineering analysis. Int. J. Numer. Meth. Eng. 24 (1987) 337-357.
+ */
+pub fn recovered_gradient(solution: &DgSolution, weighting: &Weighting) -> ContinuousField {
+    return vertex_average(solution, weighting, 2, &|element, vertex| {
+        let (gx, gy) = solution.gradient_in(element, vertex);
+        DVector::from_vec(vec![gx, gy])
+    });
+}
+
+/**
+ * Error indicator of each element, in domain order: L2 norm over the element
+ * of the recovered gradient minus the gradient of the solution
+ */
+pub fn indicators(solution: &DgSolution, weighting: &Weighting) -> Vec<f64> {
+    let domain = solution.domain;
+    let recovered = recovered_gradient(solution, weighting);
+    return domain
+        .elements
+        .iter()
+        .map(|element| {
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let degree = domain.element_basis(element).degree();
+            let rule = dunavant::rule((2 * degree.max(1)).min(dunavant::MAX_DEGREE));
+            let area = utils::jacobian(p1, p2, p3).determinant().abs();
+            let squared = rule.integrate(|x, y| {
+                let point = utils::physical_point(p1, p2, p3, x, y);
+                let (gx, gy) = solution.gradient_in(element, &point);
+                let g = recovered.interpolate_in(element, &point);
+                (g[0] - gx).powi(2) + (g[1] - gy).powi(2)
+            });
+            (area * squared).sqrt()
+        })
+        .collect();
+}
+
+/**
+ * Weighted average at each vertex of the element values f(element, vertex),
+ * as many components as given
+ */
+fn vertex_average(
+    solution: &DgSolution,
+    weighting: &Weighting,
+    components: usize,
+    f: &dyn Fn(&Rc<TriangleElementL1>, &Point) -> DVector<f64>,
+) -> ContinuousField {
+    let mut vertices: Vec<Rc<Point>> = Vec::new();
+    let mut vertex_mapping: HashMap<Rc<Point>, usize> = HashMap::new();
+    let mut sums: Vec<DVector<f64>> = Vec::new();
+    let mut weights: Vec<f64> = Vec::new();
+
+    for element in solution.domain.elements.iter() {
+        let weight = match weighting {
+            Weighting::Uniform => 1.0,
+            Weighting::Area => {
+                0.5 * utils::jacobian(&element.p1, &element.p2, &element.p3)
+                    .determinant()
+                    .abs()
+            }
+        };
+        for vertex in [&element.p1, &element.p2, &element.p3].iter() {
+            let index = *vertex_mapping.entry(Rc::clone(vertex)).or_insert_with(|| {
+                vertices.push(Rc::clone(vertex));
+                sums.push(DVector::zeros(components));
+                weights.push(0.0);
+                vertices.len() - 1
+            });
+            sums[index] += f(element, vertex) * weight;
+            weights[index] += weight;
+        }
+    }
+
+    let mut values = DMatrix::<f64>::zeros(vertices.len(), components);
+    for (index, (sum, weight)) in sums.iter().zip(weights.iter()).enumerate() {
+        values.set_row(index, &(sum / *weight).transpose());
+    }
+    return ContinuousField {
+        vertices,
+        values,
+        vertex_mapping,
+    };
+}
+
+#[cfg(test)]
+mod vertex_recovery {
+    use super::*;
+    use crate::triangle::{
+        basis::{dubiner, shape::Basis},
+        mesh,
+        post_processing::error_norms,
+        system_builder::domain::Domain,
+    };
+
+    fn interpolate(domain: &Domain, field: &dyn Fn(f64, f64) -> f64) -> DMatrix<f64> {
+        let mut coefficients = DMatrix::<f64>::zeros(domain.size(), 1);
+        for element in domain.elements.iter() {
+            let (u1, u2, u3) = (
+                field(element.p1.x, element.p1.y),
+                field(element.p2.x, element.p2.y),
+                field(element.p3.x, element.p3.y),
+            );
+            let local = match domain.element_basis(element) {
+                Basis::Nodal => DVector::from_vec(vec![u1, u2, u3]),
+                basis => dubiner::linear_to_modal(basis.degree(), u1, u2, u3),
+            };
+            for (index, value) in domain.element_indexes(element).iter().zip(local.iter()) {
+                coefficients[(*index, 0)] = *value;
+            }
+        }
+        return coefficients;
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Linear fields are recovered exactly, with vanishing indicators */
+        let field = |x: f64, y: f64| 1.0 + 2.0 * x - 3.0 * y;
+        let mut domain = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (2.0, 1.0), 4, 3, &mut domain);
+        let solution = DgSolution::new(&domain, &interpolate(&domain, &field));
+        for weighting in [Weighting::Uniform, Weighting::Area].iter() {
+            let average = nodal_average(&solution, weighting);
+            assert_eq!(average.vertices.len(), 20);
+            for vertex in average.vertices.iter() {
+                let value = average.vertex_value(vertex).unwrap();
+                assert!((value[0] - field(vertex.x, vertex.y)).abs() < 1.0E-12);
+            }
+            let gradient = recovered_gradient(&solution, weighting);
+            assert!((gradient.values.column(0).add_scalar(-2.0)).amax() < 1.0E-12);
+            assert!((gradient.values.column(1).add_scalar(3.0)).amax() < 1.0E-12);
+            assert!(indicators(&solution, weighting)
+                .iter()
+                .all(|e| *e < 1.0E-12));
+        }
+        assert!(nodal_average(&solution, &Weighting::Uniform)
+            .vertex_value(&Point::new(0.3, 0.3))
+            .is_none());
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Unit step across x = 1 between the squares [0,1]x[0,1] and [1,3]x[0,1]:
+            shared vertices average the element counts, or the element areas
+        */
+        let mut domain = Domain::new_empty();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 1, 1, &mut domain);
+        mesh::rectangle((1.0, 0.0), (3.0, 1.0), 1, 1, &mut domain);
+        let mut coefficients = interpolate(&domain, &|_, _| 0.0);
+        for element in domain.elements.iter().skip(2) {
+            for index in domain.element_indexes(element).iter() {
+                coefficients[(*index, 0)] = 1.0;
+            }
+        }
+        let solution = DgSolution::new(&domain, &coefficients);
+
+        let uniform = nodal_average(&solution, &Weighting::Uniform);
+        let area = nodal_average(&solution, &Weighting::Area);
+        assert_eq!(uniform.vertices.len(), 6);
+        let at = |field: &ContinuousField, x, y| field.vertex_value(&Point::new(x, y)).unwrap()[0];
+        assert!((at(&uniform, 1.0, 0.0) - 1.0 / 3.0).abs() < 1.0E-14);
+        assert!((at(&uniform, 1.0, 1.0) - 2.0 / 3.0).abs() < 1.0E-14);
+        assert!((at(&area, 1.0, 0.0) - 0.5).abs() < 1.0E-14);
+        assert!((at(&area, 1.0, 1.0) - 0.8).abs() < 1.0E-14);
+        assert!(at(&area, 0.0, 1.0).abs() < 1.0E-14);
+        assert!((at(&area, 3.0, 0.0) - 1.0).abs() < 1.0E-14);
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            Nodal interpolant of u = x^2: the recovered gradient is exact at interior vertices,
+            and the indicators estimate the broken H1 error
+        */
+        let exact_gradient = |point: &Point| (2.0 * point.x, 0.0);
+        let mut domain = Domain::new_empty();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 8, 8, &mut domain);
+        let solution = DgSolution::new(&domain, &interpolate(&domain, &|x, _| x * x));
+
+        let gradient = recovered_gradient(&solution, &Weighting::Area);
+        for vertex in gradient.vertices.iter() {
+            if vertex.x > 0.0 && vertex.x < 1.0 && vertex.y > 0.0 && vertex.y < 1.0 {
+                let value = gradient.vertex_value(vertex).unwrap();
+                assert!((value[0] - 2.0 * vertex.x).abs() < 1.0E-12);
+                assert!(value[1].abs() < 1.0E-12);
+            }
+        }
+
+        let estimate = indicators(&solution, &Weighting::Area)
+            .iter()
+            .map(|e| e * e)
+            .sum::<f64>()
+            .sqrt();
+        let error = error_norms::h1_seminorm(&solution, &exact_gradient);
+        assert!((estimate / error - 1.0).abs() < 0.1);
+    }
+}
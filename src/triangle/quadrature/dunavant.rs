@@ -0,0 +1,212 @@
+/**
+ * Dunavant symmetric quadrature rules for the default triangle ((0,0),(1,0), (0,1))
+ *
+ *  Reference:
+ *      Dunavant, D.A. High degree efficient symmetrical Gaussian quadrature
+ *      rules for the triangle. International Journal for Numerical Methods
+ *      in Engineering, 1985. Vol 21, 1129-1148.
+ */
+pub struct TriangleRule {
+    /* Reference coordinates (x, y) of each integration point */
+    pub points: Vec<(f64, f64)>,
+    /* Weights sum up to the default triangle area: 1/2 */
+    pub weights: Vec<f64>,
+}
+
+pub const MAX_DEGREE: usize = 8;
+
+impl TriangleRule {
+    fn new() -> Self {
+        Self {
+            points: Vec::new(),
+            weights: Vec::new(),
+        }
+    }
+
+    /**
+     * Barycentric centroid (1/3, 1/3, 1/3)
+     */
+    fn centroid(&mut self, weight: f64) {
+        self.points.push((1.0 / 3.0, 1.0 / 3.0));
+        self.weights.push(weight / 2.0);
+    }
+
+    /**
+     * Barycentric orbit (a, b, b), where a + 2b = 1
+     */
+    fn orbit_3(&mut self, a: f64, b: f64, weight: f64) {
+        for (x, y) in [(b, b), (a, b), (b, a)] {
+            self.points.push((x, y));
+            self.weights.push(weight / 2.0);
+        }
+    }
+
+    /**
+     * Barycentric orbit (a, b, c), where a + b + c = 1
+     */
+    fn orbit_6(&mut self, a: f64, b: f64, c: f64, weight: f64) {
+        for (x, y) in [(b, c), (c, b), (a, c), (c, a), (a, b), (b, a)] {
+            self.points.push((x, y));
+            self.weights.push(weight / 2.0);
+        }
+    }
+
+    /**
+     * Integrates f(x, y) over the default triangle
+     */
+    pub fn integrate<F: Fn(f64, f64) -> f64>(&self, f: F) -> f64 {
+        let mut sum = 0.0;
+        for ((x, y), w) in self.points.iter().zip(self.weights.iter()) {
+            sum += w * f(*x, *y);
+        }
+        return sum;
+    }
+}
+
+/**
+ * Rule integrating exactly every polynomial up to the requested degree
+ */
+pub fn rule(degree: usize) -> TriangleRule {
+    let mut rule = TriangleRule::new();
+    match degree {
+        0 | 1 => {
+            rule.centroid(1.0);
+        }
+        2 => {
+            rule.orbit_3(2.0 / 3.0, 1.0 / 6.0, 1.0 / 3.0);
+        }
+        3 => {
+            rule.centroid(-0.5625);
+            rule.orbit_3(0.6, 0.2, 0.520_833_333_333_333);
+        }
+        4 => {
+            rule.orbit_3(
+                0.108_103_018_168_070,
+                0.445_948_490_915_965,
+                0.223_381_589_678_011,
+            );
+            rule.orbit_3(
+                0.816_847_572_980_459,
+                0.091_576_213_509_771,
+                0.109_951_743_655_322,
+            );
+        }
+        5 => {
+            rule.centroid(0.225);
+            rule.orbit_3(
+                0.059_715_871_789_770,
+                0.470_142_064_105_115,
+                0.132_394_152_788_506,
+            );
+            rule.orbit_3(
+                0.797_426_985_353_087,
+                0.101_286_507_323_456,
+                0.125_939_180_544_827,
+            );
+        }
+        6 => {
+            rule.orbit_3(
+                0.501_426_509_658_179,
+                0.249_286_745_170_910,
+                0.116_786_275_726_379,
+            );
+            rule.orbit_3(
+                0.873_821_971_016_996,
+                0.063_089_014_491_502,
+                0.050_844_906_370_207,
+            );
+            rule.orbit_6(
+                0.053_145_049_844_817,
+                0.310_352_451_033_784,
+                0.636_502_499_121_399,
+                0.082_851_075_618_374,
+            );
+        }
+        7 => {
+            rule.centroid(-0.149_570_044_467_682);
+            rule.orbit_3(
+                0.479_308_067_841_920,
+                0.260_345_966_079_040,
+                0.175_615_257_433_208,
+            );
+            rule.orbit_3(
+                0.869_739_794_195_568,
+                0.065_130_102_902_216,
+                0.053_347_235_608_838,
+            );
+            rule.orbit_6(
+                0.048_690_315_425_316,
+                0.312_865_496_004_874,
+                0.638_444_188_569_810,
+                0.077_113_760_890_257,
+            );
+        }
+        8 => {
+            rule.centroid(0.144_315_607_677_787);
+            rule.orbit_3(
+                0.081_414_823_414_554,
+                0.459_292_588_292_723,
+                0.095_091_634_267_285,
+            );
+            rule.orbit_3(
+                0.658_861_384_496_480,
+                0.170_569_307_751_760,
+                0.103_217_370_534_718,
+            );
+            rule.orbit_3(
+                0.898_905_543_365_938,
+                0.050_547_228_317_031,
+                0.032_458_497_623_198,
+            );
+            rule.orbit_6(
+                0.008_394_777_409_958,
+                0.263_112_829_634_638,
+                0.728_492_392_955_404,
+                0.027_230_314_174_435,
+            );
+        }
+        _ => panic!("Not expected Dunavant rule of degree greater than 8"),
+    }
+    return rule;
+}
+
+#[cfg(test)]
+mod exactness {
+    use super::*;
+
+    fn factorial(n: usize) -> f64 {
+        (1..=n).fold(1.0, |acc, k| acc * k as f64)
+    }
+
+    #[test]
+    fn sample_1() {
+        /*
+            Integral of x^a y^b over the default triangle is a! b! / (a + b + 2)!
+        */
+        for degree in 0..=MAX_DEGREE {
+            let rule = rule(degree);
+            for a in 0..=degree {
+                for b in 0..=(degree - a) {
+                    let expected = factorial(a) * factorial(b) / factorial(a + b + 2);
+                    let integral = rule.integrate(|x, y| x.powi(a as i32) * y.powi(b as i32));
+                    assert!(float_cmp::approx_eq!(
+                        f64,
+                        integral,
+                        expected,
+                        epsilon = 1.0E-12f64
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Points lie inside the default triangle */
+        for degree in 0..=MAX_DEGREE {
+            for (x, y) in rule(degree).points.iter() {
+                assert!(*x > 0.0 && *y > 0.0 && x + y < 1.0);
+            }
+        }
+    }
+}
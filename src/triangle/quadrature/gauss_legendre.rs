@@ -0,0 +1,134 @@
+use std::f64::consts::PI;
+
+/**
+ * Gauss-Legendre rules over the edge parameter l in [0, 1]
+ */
+pub struct EdgeRule {
+    /* Edge parameter of each integration point */
+    pub points: Vec<f64>,
+    /* Weights sum up to the parameter length: 1 */
+    pub weights: Vec<f64>,
+}
+
+impl EdgeRule {
+    /**
+     * Integrates f(l) over [0, 1]
+     */
+    pub fn integrate<F: Fn(f64) -> f64>(&self, f: F) -> f64 {
+        let mut sum = 0.0;
+        for (l, w) in self.points.iter().zip(self.weights.iter()) {
+            sum += w * f(*l);
+        }
+        return sum;
+    }
+}
+
+/**
+ * Legendre polynomial P_n(x) and its derivative, by the three term recurrence
+ */
+fn legendre(n: usize, x: f64) -> (f64, f64) {
+    let mut p_prev = 1.0;
+    let mut p = x;
+    if n == 0 {
+        return (1.0, 0.0);
+    }
+    for k in 2..=n {
+        let k = k as f64;
+        let p_next = ((2.0 * k - 1.0) * x * p - (k - 1.0) * p_prev) / k;
+        p_prev = p;
+        p = p_next;
+    }
+    let n = n as f64;
+    let dp = n * (x * p - p_prev) / (x * x - 1.0);
+    return (p, dp);
+}
+
+/**
+ * Rule with n points, computed by Newton iterations on the roots of P_n
+ */
+pub fn points(n: usize) -> EdgeRule {
+    let mut rule = EdgeRule {
+        points: Vec::with_capacity(n),
+        weights: Vec::with_capacity(n),
+    };
+
+    for i in 0..n {
+        /* Chebyshev-like initial guess for the i-th root */
+        let mut x = -(PI * (i as f64 + 0.75) / (n as f64 + 0.5)).cos();
+        for _ in 0..100 {
+            let (p, dp) = legendre(n, x);
+            let dx = p / dp;
+            x -= dx;
+            if dx.abs() < 1.0E-15 {
+                break;
+            }
+        }
+        let (_, dp) = legendre(n, x);
+        let weight = 2.0 / ((1.0 - x * x) * dp * dp);
+
+        /* Maps [-1, 1] into [0, 1] */
+        rule.points.push((x + 1.0) / 2.0);
+        rule.weights.push(weight / 2.0);
+    }
+    return rule;
+}
+
+/**
+ * Rule integrating exactly every polynomial up to the requested degree
+ */
+pub fn rule(degree: usize) -> EdgeRule {
+    return points(degree / 2 + 1);
+}
+
+#[cfg(test)]
+mod exactness {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /* Integral of l^a over [0, 1] is 1 / (a + 1) */
+        for degree in 0..20 {
+            let rule = rule(degree);
+            for a in 0..=degree {
+                let integral = rule.integrate(|l| l.powi(a as i32));
+                assert!(float_cmp::approx_eq!(
+                    f64,
+                    integral,
+                    1.0 / (a as f64 + 1.0),
+                    epsilon = 1.0E-13f64
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Two points rule has nodes at 1/2 -+ 1/(2 sqrt(3)) */
+        let rule = points(2);
+        let offset = 0.5 / 3.0_f64.sqrt();
+        assert!(float_cmp::approx_eq!(
+            f64,
+            rule.points[0],
+            0.5 - offset,
+            epsilon = 1.0E-15f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            rule.points[1],
+            0.5 + offset,
+            epsilon = 1.0E-15f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            rule.weights[0],
+            0.5,
+            epsilon = 1.0E-15f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            rule.weights[1],
+            0.5,
+            epsilon = 1.0E-15f64
+        ));
+    }
+}
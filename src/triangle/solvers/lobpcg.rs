@@ -0,0 +1,311 @@
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+
+use crate::common::error::Error;
+
+/**
+ * Operator applied to each column of a block of vectors
+ */
+pub type Operator<'a> = &'a dyn Fn(&DMatrix<f64>) -> DMatrix<f64>;
+
+/**
+ * Each pair converges once |A x - lambda M x| <= tolerance * (|lambda| + 1) * |M x|,
+ * so that zero eigenvalues also converge
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            tolerance: 1.0E-6,
+            max_iterations: 1000,
+        }
+    }
+}
+
+/**
+ * Eigenvalues in increasing order, with their M orthonormal eigenvectors as columns
+ */
+pub struct EigenPairs {
+    pub values: Vec<f64>,
+    pub vectors: DMatrix<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/**
+ * Lowest count eigenpairs of A x = lambda M x, A symmetric and M symmetric positive
+ * definite. Only applies A, M and the preconditioner, which approximates the inverse of A.
+ *  The initial block may take more columns than count: the extra ones are iterated
+ *  as well, which speeds up the convergence of the last wanted pairs, but they need
+ *  not converge. Numerically dependent initial columns are dropped, and the solver
+ *  fails if fewer than count independent ones remain.
+ *
+ *  Reference:
+ *      Knyazev, A.V. Toward the optimal preconditioned eigensolver: locally optimal
+ *      block preconditioned conjugate gradient method. SISC, 2001. Vol 23, 517-541.
+ */
+pub fn solve(
+    stiffness: Operator,
+    mass: Operator,
+    preconditioner: Operator,
+    initial: &DMatrix<f64>,
+    count: usize,
+    options: &Options,
+) -> Result<EigenPairs, Error> {
+    let (values, coefficients) = rayleigh_ritz(initial, &stiffness(initial), &mass(initial));
+    let block = values.len();
+    if block < count {
+        return Err(Error::RankDeficient);
+    }
+    let coefficients = coefficients.columns(0, block);
+    let mut x = initial * coefficients;
+    let (mut ax, mut mx) = (stiffness(&x), mass(&x));
+    let mut lambda = values[..block].to_vec();
+    /* Previous directions with their images by A and M */
+    let mut directions: Option<(DMatrix<f64>, DMatrix<f64>, DMatrix<f64>)> = None;
+
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < options.max_iterations {
+        let residual = &ax - &mx * DMatrix::from_diagonal(&DVector::from_vec(lambda.clone()));
+        converged = (0..count).all(|j| {
+            residual.column(j).norm()
+                <= options.tolerance * (lambda[j].abs() + 1.0) * mx.column(j).norm()
+        });
+        if converged {
+            break;
+        }
+        iterations += 1;
+
+        let mut w = preconditioner(&residual);
+        normalize_columns(&mut w);
+        let (aw, mw) = (stiffness(&w), mass(&w));
+        let (basis, a_basis, m_basis) = match directions.as_ref() {
+            Some((p, ap, mp)) => (
+                concatenate(&[&x, &w, p]),
+                concatenate(&[&ax, &aw, ap]),
+                concatenate(&[&mx, &mw, mp]),
+            ),
+            None => (
+                concatenate(&[&x, &w]),
+                concatenate(&[&ax, &aw]),
+                concatenate(&[&mx, &mw]),
+            ),
+        };
+
+        let (values, coefficients) = rayleigh_ritz(&basis, &a_basis, &m_basis);
+        let coefficients = coefficients.columns(0, block);
+        let tail = basis.ncols() - block;
+        let update = coefficients.rows(block, tail);
+        let mut p = basis.columns(block, tail) * update;
+        let mut ap = a_basis.columns(block, tail) * update;
+        let mut mp = m_basis.columns(block, tail) * update;
+        for j in 0..block {
+            let norm = p.column(j).norm();
+            if norm > 0.0 {
+                p.column_mut(j).unscale_mut(norm);
+                ap.column_mut(j).unscale_mut(norm);
+                mp.column_mut(j).unscale_mut(norm);
+            }
+        }
+
+        x = &basis * coefficients;
+        ax = &a_basis * coefficients;
+        mx = &m_basis * coefficients;
+        lambda = values[..block].to_vec();
+        directions = Some((p, ap, mp));
+    }
+
+    lambda.truncate(count);
+    return Ok(EigenPairs {
+        values: lambda,
+        vectors: x.columns(0, count).clone_owned(),
+        iterations,
+        converged,
+    });
+}
+
+/**
+ * Ritz values in increasing order and coefficients of the ritz vectors over the basis,
+ * given the images of the basis by A and M.
+ *  The basis is made M orthonormal through the eigenvectors of its gram matrix,
+ *  dropping directions that are numerically dependent
+ */
+fn rayleigh_ritz(
+    basis: &DMatrix<f64>,
+    stiffness: &DMatrix<f64>,
+    mass: &DMatrix<f64>,
+) -> (Vec<f64>, DMatrix<f64>) {
+    let gram = symmetric(&(basis.transpose() * mass));
+    let gram = SymmetricEigen::new(gram);
+    let largest = gram.eigenvalues.iter().cloned().fold(0.0, f64::max);
+    let kept: Vec<usize> = (0..gram.eigenvalues.len())
+        .filter(|&i| gram.eigenvalues[i] > 1.0E-12 * largest)
+        .collect();
+    let orthonormal = DMatrix::from_fn(basis.ncols(), kept.len(), |i, j| {
+        gram.eigenvectors[(i, kept[j])] / gram.eigenvalues[kept[j]].sqrt()
+    });
+
+    let projected =
+        symmetric(&(orthonormal.transpose() * basis.transpose() * stiffness * &orthonormal));
+    let projected = SymmetricEigen::new(projected);
+    let mut order: Vec<usize> = (0..projected.eigenvalues.len()).collect();
+    order.sort_by(|&a, &b| {
+        projected.eigenvalues[a]
+            .partial_cmp(&projected.eigenvalues[b])
+            .expect("Not expected undefined ritz value")
+    });
+
+    let values = order.iter().map(|&i| projected.eigenvalues[i]).collect();
+    let vectors = projected.eigenvectors.select_columns(order.iter());
+    return (values, orthonormal * vectors);
+}
+
+fn symmetric(matrix: &DMatrix<f64>) -> DMatrix<f64> {
+    return (matrix + matrix.transpose()) * 0.5;
+}
+
+fn normalize_columns(matrix: &mut DMatrix<f64>) {
+    for mut column in matrix.column_iter_mut() {
+        let norm = column.norm();
+        if norm > 0.0 {
+            column /= norm;
+        }
+    }
+}
+
+fn concatenate(blocks: &[&DMatrix<f64>]) -> DMatrix<f64> {
+    let rows = blocks[0].nrows();
+    let cols = blocks.iter().map(|block| block.ncols()).sum();
+    let mut matrix = DMatrix::<f64>::zeros(rows, cols);
+    let mut offset = 0;
+    for block in blocks.iter() {
+        matrix
+            .slice_mut((0, offset), (rows, block.ncols()))
+            .copy_from(block);
+        offset += block.ncols();
+    }
+    return matrix;
+}
+
+#[cfg(test)]
+mod convergence {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /*
+            Second difference matrix of size n with unit mass:
+            eigenvalues 2 - 2 cos(k pi / (n + 1)), found without preconditioning
+        */
+        let n = 40;
+        let a = DMatrix::from_fn(n, n, |i, j| match (i as i64 - j as i64).abs() {
+            0 => 2.0,
+            1 => -1.0,
+            _ => 0.0,
+        });
+        let initial = DMatrix::from_fn(n, 3, |i, j| ((i + 1) as f64 * (j as f64 + 0.5)).sin());
+        let eigen = solve(
+            &|v: &DMatrix<f64>| &a * v,
+            &|v: &DMatrix<f64>| v.clone(),
+            &|v: &DMatrix<f64>| v.clone(),
+            &initial,
+            3,
+            &Options {
+                tolerance: 1.0E-8,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+        assert!(eigen.converged);
+        for (k, value) in eigen.values.iter().enumerate() {
+            let pi = std::f64::consts::PI;
+            let exact = 2.0 - 2.0 * ((k + 1) as f64 * pi / (n + 1) as f64).cos();
+            assert!((value - exact).abs() < 1.0E-10);
+        }
+        let orthogonality = eigen.vectors.transpose() * &eigen.vectors;
+        assert!((orthogonality - DMatrix::identity(3, 3)).norm() < 1.0E-8);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Generalized problem with diagonal mass: same spectrum as M^-1/2 A M^-1/2 */
+        let n = 30;
+        let a = DMatrix::from_fn(n, n, |i, j| match (i as i64 - j as i64).abs() {
+            0 => 2.0,
+            1 => -1.0,
+            _ => 0.0,
+        });
+        let m = DMatrix::from_fn(n, n, |i, j| if i == j { 1.0 + (i % 3) as f64 } else { 0.0 });
+        let scale = m.map(|v| if v > 0.0 { 1.0 / v.sqrt() } else { 0.0 });
+        let reduced = SymmetricEigen::new(&scale * &a * &scale);
+        let mut exact: Vec<f64> = reduced.eigenvalues.iter().cloned().collect();
+        exact.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let diagonal = a.diagonal();
+        let initial = DMatrix::from_fn(n, 2, |i, j| ((i + 1) as f64 * (j as f64 + 0.3)).cos());
+        let eigen = solve(
+            &|v: &DMatrix<f64>| &a * v,
+            &|v: &DMatrix<f64>| &m * v,
+            &|v: &DMatrix<f64>| {
+                DMatrix::from_fn(v.nrows(), v.ncols(), |i, j| v[(i, j)] / diagonal[i])
+            },
+            &initial,
+            1,
+            &Options::default(),
+        )
+        .unwrap();
+        assert!(eigen.converged);
+        assert!((eigen.values[0] - exact[0]).abs() < 1.0E-10);
+        assert_eq!(eigen.values.len(), 1);
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            Dependent initial columns are dropped: the block still holds two
+            independent ones for a single pair, but not enough for three pairs
+        */
+        let n = 10;
+        let a = DMatrix::from_fn(n, n, |i, j| if i == j { (i + 1) as f64 } else { 0.0 });
+        let column = DVector::from_fn(n, |i, _| (i + 1) as f64);
+        let initial = DMatrix::from_columns(&[
+            column.clone(),
+            column.clone() * 2.0,
+            DVector::from_fn(n, |i, _| if i == 0 { 1.0 } else { 0.0 }),
+        ]);
+        let identity = |v: &DMatrix<f64>| v.clone();
+        let eigen = solve(
+            &|v| &a * v,
+            &identity,
+            &identity,
+            &initial,
+            1,
+            &Options::default(),
+        );
+        assert!(eigen.unwrap().converged);
+
+        let eigen = solve(
+            &|v| &a * v,
+            &identity,
+            &identity,
+            &initial,
+            3,
+            &Options::default(),
+        );
+        assert_eq!(eigen.err(), Some(Error::RankDeficient));
+        let eigen = solve(
+            &|v| &a * v,
+            &identity,
+            &identity,
+            &initial,
+            4,
+            &Options::default(),
+        );
+        assert_eq!(eigen.err(), Some(Error::RankDeficient));
+    }
+}
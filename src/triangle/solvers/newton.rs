@@ -0,0 +1,213 @@
+use nalgebra::DMatrix;
+
+/**
+ * Stopping criteria of the newton iterations: the residual norm falls below
+ * absolute + relative * initial residual norm, or the update norm below
+ * step * (1 + solution norm)
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    pub absolute: f64,
+    pub relative: f64,
+    pub step: f64,
+    pub max_iterations: usize,
+    /* Greatest number of halvings of the update along the line search */
+    pub max_halvings: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            absolute: 1.0E-10,
+            relative: 1.0E-10,
+            step: 1.0E-12,
+            max_iterations: 50,
+            max_halvings: 20,
+        }
+    }
+}
+
+/**
+ * State after each iteration: residual norm at the new solution,
+ * norm of the applied update and the damping factor that scaled it
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Iteration {
+    pub residual: f64,
+    pub step: f64,
+    pub damping: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub converged: bool,
+    /* Line search without sufficient decrease, which stopped the iterations */
+    pub stagnated: bool,
+    pub initial_residual: f64,
+    pub iterations: Vec<Iteration>,
+}
+
+/**
+ * Solves residual(u) = 0 from the initial guess
+ */
+pub fn solve(
+    initial: &DMatrix<f64>,
+    residual: &dyn Fn(&DMatrix<f64>) -> DMatrix<f64>,
+    jacobian: &dyn Fn(&DMatrix<f64>) -> DMatrix<f64>,
+    options: &Options,
+) -> (DMatrix<f64>, Report) {
+    return solve_monitored(initial, residual, jacobian, options, &mut |_| {});
+}
+
+/**
+ * Solves residual(u) = 0 from the initial guess, handing each iteration to the monitor.
+ *  Updates come from the jacobian system J du = - r, halved until the residual
+ *  norm decreases enough: |r(u + a du)| < (1 - 1e-4 a) |r(u)|.
+ *  If the halvings run out first, the last damped update is applied and the
+ *  iterations stop unconverged, unless the residual criterion holds
+ */
+pub fn solve_monitored(
+    initial: &DMatrix<f64>,
+    residual: &dyn Fn(&DMatrix<f64>) -> DMatrix<f64>,
+    jacobian: &dyn Fn(&DMatrix<f64>) -> DMatrix<f64>,
+    options: &Options,
+    monitor: &mut dyn FnMut(&Iteration),
+) -> (DMatrix<f64>, Report) {
+    let mut u = initial.clone();
+    let mut r = residual(&u);
+    let mut norm = r.norm();
+    let tolerance = options.absolute + options.relative * norm;
+    let mut report = Report {
+        converged: norm <= tolerance,
+        stagnated: false,
+        initial_residual: norm,
+        iterations: Vec::new(),
+    };
+
+    while !report.converged && report.iterations.len() < options.max_iterations {
+        let update = match jacobian(&u).lu().solve(&(-&r)) {
+            Some(update) => update,
+            None => break,
+        };
+
+        let mut damping = 1.0;
+        let mut candidate = &u + &update;
+        let mut candidate_residual = residual(&candidate);
+        let mut halvings = 0;
+        while candidate_residual.norm() >= (1.0 - 1.0E-4 * damping) * norm {
+            if halvings == options.max_halvings {
+                report.stagnated = true;
+                break;
+            }
+            halvings += 1;
+            damping *= 0.5;
+            candidate = &u + &update * damping;
+            candidate_residual = residual(&candidate);
+        }
+
+        u = candidate;
+        r = candidate_residual;
+        norm = r.norm();
+        let iteration = Iteration {
+            residual: norm,
+            step: update.norm() * damping,
+            damping,
+        };
+        monitor(&iteration);
+        report.iterations.push(iteration);
+        report.converged = norm <= tolerance
+            || (!report.stagnated && iteration.step <= options.step * (1.0 + u.norm()));
+        if report.stagnated {
+            break;
+        }
+    }
+    return (u, report);
+}
+
+#[cfg(test)]
+mod convergence {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /* Quadratic convergence to the root of x^2 = 2 */
+        let residual = |u: &DMatrix<f64>| u.map(|x| x * x - 2.0);
+        let jacobian = |u: &DMatrix<f64>| DMatrix::from_element(1, 1, 2.0 * u[(0, 0)]);
+        let (u, report) = solve(
+            &DMatrix::from_element(1, 1, 1.0),
+            &residual,
+            &jacobian,
+            &Options::default(),
+        );
+        assert!(report.converged);
+        assert!((u[(0, 0)] - 2.0_f64.sqrt()).abs() < 1.0E-10);
+        assert!(report.iterations.len() <= 6);
+        let residuals: Vec<f64> = report.iterations.iter().map(|it| it.residual).collect();
+        assert!(residuals[3] < residuals[2].powi(2) * 10.0);
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Full newton steps of arctan(x) = 0 diverge from x = 2,
+            the line search damps them into convergence
+        */
+        let residual = |u: &DMatrix<f64>| u.map(f64::atan);
+        let jacobian =
+            |u: &DMatrix<f64>| DMatrix::from_element(1, 1, 1.0 / (1.0 + u[(0, 0)].powi(2)));
+        let mut monitored = Vec::new();
+        let (u, report) = solve_monitored(
+            &DMatrix::from_element(1, 1, 2.0),
+            &residual,
+            &jacobian,
+            &Options::default(),
+            &mut |iteration| monitored.push(*iteration),
+        );
+        assert!(report.converged);
+        assert!(u[(0, 0)].abs() < 1.0E-10);
+        assert!(report.iterations[0].damping < 1.0);
+        assert_eq!(monitored, report.iterations);
+
+        /* Without halvings the iterate grows, and the iterations stop */
+        let options = Options {
+            max_halvings: 0,
+            max_iterations: 4,
+            ..Options::default()
+        };
+        let (u, report) = solve(
+            &DMatrix::from_element(1, 1, 2.0),
+            &residual,
+            &jacobian,
+            &options,
+        );
+        assert!(!report.converged && report.stagnated);
+        assert_eq!(report.iterations.len(), 1);
+        assert!(u[(0, 0)].abs() > 2.0);
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            x^2 + 1 = 0 has no root: the residual norm stalls at 1 near x = 0,
+            where the line search fails. Its damped steps are tiny but the
+            iterations are not taken as converged
+        */
+        let residual = |u: &DMatrix<f64>| u.map(|x| x * x + 1.0);
+        let jacobian = |u: &DMatrix<f64>| DMatrix::from_element(1, 1, 2.0 * u[(0, 0)]);
+        let options = Options {
+            max_halvings: 80,
+            ..Options::default()
+        };
+        let (_, report) = solve(
+            &DMatrix::from_element(1, 1, 0.5),
+            &residual,
+            &jacobian,
+            &options,
+        );
+        assert!(report.stagnated);
+        assert!(!report.converged);
+        let last = report.iterations.last().unwrap();
+        assert!(last.residual >= 1.0);
+        assert!(last.step <= options.step);
+    }
+}
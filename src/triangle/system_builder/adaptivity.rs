@@ -0,0 +1,197 @@
+use nalgebra::{DMatrix, DVector};
+
+use crate::common::error::Error;
+use crate::triangle::{
+    basis::{dubiner, shape::Basis},
+    element::TriangleElementL1,
+    system_builder::domain::Domain,
+};
+
+use std::rc::Rc;
+
+/**
+ * Modal coefficients of the element field, given the solution of the whole domain.
+ * Nodal fields are converted into modal basis of degree 1
+ */
+pub fn modal_coefficients(
+    domain: &Domain,
+    solution: &DMatrix<f64>, /* Nx1 matrix */
+    element: &Rc<TriangleElementL1>,
+) -> DVector<f64> {
+    let values: Vec<f64> = domain
+        .element_indexes(element)
+        .iter()
+        .map(|index| solution[(*index, 0)])
+        .collect();
+    match domain.element_basis(element) {
+        Basis::Nodal => dubiner::linear_to_modal(1, values[0], values[1], values[2]),
+        Basis::Modal(_) => DVector::from_vec(values),
+    }
+}
+
+/**
+ * Modal decay indicator: share of the element energy held by the modes of highest degree.
+ * Smooth fields have fast decaying modes, so small values point to smooth fields
+ * and large values to steep or discontinuous ones
+ *
+ *  Reference:
+ *      Persson, P.O.; Peraire, J. Sub-cell shock capturing for discontinuous
+ *      Galerkin methods. AIAA paper 2006-112.
+ */
+pub fn smoothness_indicator(
+    domain: &Domain,
+    solution: &DMatrix<f64>,
+    element: &Rc<TriangleElementL1>,
+) -> f64 {
+    let coefficients = modal_coefficients(domain, solution, element);
+    let degree = domain.element_basis(element).degree();
+    if degree == 0 {
+        return 0.0;
+    }
+
+    let total = coefficients.norm_squared();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let highest = coefficients
+        .rows_range(dubiner::size(degree - 1)..)
+        .norm_squared();
+    return highest / total;
+}
+
+/**
+ * Indicators of every element, in the order of Domain::elements
+ */
+pub fn indicators(domain: &Domain, solution: &DMatrix<f64>) -> Vec<f64> {
+    return domain
+        .elements
+        .iter()
+        .map(|element| smoothness_indicator(domain, solution, element))
+        .collect();
+}
+
+/**
+ * Raises by one the degree of modal elements whose indicator is below the threshold,
+ * up to the maximum degree: higher degree only pays off where the field is smooth.
+ * Elements above the threshold keep their degree, to be h-refined or limited instead,
+ * and nodal elements are left as they are.
+ * Returns the solution in the new numbering: modes are hierarchical,
+ * so the former field is kept and new modes are zero.
+ * Fails if the maximum degree is beyond the ones the domain supports
+ */
+pub fn p_refine(
+    domain: &mut Domain,
+    solution: &DMatrix<f64>,
+    threshold: f64,
+    max_degree: usize,
+) -> Result<DMatrix<f64>, Error> {
+    let elements = domain.elements.clone();
+    let values: Vec<Vec<f64>> = elements
+        .iter()
+        .map(|element| {
+            domain
+                .element_indexes(element)
+                .iter()
+                .map(|index| solution[(*index, 0)])
+                .collect()
+        })
+        .collect();
+    let indicators = indicators(domain, solution);
+
+    for (element, indicator) in elements.iter().zip(indicators.iter()) {
+        if let Basis::Modal(degree) = domain.element_basis(element) {
+            if *indicator < threshold && degree < max_degree {
+                domain.set_element_degree(element, degree + 1)?;
+            }
+        }
+    }
+
+    let mut refined = DMatrix::<f64>::zeros(domain.size(), 1);
+    for (element, element_values) in elements.iter().zip(values.iter()) {
+        let indexes = domain.element_indexes(element);
+        for (index, value) in indexes.iter().zip(element_values.iter()) {
+            refined[(*index, 0)] = *value;
+        }
+    }
+    return Ok(refined);
+}
+
+#[cfg(test)]
+mod refinement {
+    use super::*;
+    use crate::common::point::Point;
+
+    fn square(degree: usize) -> Domain {
+        let p1 = Rc::new(Point::new(0.0, 0.0));
+        let p2 = Rc::new(Point::new(1.0, 0.0));
+        let p3 = Rc::new(Point::new(1.0, 1.0));
+        let p4 = Rc::new(Point::new(0.0, 1.0));
+
+        let t1 = Rc::new(TriangleElementL1::new(&p1, &p2, &p4));
+        let t2 = Rc::new(TriangleElementL1::new(&p4, &p2, &p3));
+
+        let mut domain = Domain::new_modal(degree).unwrap();
+        domain.insert_element(&t1);
+        domain.insert_element(&t2);
+        return domain;
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Linear field has no energy at higher modes */
+        let domain = square(3);
+        let mut solution = DMatrix::<f64>::zeros(domain.size(), 1);
+        for element in domain.elements.iter() {
+            let modal = dubiner::linear_to_modal(3, element.p1.y, element.p2.y, element.p3.y);
+            for (index, value) in domain.element_indexes(element).iter().zip(modal.iter()) {
+                solution[(*index, 0)] = *value;
+            }
+        }
+        for indicator in indicators(&domain, &solution).iter() {
+            assert!(indicator.abs() < 1.0E-20);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Only the smooth element is raised, the one with energy at its highest modes is kept */
+        let mut domain = square(2);
+        let mut solution = DMatrix::<f64>::zeros(domain.size(), 1);
+        solution[(0, 0)] = 1.0;
+        solution[(5, 0)] = 1.0;
+        solution[(6, 0)] = 2.0;
+
+        let refined = p_refine(&mut domain, &solution, 0.1, 4).unwrap();
+        let (t1, t2) = (&domain.elements[0], &domain.elements[1]);
+        assert_eq!(domain.element_basis(t1), Basis::Modal(2));
+        assert_eq!(domain.element_basis(t2), Basis::Modal(3));
+        assert_eq!(domain.size(), 16);
+
+        /* Coefficients follow the new numbering */
+        assert_eq!(domain.element_indexes(t2), (6..16).collect::<Vec<usize>>());
+        assert_eq!(refined[(0, 0)], 1.0);
+        assert_eq!(refined[(5, 0)], 1.0);
+        assert_eq!(refined[(6, 0)], 2.0);
+        assert_eq!(refined[(15, 0)], 0.0);
+
+        /* Degree stops at the maximum */
+        let refined = p_refine(&mut domain, &refined, 0.1, 3).unwrap();
+        assert_eq!(domain.element_basis(&domain.elements[1]), Basis::Modal(3));
+        assert_eq!(refined.nrows(), 16);
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Nodal elements keep their basis and values */
+        let p1 = Rc::new(Point::new(0.0, 0.0));
+        let p2 = Rc::new(Point::new(1.0, 0.0));
+        let p3 = Rc::new(Point::new(0.0, 1.0));
+        let mut domain = Domain::new_empty();
+        domain.insert_element(&Rc::new(TriangleElementL1::new(&p1, &p2, &p3)));
+
+        let solution = DMatrix::from_row_slice(3, 1, &[1.0, 2.0, 3.0]);
+        let refined = p_refine(&mut domain, &solution, 0.1, 4).unwrap();
+        assert_eq!(domain.element_basis(&domain.elements[0]), Basis::Nodal);
+        assert_eq!(refined, solution);
+    }
+}
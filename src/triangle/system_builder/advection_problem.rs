@@ -0,0 +1,204 @@
+use nalgebra::DMatrix;
+
+use crate::common::point::Point;
+use crate::triangle::{
+    integrands::{advection::Velocity, utils},
+    system_builder::{
+        domain::Domain,
+        external::inflow,
+        internal::{advection, l2_mass, upwind_flux},
+    },
+    time_stepping::ssp_rk::{self, Scheme},
+};
+
+/**
+ * Linear advection: du/dt + b . grad(u) = 0
+ *  Semi discrete system M du/dt = f - A u, where A gathers the volume term
+ *  and the upwind fluxes, and f the inflow of dirichlet values.
+ *  Mass is block diagonal, so M^-1 A and M^-1 f are kept instead
+ */
+pub struct AdvectionProblem {
+    pub operator: DMatrix<f64>,
+    pub source: DMatrix<f64>,
+
+    /* Greatest speed found at element centroids */
+    pub max_speed: f64,
+    /* Smallest inscribed circle diameter over the greatest basis degree */
+    pub min_length: f64,
+}
+
+impl AdvectionProblem {
+    pub fn new(velocity: Velocity, domain: &Domain) -> Self {
+        let size = domain.size();
+        let mut operator = DMatrix::<f64>::zeros(size, size);
+        let mut source = DMatrix::<f64>::zeros(size, 1);
+
+        advection::build(&mut operator, velocity, domain)
+            .expect("Failed to build advection matrix");
+        upwind_flux::build(&mut operator, velocity, domain).expect("Failed to set upwind flux");
+        inflow::build(&mut operator, &mut source, velocity, domain)
+            .expect("Failed to set inflow constraints");
+        l2_mass::apply_inverse(&mut operator, domain).expect("Failed to invert mass matrix");
+        l2_mass::apply_inverse(&mut source, domain).expect("Failed to invert mass matrix");
+
+        let mut max_speed: f64 = 0.0;
+        let mut min_length = f64::INFINITY;
+        for element in domain.elements.iter() {
+            let centroid =
+                utils::physical_point(&element.p1, &element.p2, &element.p3, 1.0 / 3.0, 1.0 / 3.0);
+            let (bx, by) = velocity(&centroid);
+            max_speed = max_speed.max((bx * bx + by * by).sqrt());
+
+            let degree = domain.element_basis(element).degree() as f64;
+            min_length = min_length.min(
+                inscribed_diameter(&element.p1, &element.p2, &element.p3) / (2.0 * degree + 1.0),
+            );
+        }
+
+        return AdvectionProblem {
+            operator,
+            source,
+            max_speed,
+            min_length,
+        };
+    }
+
+    /**
+     * Time derivative of the coefficients: M^-1 (f - A u)
+     */
+    pub fn rhs(&self, u: &DMatrix<f64>) -> DMatrix<f64> {
+        return &self.source - &self.operator * u;
+    }
+
+    /**
+     * Greatest stable time step for the given courant number
+     */
+    pub fn time_step(&self, courant: f64) -> f64 {
+        return courant * self.min_length / self.max_speed;
+    }
+
+    /**
+     * Advances the coefficients by dt
+     */
+    pub fn step(&self, scheme: Scheme, u: &DMatrix<f64>, dt: f64) -> DMatrix<f64> {
+        return ssp_rk::step(scheme, u, dt, &|v: &DMatrix<f64>| self.rhs(v));
+    }
+
+    /**
+     * Advances the coefficients until final time, with steps no greater than dt
+     */
+    pub fn solve(
+        &self,
+        scheme: Scheme,
+        u: &DMatrix<f64>,
+        dt: f64,
+        final_time: f64,
+    ) -> DMatrix<f64> {
+        let steps = (final_time / dt).ceil() as usize;
+        let dt = final_time / steps as f64;
+        let mut u = u.clone();
+        for _ in 0..steps {
+            u = self.step(scheme, &u, dt);
+        }
+        return u;
+    }
+}
+
+/**
+ * Diameter of the circle inscribed in triangle (p1, p2, p3)
+ */
+pub(crate) fn inscribed_diameter(p1: &Point, p2: &Point, p3: &Point) -> f64 {
+    let area = utils::jacobian(p1, p2, p3).determinant().abs() / 2.0;
+    let perimeter = (0..3)
+        .map(|n| utils::edge_length(p1, p2, p3, n))
+        .sum::<f64>();
+    return 4.0 * area / perimeter;
+}
+
+#[cfg(test)]
+mod transport {
+    use super::*;
+    use crate::triangle::{basis::dubiner, mesh};
+    use nalgebra::DVector;
+
+    /* Modal coefficients interpolating f at the nodes of each element */
+    fn interpolate(domain: &Domain, f: &dyn Fn(&Point) -> f64) -> DMatrix<f64> {
+        let mut u = DMatrix::<f64>::zeros(domain.size(), 1);
+        for element in domain.elements.iter() {
+            let degree = domain.element_basis(element).degree();
+            let nodal = DVector::from_iterator(
+                dubiner::size(degree),
+                dubiner::nodes(degree).iter().map(|(x, y)| {
+                    f(&utils::physical_point(
+                        &element.p1,
+                        &element.p2,
+                        &element.p3,
+                        *x,
+                        *y,
+                    ))
+                }),
+            );
+            let modal = dubiner::nodal_to_modal(degree, &nodal);
+            for (index, value) in domain.element_indexes(element).iter().zip(modal.iter()) {
+                u[(*index, 0)] = *value;
+            }
+        }
+        return u;
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Constant state is kept, as long as inflow brings the same value */
+        let mut domain = Domain::new_modal(2).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 3, 3, &mut domain);
+        for edge in boundary.left.iter().chain(boundary.bottom.iter()) {
+            domain.insert_dirichlet_constraint(edge, vec![1.0, 1.0]);
+        }
+        let velocity = |_: &Point| (1.0, 0.5);
+        let problem = AdvectionProblem::new(&velocity, &domain);
+
+        let u = interpolate(&domain, &|_| 1.0);
+        let dt = problem.time_step(0.5);
+        let answer = problem.solve(Scheme::SspRk3, &u, dt, 0.5);
+        assert!((answer - u).norm() < 1.0E-12);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Translating gaussian pulse keeps its shape and peak */
+        let mut domain = Domain::new_modal(3).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 6, 6, &mut domain);
+        let velocity = |_: &Point| (1.0, 0.5);
+        let problem = AdvectionProblem::new(&velocity, &domain);
+
+        let pulse = |center: (f64, f64)| {
+            move |point: &Point| {
+                let r2 = (point.x - center.0).powi(2) + (point.y - center.1).powi(2);
+                (-r2 / 0.01).exp()
+            }
+        };
+        let u = interpolate(&domain, &pulse((0.3, 0.3)));
+        let dt = problem.time_step(0.5);
+        let answer = problem.solve(Scheme::SspRk3, &u, dt, 0.4);
+
+        /* Nodal values against the translated pulse */
+        let (mut error, mut peak): (f64, f64) = (0.0, 0.0);
+        for element in domain.elements.iter() {
+            let modal = DVector::from_iterator(
+                dubiner::size(3),
+                domain
+                    .element_indexes(element)
+                    .iter()
+                    .map(|index| answer[(*index, 0)]),
+            );
+            let nodal = dubiner::modal_to_nodal(3, &modal);
+            for ((x, y), value) in dubiner::nodes(3).iter().zip(nodal.iter()) {
+                let point = utils::physical_point(&element.p1, &element.p2, &element.p3, *x, *y);
+                error = error.max((value - pulse((0.7, 0.5))(&point)).abs());
+                peak = peak.max(*value);
+            }
+        }
+        assert!(error < 0.1);
+        assert!(peak > 0.9 && peak < 1.0);
+    }
+}
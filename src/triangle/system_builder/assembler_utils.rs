@@ -1,9 +1,9 @@
 use nalgebra::DMatrix;
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-fn linear(global_1: usize, global_2: usize, global_3: usize) -> HashMap<usize, usize> {
-    let mut map: HashMap<usize, usize> = HashMap::new();
+fn linear(global_1: usize, global_2: usize, global_3: usize) -> BTreeMap<usize, usize> {
+    let mut map: BTreeMap<usize, usize> = BTreeMap::new();
 
     map.insert(0, global_1);
     map.insert(1, global_2);
@@ -16,9 +16,9 @@ pub fn linear_map(
     global_1: usize,
     global_2: usize,
     global_3: usize,
-) -> HashMap<(usize, usize), (usize, usize)> {
+) -> BTreeMap<(usize, usize), (usize, usize)> {
     let linear_map = linear(global_1, global_2, global_3);
-    let mut squared_map: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    let mut squared_map: BTreeMap<(usize, usize), (usize, usize)> = BTreeMap::new();
 
     for (local_row, global_row) in linear_map.iter() {
         squared_map.insert((*local_row, 0), (*global_row, 0));
@@ -34,10 +34,10 @@ pub fn cross_map(
     global_4: usize,
     global_5: usize,
     global_6: usize,
-) -> HashMap<(usize, usize), (usize, usize)> {
+) -> BTreeMap<(usize, usize), (usize, usize)> {
     let l1 = linear(global_1, global_2, global_3);
     let l2 = linear(global_4, global_5, global_6);
-    let mut squared_map: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    let mut squared_map: BTreeMap<(usize, usize), (usize, usize)> = BTreeMap::new();
 
     for (local_row, global_row) in l1.iter() {
         for (local_col, global_col) in l2.iter() {
@@ -52,9 +52,9 @@ pub fn square_map(
     global_1: usize,
     global_2: usize,
     global_3: usize,
-) -> HashMap<(usize, usize), (usize, usize)> {
+) -> BTreeMap<(usize, usize), (usize, usize)> {
     let linear_map = linear(global_1, global_2, global_3);
-    let mut squared_map: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    let mut squared_map: BTreeMap<(usize, usize), (usize, usize)> = BTreeMap::new();
 
     for (local_row, global_row) in linear_map.iter() {
         for (local_col, global_col) in linear_map.iter() {
@@ -65,10 +65,35 @@ pub fn square_map(
     return squared_map;
 }
 
+/**
+ * Maps local (row, col) into global indexes of arbitrary sized element blocks
+ */
+pub fn block_map(rows: &[usize], cols: &[usize]) -> BTreeMap<(usize, usize), (usize, usize)> {
+    let mut block_map: BTreeMap<(usize, usize), (usize, usize)> = BTreeMap::new();
+
+    for (local_row, global_row) in rows.iter().enumerate() {
+        for (local_col, global_col) in cols.iter().enumerate() {
+            block_map.insert((local_row, local_col), (*global_row, *global_col));
+        }
+    }
+
+    return block_map;
+}
+
+/**
+ * Maps local rows into global indexes of an arbitrary sized column
+ */
+pub fn column_map(rows: &[usize]) -> BTreeMap<(usize, usize), (usize, usize)> {
+    return block_map(rows, &[0]);
+}
+
+/**
+ * Adds the local matrix into the global one, in local index order
+ */
 pub fn map(
     global: &mut DMatrix<f64>,
     local: &DMatrix<f64>,
-    index_map: &HashMap<(usize, usize), (usize, usize)>,
+    index_map: &BTreeMap<(usize, usize), (usize, usize)>,
 ) {
     for (local_index, global_index) in index_map.iter() {
         global[*global_index] += local[*local_index];
@@ -1,34 +1,154 @@
 use nalgebra::DMatrix;
 
-use crate::triangle::system_builder::{
-    domain::Domain,
-    external::{dirichlet, neumann},
-    internal::{flux_artificial, flux_natural, jump_penalty, mass},
+use crate::common::point::Point;
+use crate::triangle::{
+    integrands::advection::Velocity,
+    system_builder::{
+        domain::Domain,
+        external::{dirichlet, inflow, neumann, source},
+        internal::{
+            advection, flux_artificial, flux_natural, jump_penalty, local_gradient, mass,
+            upwind_flux,
+        },
+    },
 };
 
 pub fn build(sigma: f64, domain: &Domain) -> (DMatrix<f64>, DMatrix<f64>) {
-    let system_size = domain.elements.len() * 3;
+    let system_matrix = interior_penalty(sigma, domain);
+    let mut extern_matrix: DMatrix<f64> = DMatrix::<f64>::zeros(domain.size(), 1);
+
+    dirichlet::build_extern(
+        &mut extern_matrix,
+        sigma,
+        domain,
+        &domain.dirichlet_constraints,
+    )
+    .expect("Failed to set dirichlet constraints");
+    neumann::build(&mut extern_matrix, domain).expect("Failed to set neumann constraints");
+
+    return (system_matrix, extern_matrix);
+}
+
+/**
+ * Poisson system: - laplacian(u) = f, the interior penalty system of build
+ * with the volumetric source f added to the extern matrix
+ */
+pub fn build_with_source(
+    sigma: f64,
+    source: &dyn Fn(&Point) -> f64,
+    domain: &Domain,
+) -> (DMatrix<f64>, DMatrix<f64>) {
+    let (system_matrix, mut extern_matrix) = build(sigma, domain);
+    source::build(&mut extern_matrix, source, domain).expect("Failed to set volumetric source");
+
+    return (system_matrix, extern_matrix);
+}
+
+/**
+ * Poisson system of build_with_source, with the dirichlet values given as a function g
+ * of the boundary points: g is integrated at the edge quadrature points of every
+ * dirichlet constraint, whose own values are not used
+ */
+pub fn build_with_boundary_function(
+    sigma: f64,
+    source: &dyn Fn(&Point) -> f64,
+    boundary: &dyn Fn(&Point) -> f64,
+    domain: &Domain,
+) -> (DMatrix<f64>, DMatrix<f64>) {
+    let system_matrix = interior_penalty(sigma, domain);
+    let mut extern_matrix: DMatrix<f64> = DMatrix::<f64>::zeros(domain.size(), 1);
+
+    dirichlet::build_extern_function(
+        &mut extern_matrix,
+        sigma,
+        domain,
+        &domain.dirichlet_constraints,
+        boundary,
+    )
+    .expect("Failed to set dirichlet constraints");
+    neumann::build(&mut extern_matrix, domain).expect("Failed to set neumann constraints");
+    source::build(&mut extern_matrix, source, domain).expect("Failed to set volumetric source");
 
+    return (system_matrix, extern_matrix);
+}
+
+/**
+ * System matrix of the interior penalty method, dirichlet edges included,
+ * shared by the builders which only differ on the extern matrix
+ */
+fn interior_penalty(sigma: f64, domain: &Domain) -> DMatrix<f64> {
+    let system_size = domain.size();
     let mut system_matrix: DMatrix<f64> = DMatrix::<f64>::zeros(system_size, system_size);
-    let mut extern_matrix: DMatrix<f64> = DMatrix::<f64>::zeros(system_size, 1);
 
     mass::build(&mut system_matrix, domain).expect("Failed to build mass matrix");
     flux_natural::build(&mut system_matrix, domain).expect("Failed to set natural flux");
     flux_artificial::build(&mut system_matrix, domain)
         .expect("Failed to set artificial anti-symmetric flux");
 
+    jump_penalty::build(&mut system_matrix, sigma, domain)
+        .expect("Failed to set inter element penalties");
+    dirichlet::build_matrix(
+        &mut system_matrix,
+        sigma,
+        domain,
+        &domain.dirichlet_constraints,
+    )
+    .expect("Failed to set dirichlet constraints");
+
+    return system_matrix;
+}
+
+/**
+ * Same system as build, with the local discontinuous Galerkin formulation
+ * in place of the interior penalty one: the auxiliary gradient is eliminated
+ * element by element, and sigma scales the penalties on jumps and dirichlet values
+ */
+pub fn build_ldg(sigma: f64, domain: &Domain) -> (DMatrix<f64>, DMatrix<f64>) {
+    let system_size = domain.size();
+
+    let mut system_matrix: DMatrix<f64> = DMatrix::<f64>::zeros(system_size, system_size);
+    let mut extern_matrix: DMatrix<f64> = DMatrix::<f64>::zeros(system_size, 1);
+
+    local_gradient::build(&mut system_matrix, &mut extern_matrix, domain)
+        .expect("Failed to set local gradient");
     jump_penalty::build(&mut system_matrix, sigma, domain).expect("Failed to set inter element penalties");
-    dirichlet::build(&mut system_matrix, &mut extern_matrix, sigma, domain).expect("Failed to set dirichlet constraints");
+    dirichlet::build_penalty(&mut system_matrix, &mut extern_matrix, sigma, domain)
+        .expect("Failed to set dirichlet constraints");
     neumann::build(&mut extern_matrix, domain).expect("Failed to set neumann constraints");
 
     return (system_matrix, extern_matrix);
 }
 
+/**
+ * Convection diffusion system: - k laplacian(u) + b . grad(u) = 0
+ *  Diffusion is the interior penalty system scaled by k, so neumann values
+ *  are the normal flux k grad(u) . n. Advection takes the upwind flux,
+ *  which keeps it stable when diffusion vanishes
+ */
+pub fn build_convection_diffusion(
+    diffusivity: f64,
+    velocity: Velocity,
+    sigma: f64,
+    domain: &Domain,
+) -> (DMatrix<f64>, DMatrix<f64>) {
+    let (mut system_matrix, mut extern_matrix) = build(sigma, domain);
+    system_matrix *= diffusivity;
+    extern_matrix *= diffusivity;
+
+    advection::build(&mut system_matrix, velocity, domain).expect("Failed to build advection matrix");
+    upwind_flux::build(&mut system_matrix, velocity, domain).expect("Failed to set upwind flux");
+    inflow::build(&mut system_matrix, &mut extern_matrix, velocity, domain)
+        .expect("Failed to set inflow constraints");
+
+    return (system_matrix, extern_matrix);
+}
+
 #[cfg(test)]
 mod system_build {
     use super::*;
     use crate::common::{edge::Edge, point::Point};
-    use crate::triangle::element::TriangleElementL1;
+    use crate::triangle::{basis::dubiner, element::TriangleElementL1};
+    use nalgebra::DVector;
 
     use std::rc::Rc;
 
@@ -73,7 +193,7 @@ mod system_build {
         domain.insert_dirichlet_constraint(&e6, vec![0.0, 0.0]);
 
         fn solve(sigma: f64, domain: &Domain) -> f64 {
-            let (system_matrix, extern_matrix) = build(sigma, &domain);
+            let (system_matrix, extern_matrix) = build(sigma, domain);
             let inverse = system_matrix.try_inverse().unwrap();
             let answer = inverse * extern_matrix;
 
@@ -81,12 +201,12 @@ mod system_build {
                 18,
                 1,
                 &vec![
-                    0.0, 1.0, -1.0, /*  */
-                    1.0, 1.0, -1.0, /*  */
-                    1.0, 0.0, -1.0, /*  */
-                    0.0, 0.0, -1.0, /*  */
-                    0.0, 0.0, -1.0, /*  */
-                    0.0, 0.0, -1.0, /*  */
+                    0.0, 1.0, 0.5, /*  */
+                    1.0, 1.0, 0.5, /*  */
+                    1.0, 0.0, 0.5, /*  */
+                    0.0, 0.0, 0.5, /*  */
+                    0.0, 0.0, 0.5, /*  */
+                    0.0, 0.0, 0.5, /*  */
                 ],
             );
             let error = ((&answer - &expected).transpose() * (&answer - &expected))[(0, 0)].sqrt();
@@ -131,12 +251,12 @@ mod system_build {
         domain.insert_dirichlet_constraint(&e4, vec![1.0, 0.0]);
 
         fn solve(sigma: f64, domain: &Domain) -> f64 {
-            let (system_matrix, extern_matrix) = build(sigma, &domain);
+            let (system_matrix, extern_matrix) = build(sigma, domain);
             let inverse = system_matrix.try_inverse().unwrap();
             let answer = inverse * extern_matrix;
 
             let expected =
-                DMatrix::<f64>::from_row_slice(6, 1, &vec![0.0, 0.0, 1.0, 1.0, 0.0, 0.0]);
+                DMatrix::<f64>::from_row_slice(6, 1, &[0.0, 0.0, 1.0, 1.0, 0.0, 0.0]);
             let error = ((&answer - &expected).transpose() * (&answer - &expected))[(0, 0)].sqrt();
             // println!("{} {}", answer, error);
             return error;
@@ -174,11 +294,11 @@ mod system_build {
         domain.insert_dirichlet_constraint(&e4, vec![0.0, 0.0]);
 
         fn solve(sigma: f64, domain: &Domain) -> f64 {
-            let (system_matrix, extern_matrix) = build(sigma, &domain);
+            let (system_matrix, extern_matrix) = build(sigma, domain);
             let inverse = system_matrix.try_inverse().unwrap();
             let answer = inverse * extern_matrix;
 
-            let expected = DMatrix::<f64>::from_row_slice(3, 1, &vec![0.0, 1.0, 0.0]);
+            let expected = DMatrix::<f64>::from_row_slice(3, 1, &[0.0, 1.0, 0.0]);
             let error = ((&answer - &expected).transpose() * (&answer - &expected))[(0, 0)].sqrt();
             // println!("{} {}", answer, error);
             return error;
@@ -220,25 +340,344 @@ mod system_build {
         domain.insert_neumann_constraint(&e4, vec![0.0, 0.0]);
 
         fn solve(sigma: f64, domain: &Domain) -> f64 {
-            let (system_matrix, extern_matrix) = build(sigma, &domain);
+            let (system_matrix, extern_matrix) = build(sigma, domain);
             let inverse = system_matrix.try_inverse().unwrap();
             let answer = inverse * extern_matrix;
 
             let expected =
-                DMatrix::<f64>::from_row_slice(6, 1, &vec![0.0, 0.0, 1.0, 1.0, 0.0, 1.0]);
+                DMatrix::<f64>::from_row_slice(6, 1, &[0.0, 0.0, 1.0, 1.0, 0.0, 1.0]);
             let error = ((&answer - &expected).transpose() * (&answer - &expected))[(0, 0)].sqrt();
             println!("{} {}", answer, error);
             return error;
         }
-        let err1 = solve(10.0, &domain);
-        let err2 = solve(100.0, &domain);
-        let err3 = solve(1000.0, &domain);
-        let err4 = solve(10000.0, &domain);
-        let err5 = solve(100000.0, &domain);
+        /* Linear solution u = y is reproduced regardless of the penalty */
+        assert!(solve(10.0, &domain) < 1.0E-12);
+        assert!(solve(100.0, &domain) < 1.0E-12);
+        assert!(solve(1000.0, &domain) < 1.0E-12);
+        assert!(solve(10000.0, &domain) < 1.0E-12);
+        assert!(solve(100000.0, &domain) < 1.0E-12);
+    }
 
-        assert!(err2 < err1);
-        assert!(err3 < err2);
-        assert!(err4 < err3);
-        assert!(err5 < err4);
+    #[test]
+    fn sample_5() {
+        /* square into triangles, with modal basis of degree 2 */
+        let p1 = Rc::new(Point::new(0.0, 0.0));
+        let p2 = Rc::new(Point::new(1.0, 0.0));
+        let p3 = Rc::new(Point::new(1.0, 1.0));
+        let p4 = Rc::new(Point::new(0.0, 1.0));
+
+        let e1 = Rc::new(Edge::new(&p1, &p2));
+        let e2 = Rc::new(Edge::new(&p2, &p3));
+        let e3 = Rc::new(Edge::new(&p3, &p4));
+        let e4 = Rc::new(Edge::new(&p4, &p1));
+
+        let t1 = Rc::new(TriangleElementL1::new(&p1, &p2, &p4));
+        let t2 = Rc::new(TriangleElementL1::new(&p4, &p2, &p3));
+
+        let mut domain = Domain::new_modal(2).unwrap();
+        domain.insert_element(&t1);
+        domain.insert_element(&t2);
+
+        domain.insert_dirichlet_constraint(&e1, vec![0.0, 0.0]);
+        domain.insert_dirichlet_constraint(&e3, vec![1.0, 1.0]);
+        domain.insert_neumann_constraint(&e2, vec![0.0, 0.0]);
+        domain.insert_neumann_constraint(&e4, vec![0.0, 0.0]);
+
+        let (system_matrix, extern_matrix) = build(10.0, &domain);
+        assert_eq!(system_matrix.nrows(), 12);
+        let answer = system_matrix.lu().solve(&extern_matrix).unwrap();
+
+        /* Linear solution u = y is reproduced at the vertices of each element */
+        for element in domain.elements.iter() {
+            let modal = DVector::from_iterator(
+                6,
+                domain.element_indexes(element).iter().map(|index| answer[(*index, 0)]),
+            );
+            let (u1, u2, u3) = dubiner::vertex_values(2, &modal);
+            assert!((u1 - element.p1.y).abs() < 1.0E-12);
+            assert!((u2 - element.p2.y).abs() < 1.0E-12);
+            assert!((u3 - element.p3.y).abs() < 1.0E-12);
+        }
+    }
+
+    #[test]
+    fn sample_6() {
+        /* square into triangles of different degree */
+        let p1 = Rc::new(Point::new(0.0, 0.0));
+        let p2 = Rc::new(Point::new(1.0, 0.0));
+        let p3 = Rc::new(Point::new(1.0, 1.0));
+        let p4 = Rc::new(Point::new(0.0, 1.0));
+
+        let e1 = Rc::new(Edge::new(&p1, &p2));
+        let e2 = Rc::new(Edge::new(&p2, &p3));
+        let e3 = Rc::new(Edge::new(&p3, &p4));
+        let e4 = Rc::new(Edge::new(&p4, &p1));
+
+        let t1 = Rc::new(TriangleElementL1::new(&p1, &p2, &p4));
+        let t2 = Rc::new(TriangleElementL1::new(&p4, &p2, &p3));
+
+        let mut domain = Domain::new_empty();
+        domain.insert_element(&t1);
+        domain.insert_element_with_degree(&t2, 3).unwrap();
+
+        domain.insert_dirichlet_constraint(&e1, vec![0.0, 0.0]);
+        domain.insert_dirichlet_constraint(&e3, vec![1.0, 1.0]);
+        domain.insert_neumann_constraint(&e2, vec![0.0, 0.0]);
+        domain.insert_neumann_constraint(&e4, vec![0.0, 0.0]);
+
+        let (system_matrix, extern_matrix) = build(10.0, &domain);
+        assert_eq!(system_matrix.nrows(), 13);
+        assert!((&system_matrix - system_matrix.transpose()).norm() < 1.0E-12);
+        let answer = system_matrix.lu().solve(&extern_matrix).unwrap();
+
+        /* Linear solution u = y is reproduced by both elements */
+        for (index, point) in domain.element_indexes(&t1).iter().zip([&p1, &p2, &p4].iter()) {
+            assert!((answer[(*index, 0)] - point.y).abs() < 1.0E-12);
+        }
+        let modal = DVector::from_iterator(
+            10,
+            domain.element_indexes(&t2).iter().map(|index| answer[(*index, 0)]),
+        );
+        let (u1, u2, u3) = dubiner::vertex_values(3, &modal);
+        assert!((u1 - 1.0).abs() < 1.0E-12);
+        assert!(u2.abs() < 1.0E-12);
+        assert!((u3 - 1.0).abs() < 1.0E-12);
+    }
+
+    #[test]
+    fn sample_7() {
+        /* square into triangles, with piecewise constant modal basis */
+        let p1 = Rc::new(Point::new(0.0, 0.0));
+        let p2 = Rc::new(Point::new(1.0, 0.0));
+        let p3 = Rc::new(Point::new(1.0, 1.0));
+        let p4 = Rc::new(Point::new(0.0, 1.0));
+
+        let e1 = Rc::new(Edge::new(&p1, &p2));
+        let e3 = Rc::new(Edge::new(&p3, &p4));
+
+        let t1 = Rc::new(TriangleElementL1::new(&p1, &p2, &p4));
+        let t2 = Rc::new(TriangleElementL1::new(&p4, &p2, &p3));
+
+        let mut domain = Domain::new_modal(0).unwrap();
+        domain.insert_element(&t1);
+        domain.insert_element(&t2);
+
+        domain.insert_dirichlet_constraint(&e1, vec![0.0, 0.0]);
+        domain.insert_dirichlet_constraint(&e3, vec![1.0, 1.0]);
+
+        let (system_matrix, extern_matrix) = build(10.0, &domain);
+        assert_eq!(system_matrix.nrows(), 2);
+        assert_eq!(extern_matrix.nrows(), 2);
+        assert!((&system_matrix - system_matrix.transpose()).norm() < 1.0E-12);
+
+        /* Penalties alone couple constants: values lie between both dirichlet ones */
+        let answer = system_matrix.cholesky().unwrap().solve(&extern_matrix);
+        let bottom = answer[(domain.element_indexes(&t1)[0], 0)];
+        let top = answer[(domain.element_indexes(&t2)[0], 0)];
+        assert!(0.0 < bottom && bottom < top && top < 1.0);
+    }
+}
+
+#[cfg(test)]
+mod convection_diffusion {
+    use super::*;
+    use crate::common::point::Point;
+    use crate::triangle::mesh;
+
+    #[test]
+    fn sample_1() {
+        /* Pure advection transports u = y - x / 2 along b = (1, 1/2) exactly */
+        let mut domain = Domain::new_empty();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 4, 4, &mut domain);
+        let exact = |point: &Point| point.y - point.x / 2.0;
+        for edge in boundary.left.iter().chain(boundary.bottom.iter()) {
+            domain.insert_dirichlet_constraint(edge, vec![exact(&edge.p1), exact(&edge.p2)]);
+        }
+
+        let velocity = |_: &Point| (1.0, 0.5);
+        let (system_matrix, extern_matrix) =
+            build_convection_diffusion(0.0, &velocity, 10.0, &domain);
+        let answer = system_matrix.lu().solve(&extern_matrix).unwrap();
+
+        for element in domain.elements.iter() {
+            let indexes = domain.element_indexes(element);
+            for (index, point) in indexes
+                .iter()
+                .zip([&element.p1, &element.p2, &element.p3].iter())
+            {
+                assert!((answer[(*index, 0)] - exact(point)).abs() < 1.0E-12);
+            }
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Outflow boundary layer at Peclet number 1.0E6: u = 0 at inflow x = 0
+            and u = 1 at outflow x = 1. Solution stays bounded, without the
+            oscillations of continuous galerkin, and vanishes upstream.
+            The upwind flux ignores outflow data, which only enters through the
+            diffusive penalty k sigma / h, so the outflow value is mostly lost:
+            u = 1 is weakly enforced as u = k sigma / h at x = 1
+        */
+        let mut domain = Domain::new_empty();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 8, 8, &mut domain);
+        for edge in boundary.left.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+        for edge in boundary.right.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![1.0, 1.0]);
+        }
+        for edge in boundary.bottom.iter().chain(boundary.top.iter()) {
+            domain.insert_neumann_constraint(edge, vec![0.0, 0.0]);
+        }
+
+        let (diffusivity, sigma) = (1.0E-6, 10.0);
+        let velocity = |_: &Point| (1.0, 0.0);
+        let (system_matrix, extern_matrix) =
+            build_convection_diffusion(diffusivity, &velocity, sigma, &domain);
+        let answer = system_matrix.lu().solve(&extern_matrix).unwrap();
+
+        for element in domain.elements.iter() {
+            let indexes = domain.element_indexes(element);
+            for (index, point) in indexes
+                .iter()
+                .zip([&element.p1, &element.p2, &element.p3].iter())
+            {
+                if point.x < 1.0 - 1.0E-12 {
+                    let value = answer[(*index, 0)];
+                    assert!(value.abs() < 1.0E-3, "{} {}", point, value);
+                }
+            }
+        }
+
+        /* Edges of length h = 1 / 8 */
+        let weak_value = diffusivity * sigma * 8.0;
+        for edge in boundary.right.iter() {
+            let element = domain.adjacency.get(edge).unwrap();
+            let indexes = domain.element_indexes(element);
+            for (index, point) in indexes
+                .iter()
+                .zip([&element.p1, &element.p2, &element.p3].iter())
+            {
+                if **point == edge.p1 || **point == edge.p2 {
+                    let value = answer[(*index, 0)];
+                    assert!(
+                        (value - weak_value).abs() < 0.01 * weak_value,
+                        "{} {}",
+                        point,
+                        value
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod local_discontinuous {
+    use super::*;
+    use crate::common::point::Point;
+    use crate::triangle::{
+        basis::{dubiner, shape::Basis},
+        mesh,
+    };
+    use nalgebra::DVector;
+
+    /* Greatest error at the vertices of each element */
+    fn vertex_error(domain: &Domain, answer: &DMatrix<f64>, exact: &dyn Fn(&Point) -> f64) -> f64 {
+        let mut error: f64 = 0.0;
+        for element in domain.elements.iter() {
+            let indexes = domain.element_indexes(element);
+            let coefficients = DVector::from_iterator(
+                indexes.len(),
+                indexes.iter().map(|index| answer[(*index, 0)]),
+            );
+            let values = match domain.element_basis(element) {
+                Basis::Nodal => (coefficients[0], coefficients[1], coefficients[2]),
+                Basis::Modal(degree) => dubiner::vertex_values(degree, &coefficients),
+            };
+            error = error
+                .max((values.0 - exact(&element.p1)).abs())
+                .max((values.1 - exact(&element.p2)).abs())
+                .max((values.2 - exact(&element.p3)).abs());
+        }
+        return error;
+    }
+
+    fn square(nodes: usize, domain: &mut Domain, exact: &dyn Fn(&Point) -> f64) {
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), nodes, nodes, domain);
+        let edges = boundary
+            .left
+            .iter()
+            .chain(boundary.right.iter())
+            .chain(boundary.bottom.iter())
+            .chain(boundary.top.iter());
+        for edge in edges {
+            domain.insert_dirichlet_constraint(edge, vec![exact(&edge.p1), exact(&edge.p2)]);
+        }
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Linear solutions are reproduced, with dirichlet and neumann edges */
+        let exact = |point: &Point| 2.0 * point.x - point.y + 1.0;
+        for degree in [1, 2].iter() {
+            let mut domain = Domain::new_modal(*degree).unwrap();
+            square(3, &mut domain, &exact);
+            let (system_matrix, extern_matrix) = build_ldg(10.0, &domain);
+            let asymmetry = (&system_matrix - system_matrix.transpose()).norm();
+            assert!(asymmetry < 1.0E-14 * system_matrix.norm());
+            let answer = system_matrix.lu().solve(&extern_matrix).unwrap();
+            assert!(vertex_error(&domain, &answer, &exact) < 1.0E-12);
+        }
+
+        let mut domain = Domain::new_empty();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 3, 3, &mut domain);
+        for edge in boundary.bottom.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+        for edge in boundary.top.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![1.0, 1.0]);
+        }
+        for edge in boundary.left.iter().chain(boundary.right.iter()) {
+            domain.insert_neumann_constraint(edge, vec![0.0, 0.0]);
+        }
+        let (system_matrix, extern_matrix) = build_ldg(10.0, &domain);
+        let answer = system_matrix.lu().solve(&extern_matrix).unwrap();
+        assert!(vertex_error(&domain, &answer, &|point: &Point| point.y) < 1.0E-12);
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Harmonic u = x y: exact for both formulations at degree 2,
+            second order for both at degree 1, with errors alike
+        */
+        let exact = |point: &Point| point.x * point.y;
+        let mut domain = Domain::new_modal(2).unwrap();
+        square(2, &mut domain, &exact);
+        for formulation in [build, build_ldg].iter() {
+            let (system_matrix, extern_matrix) = formulation(10.0, &domain);
+            let answer = system_matrix.lu().solve(&extern_matrix).unwrap();
+            assert!(vertex_error(&domain, &answer, &exact) < 1.0E-12);
+        }
+
+        /* Errors of interior penalty and LDG, on meshes of 4 x 4 and 8 x 8 cells */
+        let mut errors: Vec<Vec<f64>> = Vec::new();
+        for nodes in [4, 8].iter() {
+            let mut domain = Domain::new_empty();
+            square(*nodes, &mut domain, &exact);
+            let mut row = Vec::new();
+            for formulation in [build, build_ldg].iter() {
+                let (system_matrix, extern_matrix) = formulation(10.0, &domain);
+                let answer = system_matrix.lu().solve(&extern_matrix).unwrap();
+                row.push(vertex_error(&domain, &answer, &exact));
+            }
+            errors.push(row);
+        }
+        let (coarse, fine) = (&errors[0], &errors[1]);
+        assert!(fine[1] < coarse[1] / 3.0);
+        assert!(fine[1] < 2.0 * fine[0] && fine[0] < 2.0 * fine[1]);
     }
 }
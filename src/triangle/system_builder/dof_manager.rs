@@ -0,0 +1,383 @@
+use nalgebra::DMatrix;
+
+use crate::triangle::{element::TriangleElementL1, system_builder::domain::Domain};
+
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+/* Cells per side of the grid over which the Hilbert curve orders element centroids */
+const HILBERT_ORDER: u32 = 16;
+
+pub enum Numbering {
+    /* Bandwidth reduction over the graph of elements sharing an edge */
+    ReverseCuthillMcKee,
+    /* Element centroids along the Hilbert curve over the domain bounding box */
+    Hilbert,
+}
+
+/**
+ * Permutation of the degrees of freedom after a renumbering:
+ *  forward[i] is the new global index of the former index i
+ */
+pub struct DofPermutation {
+    pub forward: Vec<usize>,
+}
+
+impl DofPermutation {
+    /**
+     * Solution of the former numbering into the new one
+     */
+    pub fn apply(&self, solution: &DMatrix<f64>) -> DMatrix<f64> {
+        let mut permuted = DMatrix::<f64>::zeros(solution.nrows(), solution.ncols());
+        for (former, new) in self.forward.iter().enumerate() {
+            permuted.set_row(*new, &solution.row(former));
+        }
+        return permuted;
+    }
+
+    /**
+     * Solution of the new numbering back into the former one
+     */
+    pub fn revert(&self, solution: &DMatrix<f64>) -> DMatrix<f64> {
+        let mut permuted = DMatrix::<f64>::zeros(solution.nrows(), solution.ncols());
+        for (former, new) in self.forward.iter().enumerate() {
+            permuted.set_row(former, &solution.row(*new));
+        }
+        return permuted;
+    }
+}
+
+/**
+ * Element order of the numbering: position k holds the index of the element placed there.
+ *  Ties are broken by element index, so that the same domain always gives the same order
+ */
+pub fn element_order(domain: &Domain, numbering: &Numbering) -> Vec<usize> {
+    match numbering {
+        Numbering::ReverseCuthillMcKee => reverse_cuthill_mckee(&neighbours(domain)),
+        Numbering::Hilbert => hilbert_order(&domain.elements),
+    }
+}
+
+/**
+ * Reorders the domain elements by the numbering, returning the permutation
+ * of the degrees of freedom. Previously built systems are no longer valid
+ */
+pub fn renumber(domain: &mut Domain, numbering: &Numbering) -> DofPermutation {
+    let order = element_order(domain, numbering);
+    let former: Vec<Vec<usize>> = order
+        .iter()
+        .map(|index| domain.element_indexes(&domain.elements[*index]))
+        .collect();
+    domain
+        .reorder_elements(&order)
+        .expect("Not expected element order other than a permutation");
+
+    let mut forward = vec![0; domain.size()];
+    for (element, indexes) in domain.elements.iter().zip(former.iter()) {
+        for (new, former) in domain.element_indexes(element).iter().zip(indexes.iter()) {
+            forward[*former] = *new;
+        }
+    }
+    return DofPermutation { forward };
+}
+
+/**
+ * Greatest distance from the diagonal among the nonzero entries
+ */
+pub fn bandwidth(matrix: &DMatrix<f64>) -> usize {
+    let mut bandwidth = 0;
+    for col in 0..matrix.ncols() {
+        for row in 0..matrix.nrows() {
+            if matrix[(row, col)] != 0.0 {
+                bandwidth = bandwidth.max(row.abs_diff(col));
+            }
+        }
+    }
+    return bandwidth;
+}
+
+/**
+ * Indexes of the elements sharing an edge with each element, ascending
+ */
+fn neighbours(domain: &Domain) -> Vec<Vec<usize>> {
+    let positions: HashMap<&Rc<TriangleElementL1>, usize> = domain
+        .elements
+        .iter()
+        .enumerate()
+        .map(|(position, element)| (element, position))
+        .collect();
+    return domain
+        .elements
+        .iter()
+        .map(|element| {
+            let (e1, e2, e3) = element.inner_edges();
+            let mut list: Vec<usize> = [e1, e2, e3]
+                .iter()
+                .filter_map(|edge| domain.adjacency.get(&edge.opposed()))
+                .map(|neighbour| positions[neighbour])
+                .collect();
+            list.sort_unstable();
+            list
+        })
+        .collect();
+}
+
+/**
+ * Reverse Cuthill-McKee ordering of a graph, each component started
+ * from a pseudo-peripheral node of least degree
+ *
+ *  Reference:
+ *      George, A.; Liu, J.W.H. Computer Solution of Large Sparse Positive
+ *      Definite Systems. Prentice-Hall, 1981.
+ */
+fn reverse_cuthill_mckee(graph: &[Vec<usize>]) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..graph.len()).collect();
+    candidates.sort_by_key(|node| (graph[*node].len(), *node));
+
+    let mut visited = vec![false; graph.len()];
+    let mut order: Vec<usize> = Vec::new();
+    for candidate in candidates.iter() {
+        if visited[*candidate] {
+            continue;
+        }
+        let start = pseudo_peripheral(graph, *candidate);
+        visited[start] = true;
+        let mut queue = VecDeque::from(vec![start]);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            let mut next: Vec<usize> = graph[node]
+                .iter()
+                .cloned()
+                .filter(|neighbour| !visited[*neighbour])
+                .collect();
+            next.sort_by_key(|neighbour| (graph[*neighbour].len(), *neighbour));
+            for neighbour in next {
+                visited[neighbour] = true;
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    order.reverse();
+    return order;
+}
+
+/**
+ * Node of the last level of a breadth first search, moved there while
+ * the number of levels grows
+ */
+fn pseudo_peripheral(graph: &[Vec<usize>], start: usize) -> usize {
+    let mut node = start;
+    let (mut levels, mut last) = level_structure(graph, node);
+    loop {
+        let candidate = *last
+            .iter()
+            .min_by_key(|other| (graph[**other].len(), **other))
+            .unwrap_or(&node);
+        let (candidate_levels, candidate_last) = level_structure(graph, candidate);
+        if candidate_levels <= levels {
+            return node;
+        }
+        node = candidate;
+        levels = candidate_levels;
+        last = candidate_last;
+    }
+}
+
+/**
+ * Number of levels of the breadth first search from the node, with the nodes of the last one
+ */
+fn level_structure(graph: &[Vec<usize>], start: usize) -> (usize, Vec<usize>) {
+    let mut visited = vec![false; graph.len()];
+    visited[start] = true;
+    let mut level = vec![start];
+    let mut levels = 1;
+    loop {
+        let mut next = Vec::new();
+        for node in level.iter() {
+            for neighbour in graph[*node].iter() {
+                if !visited[*neighbour] {
+                    visited[*neighbour] = true;
+                    next.push(*neighbour);
+                }
+            }
+        }
+        if next.is_empty() {
+            return (levels, level);
+        }
+        level = next;
+        levels += 1;
+    }
+}
+
+/**
+ * Elements sorted by the Hilbert index of their centroids
+ */
+fn hilbert_order(elements: &[Rc<TriangleElementL1>]) -> Vec<usize> {
+    let centroids: Vec<(f64, f64)> = elements
+        .iter()
+        .map(|element| {
+            (
+                (element.p1.x + element.p2.x + element.p3.x) / 3.0,
+                (element.p1.y + element.p2.y + element.p3.y) / 3.0,
+            )
+        })
+        .collect();
+    let (mut x0, mut y0) = (f64::INFINITY, f64::INFINITY);
+    let (mut x1, mut y1) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for (x, y) in centroids.iter() {
+        x0 = x0.min(*x);
+        y0 = y0.min(*y);
+        x1 = x1.max(*x);
+        y1 = y1.max(*y);
+    }
+
+    /* Square box, so that the curve keeps the aspect of the domain */
+    let side = (x1 - x0).max(y1 - y0).max(f64::MIN_POSITIVE);
+    let cells = 1u64 << HILBERT_ORDER;
+    let cell = |value: f64| (((value / side) * cells as f64) as u64).min(cells - 1);
+    let mut keys: Vec<(u64, usize)> = centroids
+        .iter()
+        .enumerate()
+        .map(|(index, (x, y))| (hilbert_index(cells, cell(x - x0), cell(y - y0)), index))
+        .collect();
+    keys.sort_unstable();
+    return keys.iter().map(|(_, index)| *index).collect();
+}
+
+/**
+ * Distance along the Hilbert curve of the cell (x, y), over a grid of cells by cells
+ */
+fn hilbert_index(cells: u64, mut x: u64, mut y: u64) -> u64 {
+    let mut index = 0;
+    let mut s = cells / 2;
+    while s > 0 {
+        let rx = ((x & s) > 0) as u64;
+        let ry = ((y & s) > 0) as u64;
+        index += s * s * ((3 * rx) ^ ry);
+        /* Rotates the quadrant */
+        if ry == 0 {
+            if rx == 1 {
+                x = cells - 1 - x;
+                y = cells - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    return index;
+}
+
+#[cfg(test)]
+mod renumbering {
+    use super::*;
+    use crate::triangle::{mesh, system_builder::builder};
+
+    /**
+     * Rectangle whose elements are inserted in scrambled order, the left edges held at one
+     */
+    fn scrambled(nx: usize, ny: usize, degree: usize) -> Domain {
+        let mut ordered = Domain::new_modal(degree).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (2.0, 1.0), nx, ny, &mut ordered);
+        let count = ordered.elements.len();
+        let mut domain = Domain::new_modal(degree).unwrap();
+        for k in 0..count {
+            domain.insert_element(&ordered.elements[(k * 37) % count]);
+        }
+        for edge in boundary.left.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![1.0, 1.0]);
+        }
+        for edge in boundary.right.iter() {
+            domain.insert_neumann_constraint(edge, vec![0.5, 0.5]);
+        }
+        return domain;
+    }
+
+    #[test]
+    fn sample_1() {
+        /*
+            Reverse Cuthill-McKee narrows the band of the scrambled system,
+            and the renumbered solve is the permuted former one
+        */
+        let mut domain = scrambled(8, 4, 1);
+        let (system_matrix, extern_matrix) = builder::build(10.0, &domain);
+        let solution = system_matrix.clone().lu().solve(&extern_matrix).unwrap();
+
+        let permutation = renumber(&mut domain, &Numbering::ReverseCuthillMcKee);
+        let (renumbered_matrix, renumbered_extern) = builder::build(10.0, &domain);
+        assert!(4 * bandwidth(&renumbered_matrix) < bandwidth(&system_matrix));
+        assert!(bandwidth(&renumbered_matrix) <= 3 * 12);
+
+        let renumbered = renumbered_matrix.lu().solve(&renumbered_extern).unwrap();
+        assert!((permutation.apply(&solution) - &renumbered).amax() < 1.0E-10);
+        assert!((permutation.revert(&renumbered) - &solution).amax() < 1.0E-10);
+    }
+
+    /* Mean distance from the diagonal of the nonzero entries */
+    fn mean_distance(matrix: &DMatrix<f64>) -> f64 {
+        let mut sum = 0.0;
+        let mut count = 0;
+        for col in 0..matrix.ncols() {
+            for row in 0..matrix.nrows() {
+                if matrix[(row, col)] != 0.0 {
+                    sum += (row as f64 - col as f64).abs();
+                    count += 1;
+                }
+            }
+        }
+        return sum / count as f64;
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Hilbert order visits every element once, nearby elements next to each other:
+            the band is not bounded across quadrants, but entries gather near the diagonal
+        */
+        let mut domain = scrambled(8, 8, 1);
+        let order = element_order(&domain, &Numbering::Hilbert);
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..128).collect::<Vec<usize>>());
+
+        let (system_matrix, _) = builder::build(10.0, &domain);
+        renumber(&mut domain, &Numbering::Hilbert);
+        let (renumbered_matrix, _) = builder::build(10.0, &domain);
+        assert!(4.0 * mean_distance(&renumbered_matrix) < mean_distance(&system_matrix));
+
+        /* The first cells of the curve: the corner quadrant of four cells */
+        assert_eq!(hilbert_index(2, 0, 0), 0);
+        assert_eq!(hilbert_index(2, 0, 1), 1);
+        assert_eq!(hilbert_index(2, 1, 1), 2);
+        assert_eq!(hilbert_index(2, 1, 0), 3);
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            Identical domains give identical orders and bitwise identical systems,
+            mixed degrees and disconnected components included
+        */
+        let build = || {
+            let mut domain = scrambled(6, 3, 2);
+            let element = Rc::clone(&domain.elements[5]);
+            domain.set_element_degree(&element, 3).unwrap();
+            mesh::rectangle((5.0, 0.0), (6.0, 1.0), 2, 2, &mut domain);
+            domain
+        };
+        let (mut first, mut second) = (build(), build());
+        let first_order = element_order(&first, &Numbering::ReverseCuthillMcKee);
+        assert_eq!(
+            first_order,
+            element_order(&second, &Numbering::ReverseCuthillMcKee)
+        );
+        assert_eq!(first_order.len(), first.elements.len());
+
+        let first_permutation = renumber(&mut first, &Numbering::ReverseCuthillMcKee);
+        let second_permutation = renumber(&mut second, &Numbering::ReverseCuthillMcKee);
+        assert_eq!(first_permutation.forward, second_permutation.forward);
+        let (first_matrix, first_extern) = builder::build(10.0, &first);
+        let (second_matrix, second_extern) = builder::build(10.0, &second);
+        assert!(first_matrix == second_matrix);
+        assert!(first_extern == second_extern);
+    }
+}
@@ -1,6 +1,11 @@
 use crate::common::edge::Edge;
+use crate::common::error::Error;
 use crate::common::point::Point;
-use crate::triangle::{boundary_constraint::BoundaryConstraint, element::TriangleElementL1};
+use crate::triangle::{
+    basis::shape::{Basis, MAX_MODAL_DEGREE},
+    boundary_constraint::{BoundaryConstraint, RadiationConstraint},
+    element::TriangleElementL1,
+};
 
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -15,11 +20,39 @@ pub struct Domain {
     */
     pub index_mapping: HashMap<(Rc<TriangleElementL1>, Rc<Point>), usize>,
 
+    /*
+        Basis of elements inserted without explicit degree
+    */
+    basis: Basis,
+
+    /*
+        Basis mapping points each triangle into its own basis, so that
+        neighbours may have different polynomial degree
+    */
+    pub basis_mapping: HashMap<Rc<TriangleElementL1>, Basis>,
+
+    /*
+        Offset mapping points each triangle into the index of its first
+        degree of freedom. The remaining ones follow sequentially,
+        as many as the size of the element basis
+    */
+    pub offset_mapping: HashMap<Rc<TriangleElementL1>, usize>,
+
+    /*
+        Running total of degrees of freedom, kept along the offsets
+    */
+    dofs: usize,
+
     /*
         Boundary Constraints
     */
     pub dirichlet_constraints: Vec<Rc<BoundaryConstraint>>,
     pub neumann_constraints: Vec<Rc<BoundaryConstraint>>,
+
+    /*
+        Nonlinear boundary constraints, only taken by the nonlinear builders
+    */
+    pub radiation_constraints: Vec<Rc<RadiationConstraint>>,
 }
 
 impl Domain {
@@ -28,38 +61,184 @@ impl Domain {
             elements: Vec::new(),
             adjacency: HashMap::new(),
             index_mapping: HashMap::new(),
+            basis: Basis::Nodal,
+            basis_mapping: HashMap::new(),
+            offset_mapping: HashMap::new(),
+            dofs: 0,
             dirichlet_constraints: Vec::new(),
             neumann_constraints: Vec::new(),
+            radiation_constraints: Vec::new(),
+        }
+    }
+
+    /**
+     * Empty domain whose elements use the modal basis of given degree.
+     * Fails for degrees beyond basis::shape::MAX_MODAL_DEGREE
+     */
+    pub fn new_modal(degree: usize) -> Result<Self, Error> {
+        let mut domain = Domain::new_empty();
+        domain.basis = checked(Basis::Modal(degree))?;
+        return Ok(domain);
+    }
+
+    /**
+     * Basis of elements inserted without explicit degree
+     */
+    pub fn basis(&self) -> Basis {
+        return self.basis;
+    }
+
+    /**
+     * Total number of degrees of freedom
+     */
+    pub fn size(&self) -> usize {
+        return self.dofs;
+    }
+
+    /**
+     * Basis of the given element
+     */
+    pub fn element_basis(&self, triangle: &Rc<TriangleElementL1>) -> Basis {
+        return *self.basis_mapping.get(triangle).unwrap();
+    }
+
+    /**
+     * Global indexes of the element degrees of freedom, in local basis order
+     */
+    pub fn element_indexes(&self, triangle: &Rc<TriangleElementL1>) -> Vec<usize> {
+        match self.element_basis(triangle) {
+            Basis::Nodal => [&triangle.p1, &triangle.p2, &triangle.p3]
+                .iter()
+                .map(|point| {
+                    *self
+                        .index_mapping
+                        .get(&(Rc::clone(triangle), Rc::clone(point)))
+                        .unwrap()
+                })
+                .collect(),
+            Basis::Modal(degree) => {
+                let offset = *self.offset_mapping.get(triangle).unwrap();
+                (offset..offset + Basis::Modal(degree).size()).collect()
+            }
         }
     }
 
     pub fn insert_element(&mut self, triangle: &Rc<TriangleElementL1>) {
+        let basis = self.basis;
+        self.push_element(triangle, basis);
+    }
+
+    /**
+     * Inserts an element with modal basis of its own degree
+     */
+    pub fn insert_element_with_degree(
+        &mut self,
+        triangle: &Rc<TriangleElementL1>,
+        degree: usize,
+    ) -> Result<(), Error> {
+        return self.insert_element_with_basis(triangle, Basis::Modal(degree));
+    }
+
+    pub fn insert_element_with_basis(
+        &mut self,
+        triangle: &Rc<TriangleElementL1>,
+        basis: Basis,
+    ) -> Result<(), Error> {
+        self.push_element(triangle, checked(basis)?);
+        return Ok(());
+    }
+
+    fn push_element(&mut self, triangle: &Rc<TriangleElementL1>, basis: Basis) {
         let (e1, e2, e3) = triangle.inner_edges();
+        let offset = self.dofs;
         self.elements.push(Rc::clone(triangle));
 
         self.adjacency.insert(Rc::clone(&e1), Rc::clone(triangle));
         self.adjacency.insert(Rc::clone(&e2), Rc::clone(triangle));
         self.adjacency.insert(Rc::clone(&e3), Rc::clone(triangle));
 
-        self.index_mapping.insert(
-            (Rc::clone(triangle), Rc::clone(&triangle.p1)),
-            self.index_mapping.len(),
-        );
-        self.index_mapping.insert(
-            (Rc::clone(triangle), Rc::clone(&triangle.p2)),
-            self.index_mapping.len(),
-        );
-        self.index_mapping.insert(
-            (Rc::clone(triangle), Rc::clone(&triangle.p3)),
-            self.index_mapping.len(),
-        );
+        self.basis_mapping.insert(Rc::clone(triangle), basis);
+        self.index_element(triangle, offset);
+        self.dofs += basis.size();
+    }
+
+    /**
+     * Changes the degree of an element. Every element is renumbered,
+     * so previously built systems are no longer valid
+     */
+    pub fn set_element_degree(
+        &mut self,
+        triangle: &Rc<TriangleElementL1>,
+        degree: usize,
+    ) -> Result<(), Error> {
+        self.basis_mapping
+            .insert(Rc::clone(triangle), checked(Basis::Modal(degree))?);
+        self.reindex();
+        return Ok(());
+    }
+
+    /**
+     * Reorders the elements, the new position k holding the former element order[k].
+     * Degrees of freedom follow the new element order, so previously built systems
+     * are no longer valid. Fails, leaving the domain as it was, if order is not
+     * a permutation of the element positions
+     */
+    pub fn reorder_elements(&mut self, order: &[usize]) -> Result<(), Error> {
+        let mut taken = vec![false; self.elements.len()];
+        for index in order.iter() {
+            match taken.get_mut(*index) {
+                Some(taken) if !*taken => *taken = true,
+                _ => return Err(Error::Format),
+            }
+        }
+        if order.len() != self.elements.len() {
+            return Err(Error::Format);
+        }
+
+        self.elements = order
+            .iter()
+            .map(|index| Rc::clone(&self.elements[*index]))
+            .collect();
+        self.reindex();
+        return Ok(());
+    }
+
+    /**
+     * Numbers every element sequentially, in the elements order
+     */
+    fn reindex(&mut self) {
+        self.index_mapping.clear();
+        self.offset_mapping.clear();
+        let mut offset = 0;
+        for triangle in self.elements.clone().iter() {
+            self.index_element(triangle, offset);
+            offset += self.element_basis(triangle).size();
+        }
+        self.dofs = offset;
+    }
+
+    /**
+     * Sets offset of the element. Nodal basis also indexes its points
+     */
+    fn index_element(&mut self, triangle: &Rc<TriangleElementL1>, offset: usize) {
+        self.offset_mapping.insert(Rc::clone(triangle), offset);
+        if self.element_basis(triangle) != Basis::Nodal {
+            return;
+        }
+
+        self.index_mapping
+            .insert((Rc::clone(triangle), Rc::clone(&triangle.p1)), offset);
+        self.index_mapping
+            .insert((Rc::clone(triangle), Rc::clone(&triangle.p2)), offset + 1);
+        self.index_mapping
+            .insert((Rc::clone(triangle), Rc::clone(&triangle.p3)), offset + 2);
     }
 
     pub fn insert_dirichlet_constraint(&mut self, edge: &Rc<Edge>, values: Vec<f64>) {
         let triangle = self.adjacency.get(edge).unwrap();
         let mut values_mapping: HashMap<Rc<Point>, f64> = HashMap::new();
 
-        values_mapping.insert(Rc::clone(&edge.p1), *values.get(0).unwrap());
+        values_mapping.insert(Rc::clone(&edge.p1), *values.first().unwrap());
         values_mapping.insert(Rc::clone(&edge.p2), *values.get(1).unwrap());
 
         self.dirichlet_constraints.push(Rc::new(BoundaryConstraint {
@@ -73,7 +252,7 @@ impl Domain {
         let triangle = self.adjacency.get(edge).unwrap();
         let mut values_mapping: HashMap<Rc<Point>, f64> = HashMap::new();
 
-        values_mapping.insert(Rc::clone(&edge.p1), *values.get(0).unwrap());
+        values_mapping.insert(Rc::clone(&edge.p1), *values.first().unwrap());
         values_mapping.insert(Rc::clone(&edge.p2), *values.get(1).unwrap());
 
         self.neumann_constraints.push(Rc::new(BoundaryConstraint {
@@ -82,4 +261,94 @@ impl Domain {
             values: values_mapping,
         }))
     }
+
+    pub fn insert_radiation_constraint(
+        &mut self,
+        edge: &Rc<Edge>,
+        emissivity: f64,
+        ambient: Vec<f64>,
+    ) {
+        let triangle = self.adjacency.get(edge).unwrap();
+        let mut ambient_mapping: HashMap<Rc<Point>, f64> = HashMap::new();
+
+        ambient_mapping.insert(Rc::clone(&edge.p1), *ambient.first().unwrap());
+        ambient_mapping.insert(Rc::clone(&edge.p2), *ambient.get(1).unwrap());
+
+        self.radiation_constraints
+            .push(Rc::new(RadiationConstraint {
+                element: Rc::clone(triangle),
+                boundary_edge: Rc::clone(edge),
+                emissivity,
+                ambient: ambient_mapping,
+            }))
+    }
 } /* end - domain */
+
+/**
+ * The basis itself, if its degree is supported by the quadrature rules
+ */
+fn checked(basis: Basis) -> Result<Basis, Error> {
+    match basis {
+        Basis::Modal(degree) if degree > MAX_MODAL_DEGREE => Err(Error::UnsupportedDegree),
+        _ => Ok(basis),
+    }
+}
+
+#[cfg(test)]
+mod degree {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /* Degrees beyond the quadrature rules are rejected up front */
+        assert!(Domain::new_modal(MAX_MODAL_DEGREE).is_ok());
+        assert_eq!(
+            Domain::new_modal(MAX_MODAL_DEGREE + 1).err(),
+            Some(Error::UnsupportedDegree)
+        );
+
+        let p1 = Rc::new(Point::new(0.0, 0.0));
+        let p2 = Rc::new(Point::new(1.0, 0.0));
+        let p3 = Rc::new(Point::new(0.0, 1.0));
+        let element = Rc::new(TriangleElementL1::new(&p1, &p2, &p3));
+        let mut domain = Domain::new_modal(1).unwrap();
+        assert!(domain
+            .insert_element_with_degree(&element, MAX_MODAL_DEGREE + 1)
+            .is_err());
+        domain.insert_element(&element);
+        assert!(domain
+            .set_element_degree(&element, MAX_MODAL_DEGREE + 1)
+            .is_err());
+        assert_eq!(domain.element_basis(&element), Basis::Modal(1));
+        assert_eq!(domain.size(), 3);
+    }
+}
+
+#[cfg(test)]
+mod reorder {
+    use super::*;
+
+    #[test]
+    fn sample_1() {
+        /* Only permutations of the element positions are taken */
+        let p1 = Rc::new(Point::new(0.0, 0.0));
+        let p2 = Rc::new(Point::new(1.0, 0.0));
+        let p3 = Rc::new(Point::new(1.0, 1.0));
+        let p4 = Rc::new(Point::new(0.0, 1.0));
+        let first = Rc::new(TriangleElementL1::new(&p1, &p2, &p4));
+        let second = Rc::new(TriangleElementL1::new(&p3, &p4, &p2));
+        let mut domain = Domain::new_modal(1).unwrap();
+        domain.insert_element(&first);
+        domain.insert_element(&second);
+
+        for order in [vec![0], vec![1, 1], vec![0, 2], vec![0, 1, 0]].iter() {
+            assert_eq!(domain.reorder_elements(order), Err(Error::Format));
+            assert!(domain.elements[0] == first);
+            assert_eq!(domain.element_indexes(&first), vec![0, 1, 2]);
+        }
+
+        assert!(domain.reorder_elements(&[1, 0]).is_ok());
+        assert!(domain.elements[0] == second);
+        assert_eq!(domain.element_indexes(&first), vec![3, 4, 5]);
+    }
+}
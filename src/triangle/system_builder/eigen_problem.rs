@@ -0,0 +1,230 @@
+use nalgebra::DMatrix;
+
+use crate::common::error::Error;
+use crate::triangle::{
+    solvers::lobpcg,
+    system_builder::{builder, domain::Domain, internal::l2_mass},
+};
+
+const GUARD: usize = 2;
+
+/**
+ * Laplacian eigenmodes: - laplacian(u) = lambda u, with u = 0 at the dirichlet
+ * edges of the domain and zero normal derivative elsewhere. Generalized problem
+ *      A x = lambda M x
+ *  A is the interior penalty system of builder::build, whose dirichlet values
+ *  are irrelevant, and M the L2 mass matrix, block diagonal over elements
+ */
+pub struct EigenProblem {
+    pub stiffness: DMatrix<f64>,
+    pub mass: DMatrix<f64>,
+    /* Nonzero entries of each stiffness row, coupling only neighbour elements */
+    rows: Vec<Vec<(usize, f64)>>,
+    blocks: Vec<ElementBlock>,
+}
+
+/**
+ * Element indexes, with the mass block and the inverse of the stiffness block
+ */
+struct ElementBlock {
+    indexes: Vec<usize>,
+    mass: DMatrix<f64>,
+    inverse: DMatrix<f64>,
+}
+
+impl EigenProblem {
+    pub fn new(sigma: f64, domain: &Domain) -> Self {
+        let (stiffness, _) = builder::build(sigma, domain);
+        let size = domain.size();
+        let mut mass = DMatrix::<f64>::zeros(size, size);
+        l2_mass::build(&mut mass, domain).expect("Failed to build mass matrix");
+
+        let blocks = domain
+            .elements
+            .iter()
+            .map(|element| {
+                let indexes = domain.element_indexes(element);
+                let block = stiffness
+                    .select_rows(indexes.iter())
+                    .select_columns(indexes.iter());
+                let inverse = block
+                    .cholesky()
+                    .expect("Not expected element stiffness without positive definiteness")
+                    .inverse();
+                let local_mass = mass
+                    .select_rows(indexes.iter())
+                    .select_columns(indexes.iter());
+                ElementBlock {
+                    indexes,
+                    mass: local_mass,
+                    inverse,
+                }
+            })
+            .collect();
+
+        let rows = stiffness
+            .row_iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, value)| **value != 0.0)
+                    .map(|(column, value)| (column, *value))
+                    .collect()
+            })
+            .collect();
+
+        return EigenProblem {
+            stiffness,
+            mass,
+            rows,
+            blocks,
+        };
+    }
+
+    /**
+     * Stiffness product over the nonzero entries
+     */
+    pub fn apply_stiffness(&self, vectors: &DMatrix<f64>) -> DMatrix<f64> {
+        let mut result = DMatrix::<f64>::zeros(vectors.nrows(), vectors.ncols());
+        for (i, row) in self.rows.iter().enumerate() {
+            for j in 0..vectors.ncols() {
+                result[(i, j)] = row
+                    .iter()
+                    .map(|(column, value)| value * vectors[(*column, j)])
+                    .sum();
+            }
+        }
+        return result;
+    }
+
+    /**
+     * Block jacobi preconditioner: inverse of the element blocks of the stiffness
+     */
+    pub fn precondition(&self, vectors: &DMatrix<f64>) -> DMatrix<f64> {
+        return self.block_product(vectors, |block| &block.inverse);
+    }
+
+    /**
+     * Mass matrix product, element by element
+     */
+    pub fn apply_mass(&self, vectors: &DMatrix<f64>) -> DMatrix<f64> {
+        return self.block_product(vectors, |block| &block.mass);
+    }
+
+    fn block_product(
+        &self,
+        vectors: &DMatrix<f64>,
+        select: fn(&ElementBlock) -> &DMatrix<f64>,
+    ) -> DMatrix<f64> {
+        let mut result = DMatrix::<f64>::zeros(vectors.nrows(), vectors.ncols());
+        for block in self.blocks.iter() {
+            let indexes = &block.indexes;
+            let local = select(block) * vectors.select_rows(indexes.iter());
+            for (row, index) in indexes.iter().enumerate() {
+                result.row_mut(*index).copy_from(&local.row(row));
+            }
+        }
+        return result;
+    }
+
+    /**
+     * Lowest count eigenpairs, eigenvectors M orthonormal.
+     *  Iterates count + guard vectors, guarding against clustered eigenvalues.
+     *  Fails if count exceeds the size of the system
+     */
+    pub fn solve(
+        &self,
+        count: usize,
+        options: &lobpcg::Options,
+    ) -> Result<lobpcg::EigenPairs, Error> {
+        let size = self.stiffness.nrows();
+        let block = (count + GUARD).min(size);
+        /* Deterministic, independent start: golden ratio sequence */
+        let initial = DMatrix::from_fn(size, block, |i, j| {
+            ((i * block + j + 1) as f64 * 0.618_033_988_749_895).fract() - 0.5
+        });
+        return lobpcg::solve(
+            &|v: &DMatrix<f64>| self.apply_stiffness(v),
+            &|v: &DMatrix<f64>| self.apply_mass(v),
+            &|v: &DMatrix<f64>| self.precondition(v),
+            &initial,
+            count,
+            options,
+        );
+    }
+}
+
+#[cfg(test)]
+mod laplacian_modes {
+    use super::*;
+    use crate::triangle::mesh;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn sample_1() {
+        /* Unit square with dirichlet edges: pi^2 (m^2 + n^2), that is 2, 5, 5, 8 times pi^2 */
+        let mut domain = Domain::new_modal(2).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 5, 5, &mut domain);
+        let edges = boundary
+            .left
+            .iter()
+            .chain(boundary.right.iter())
+            .chain(boundary.bottom.iter())
+            .chain(boundary.top.iter());
+        for edge in edges {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+
+        let problem = EigenProblem::new(10.0, &domain);
+        let eigen = problem.solve(4, &lobpcg::Options::default()).unwrap();
+        assert!(eigen.converged);
+        for (value, factor) in eigen.values.iter().zip([2.0, 5.0, 5.0, 8.0].iter()) {
+            let exact = factor * PI * PI;
+            assert!((value - exact).abs() < 0.02 * exact);
+        }
+
+        /* Mass orthonormal eigenvectors */
+        let gram = eigen.vectors.transpose() * &problem.mass * &eigen.vectors;
+        assert!((gram - DMatrix::identity(4, 4)).norm() < 1.0E-8);
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Unit disc with dirichlet boundary: squares of the bessel zeros
+            j01 = 2.404826 and the double j11 = 3.831706
+        */
+        let mut domain = Domain::new_modal(2).unwrap();
+        for edge in mesh::disc((0.0, 0.0), 1.0, 4, &mut domain).iter() {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+
+        let problem = EigenProblem::new(10.0, &domain);
+        let eigen = problem.solve(3, &lobpcg::Options::default()).unwrap();
+        assert!(eigen.converged);
+        for (value, zero) in eigen
+            .values
+            .iter()
+            .zip([2.404_826, 3.831_706, 3.831_706].iter())
+        {
+            let exact: f64 = zero * zero;
+            assert!((value - exact).abs() < 0.02 * exact);
+        }
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            Unit square without dirichlet edges: zero normal derivative everywhere,
+            so the constant mode has eigenvalue 0 and the next one pi^2
+        */
+        let mut domain = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 4, 4, &mut domain);
+
+        let problem = EigenProblem::new(10.0, &domain);
+        let eigen = problem.solve(2, &lobpcg::Options::default()).unwrap();
+        assert!(eigen.converged);
+        assert!(eigen.values[0].abs() < 1.0E-8);
+        assert!((eigen.values[1] - PI * PI).abs() < 0.05 * PI * PI);
+    }
+}
@@ -0,0 +1,615 @@
+use nalgebra::{DMatrix, DVector, Matrix3x1};
+
+use crate::common::edge::Edge;
+use crate::common::point::Point;
+use crate::triangle::{
+    basis::shape::Basis,
+    boundary_constraint::BoundaryConstraint,
+    element::TriangleElementL1,
+    integrands::{dirichlet_constraint, elasticity, interface_penalty, neumann_constraint, utils},
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/**
+ * Two dimensional reduction of the three dimensional body
+ */
+#[derive(Clone, Copy)]
+pub enum PlaneModel {
+    /* Thin plates: no stress across the thickness */
+    Stress,
+    /* Long bodies: no strain across the thickness */
+    Strain,
+}
+
+/**
+ * Isotropic linear elastic material
+ */
+pub struct Material {
+    pub young: f64,
+    pub poisson: f64,
+    pub model: PlaneModel,
+}
+
+impl Material {
+    pub fn new(young: f64, poisson: f64, model: PlaneModel) -> Self {
+        Material {
+            young,
+            poisson,
+            model,
+        }
+    }
+
+    /**
+     * Lamé parameters (lambda, mu) of the planar problem:
+     * plane stress takes the reduced lambda = 2 lambda mu / (lambda + 2 mu)
+     */
+    pub fn lame(&self) -> (f64, f64) {
+        let (young, poisson) = (self.young, self.poisson);
+        let mu = young / (2.0 * (1.0 + poisson));
+        let lambda = match self.model {
+            PlaneModel::Strain => young * poisson / ((1.0 + poisson) * (1.0 - 2.0 * poisson)),
+            PlaneModel::Stress => young * poisson / (1.0 - poisson * poisson),
+        };
+        return (lambda, mu);
+    }
+}
+
+/**
+ * Boundary conditions of each displacement component, built as the
+ * dirichlet and neumann constraints of the domain: values at both edge ends.
+ *  Each component is constrained independently, so that prescribing only
+ *  the normal displacement gives a roller. Edges without constraints are
+ *  traction free.
+ */
+#[derive(Default)]
+pub struct ElasticConstraints {
+    pub displacement_x: Vec<Rc<BoundaryConstraint>>,
+    pub displacement_y: Vec<Rc<BoundaryConstraint>>,
+    pub traction_x: Vec<Rc<BoundaryConstraint>>,
+    pub traction_y: Vec<Rc<BoundaryConstraint>>,
+}
+
+impl ElasticConstraints {
+    pub fn new() -> Self {
+        ElasticConstraints::default()
+    }
+
+    /**
+     * Prescribes both displacement components, values given as (ux, uy)
+     */
+    pub fn insert_displacement(
+        &mut self,
+        domain: &Domain,
+        edge: &Rc<Edge>,
+        values: Vec<(f64, f64)>,
+    ) {
+        self.insert_displacement_x(domain, edge, values.iter().map(|value| value.0).collect());
+        self.insert_displacement_y(domain, edge, values.iter().map(|value| value.1).collect());
+    }
+
+    pub fn insert_displacement_x(&mut self, domain: &Domain, edge: &Rc<Edge>, values: Vec<f64>) {
+        self.displacement_x.push(constraint(domain, edge, values));
+    }
+
+    pub fn insert_displacement_y(&mut self, domain: &Domain, edge: &Rc<Edge>, values: Vec<f64>) {
+        self.displacement_y.push(constraint(domain, edge, values));
+    }
+
+    /**
+     * Prescribes the traction sigma n, values given as (tx, ty)
+     */
+    pub fn insert_traction(&mut self, domain: &Domain, edge: &Rc<Edge>, values: Vec<(f64, f64)>) {
+        self.traction_x.push(constraint(
+            domain,
+            edge,
+            values.iter().map(|value| value.0).collect(),
+        ));
+        self.traction_y.push(constraint(
+            domain,
+            edge,
+            values.iter().map(|value| value.1).collect(),
+        ));
+    }
+}
+
+fn constraint(domain: &Domain, edge: &Rc<Edge>, values: Vec<f64>) -> Rc<BoundaryConstraint> {
+    let triangle = domain
+        .adjacency
+        .get(edge)
+        .expect("Not expected constraint at edge outside the domain");
+    let mut values_mapping: HashMap<Rc<Point>, f64> = HashMap::new();
+    values_mapping.insert(Rc::clone(&edge.p1), values[0]);
+    values_mapping.insert(Rc::clone(&edge.p2), values[1]);
+
+    return Rc::new(BoundaryConstraint {
+        element: Rc::clone(triangle),
+        boundary_edge: Rc::clone(edge),
+        values: values_mapping,
+    });
+}
+
+/**
+ * Plane linear elasticity: - div(sigma(u)) = 0, sigma = 2 mu eps(u) + lambda tr(eps(u)) I,
+ * by symmetric interior penalty. The displacement takes the degrees of freedom
+ * of the domain twice, [ux, uy], and the bilinear form reads
+ *      integral of sigma(u) : eps(v)
+ *      - integral over inner edges of {sigma(u) n} . [v] + {sigma(v) n} . [u]
+ *      + sigma (lambda + 2 mu) max(p, 1)^2 / |e| [u] . [v]
+ *  Prescribed displacements enter by the same terms, component by component,
+ *  with the jump taken against the prescribed values.
+ */
+pub struct ElasticityProblem {
+    pub material: Material,
+    pub system_matrix: DMatrix<f64>,
+    pub extern_matrix: DMatrix<f64>,
+}
+
+/**
+ * Coefficients of the displacement components on the domain
+ */
+pub struct ElasticSolution {
+    pub displacement_x: DMatrix<f64>,
+    pub displacement_y: DMatrix<f64>,
+}
+
+impl ElasticityProblem {
+    pub fn new(
+        material: Material,
+        sigma: f64,
+        domain: &Domain,
+        constraints: &ElasticConstraints,
+    ) -> Self {
+        let lame = material.lame();
+        let n = domain.size();
+        let mut system_matrix = DMatrix::<f64>::zeros(2 * n, 2 * n);
+        let mut extern_matrix = DMatrix::<f64>::zeros(2 * n, 1);
+
+        for element in domain.elements.iter() {
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let indexes = vector_indexes(domain, element);
+            let basis = domain.element_basis(element);
+
+            assembler_utils::map(
+                &mut system_matrix,
+                &elasticity::stiffness_basis(p1, p2, p3, &basis, lame),
+                &assembler_utils::block_map(&indexes, &indexes),
+            );
+
+            let (e1, e2, e3) = element.inner_edges();
+            for edge in [e1, e2, e3].iter() {
+                let t_right = match domain.adjacency.get(&edge.opposed()) {
+                    Some(t_right) => t_right,
+                    None => continue,
+                };
+                let (p4, p5, p6) = (&t_right.p1, &t_right.p2, &t_right.p3);
+                let right_indexes = vector_indexes(domain, t_right);
+                let right_basis = domain.element_basis(t_right);
+
+                let edge_index = element.edge_index(edge).unwrap();
+                let right_edge_index = t_right.edge_index(&edge.opposed()).unwrap();
+                let penalty =
+                    edge_penalty(sigma, lame, &basis, &right_basis, p1, p2, p3, edge_index);
+
+                /* Each side takes the rows of its own test functions */
+                let left_left = elasticity::traction_basis(
+                    p1, p2, p3, &basis, p1, p2, p3, &basis, edge_index, lame,
+                );
+                let left_right = elasticity::traction_basis(
+                    p1,
+                    p2,
+                    p3,
+                    &basis,
+                    p4,
+                    p5,
+                    p6,
+                    &right_basis,
+                    edge_index,
+                    lame,
+                );
+                let right_left = elasticity::traction_basis(
+                    p4,
+                    p5,
+                    p6,
+                    &right_basis,
+                    p1,
+                    p2,
+                    p3,
+                    &basis,
+                    right_edge_index,
+                    lame,
+                );
+
+                assembler_utils::map(
+                    &mut system_matrix,
+                    &(-0.5 * (&left_left + left_left.transpose())
+                        + penalty
+                            * both_components(&interface_penalty::bilinear_penalty_basis(
+                                p1, p2, p3, &basis, p1, p2, p3, &basis, edge_index,
+                            ))),
+                    &assembler_utils::block_map(&indexes, &indexes),
+                );
+                assembler_utils::map(
+                    &mut system_matrix,
+                    &(-0.5 * (left_right + right_left.transpose())
+                        - penalty
+                            * both_components(&interface_penalty::bilinear_penalty_basis(
+                                p1,
+                                p2,
+                                p3,
+                                &basis,
+                                p4,
+                                p5,
+                                p6,
+                                &right_basis,
+                                edge_index,
+                            ))),
+                    &assembler_utils::block_map(&indexes, &right_indexes),
+                );
+            }
+        }
+
+        let displacements = [&constraints.displacement_x, &constraints.displacement_y];
+        for (component, component_constraints) in displacements.iter().enumerate() {
+            for d_constraint in component_constraints.iter() {
+                let element = &d_constraint.element;
+                let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+                let indexes = vector_indexes(domain, element);
+                let basis = domain.element_basis(element);
+                let size = basis.size();
+
+                let edge_index = element.edge_index(&d_constraint.boundary_edge).unwrap();
+                let penalty = edge_penalty(sigma, lame, &basis, &basis, p1, p2, p3, edge_index);
+                let value = |point| *d_constraint.values.get(point).unwrap_or(&0.0);
+                let values = (value(p1), value(p2), value(p3));
+                let zeros = (0.0, 0.0, 0.0);
+
+                /* Only the rows of the constrained component */
+                let mut traction = elasticity::traction_basis(
+                    p1, p2, p3, &basis, p1, p2, p3, &basis, edge_index, lame,
+                );
+                traction
+                    .slice_mut(((1 - component) * size, 0), (size, 2 * size))
+                    .fill(0.0);
+                let bilinear = dirichlet_constraint::dirichlet_bilinear_penalty_basis(
+                    p1, p2, p3, &basis, edge_index,
+                );
+                assembler_utils::map(
+                    &mut system_matrix,
+                    &(-(&traction + traction.transpose())
+                        + penalty * component_block(&bilinear, component)),
+                    &assembler_utils::block_map(&indexes, &indexes),
+                );
+
+                let (gx, gy) = match component {
+                    0 => (values, zeros),
+                    _ => (zeros, values),
+                };
+                let linear = dirichlet_constraint::dirichlet_linear_penalty_basis(
+                    p1, p2, p3, &basis, values.0, values.1, values.2, edge_index,
+                );
+                let mut rhs = -elasticity::traction_linear_basis(
+                    p1, p2, p3, &basis, gx, gy, edge_index, lame,
+                );
+                for i in 0..size {
+                    rhs[component * size + i] += penalty * linear[i];
+                }
+                assembler_utils::map(
+                    &mut extern_matrix,
+                    &DMatrix::from_column_slice(2 * size, 1, rhs.as_slice()),
+                    &assembler_utils::column_map(&indexes),
+                );
+            }
+        }
+
+        let tractions = [&constraints.traction_x, &constraints.traction_y];
+        for (component, component_constraints) in tractions.iter().enumerate() {
+            for n_constraint in component_constraints.iter() {
+                let element = &n_constraint.element;
+                let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+                let indexes = vector_indexes(domain, element);
+                let basis = domain.element_basis(element);
+                let size = basis.size();
+
+                let edge_index = element.edge_index(&n_constraint.boundary_edge).unwrap();
+                let value = |point| *n_constraint.values.get(point).unwrap_or(&0.0);
+                let load = neumann_constraint::neumann_basis(
+                    p1,
+                    p2,
+                    p3,
+                    &basis,
+                    value(p1),
+                    value(p2),
+                    value(p3),
+                    edge_index,
+                );
+                let mut rhs = DMatrix::<f64>::zeros(2 * size, 1);
+                rhs.slice_mut((component * size, 0), (size, 1))
+                    .copy_from(&load);
+                assembler_utils::map(
+                    &mut extern_matrix,
+                    &rhs,
+                    &assembler_utils::column_map(&indexes),
+                );
+            }
+        }
+
+        return ElasticityProblem {
+            material,
+            system_matrix,
+            extern_matrix,
+        };
+    }
+
+    pub fn solve(&self) -> ElasticSolution {
+        let solution = self
+            .system_matrix
+            .clone()
+            .cholesky()
+            .expect("Not expected elasticity system without positive definiteness")
+            .solve(&self.extern_matrix);
+        let n = solution.nrows() / 2;
+        return ElasticSolution {
+            displacement_x: solution.slice((0, 0), (n, 1)).clone_owned(),
+            displacement_y: solution.slice((n, 0), (n, 1)).clone_owned(),
+        };
+    }
+
+    /**
+     * Mean stress (sigma_xx, sigma_yy, sigma_xy) of each element, in the order of the domain
+     */
+    pub fn stresses(&self, domain: &Domain, solution: &ElasticSolution) -> Vec<Matrix3x1<f64>> {
+        let lame = self.material.lame();
+        return domain
+            .elements
+            .iter()
+            .map(|element| {
+                let indexes = domain.element_indexes(element);
+                let coefficients = DVector::from_iterator(
+                    2 * indexes.len(),
+                    indexes
+                        .iter()
+                        .map(|index| solution.displacement_x[(*index, 0)])
+                        .chain(
+                            indexes
+                                .iter()
+                                .map(|index| solution.displacement_y[(*index, 0)]),
+                        ),
+                );
+                elasticity::mean_stress(
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &domain.element_basis(element),
+                    &coefficients,
+                    lame,
+                )
+            })
+            .collect();
+    }
+}
+
+/**
+ * Degrees of freedom of both components at the element: x ones, then y ones
+ */
+fn vector_indexes(domain: &Domain, element: &Rc<TriangleElementL1>) -> Vec<usize> {
+    let indexes = domain.element_indexes(element);
+    let n = domain.size();
+    return indexes
+        .iter()
+        .cloned()
+        .chain(indexes.iter().map(|index| index + n))
+        .collect();
+}
+
+/**
+ * Scalar block placed at the given displacement component
+ */
+fn component_block(block: &DMatrix<f64>, component: usize) -> DMatrix<f64> {
+    let (rows, cols) = block.shape();
+    let mut matrix = DMatrix::<f64>::zeros(2 * rows, 2 * cols);
+    matrix
+        .slice_mut((component * rows, component * cols), (rows, cols))
+        .copy_from(block);
+    return matrix;
+}
+
+fn both_components(block: &DMatrix<f64>) -> DMatrix<f64> {
+    return component_block(block, 0) + component_block(block, 1);
+}
+
+/**
+ * Penalty sigma (lambda + 2 mu) max(p, 1)^2 / |e|, p the greatest degree of both sides
+ */
+#[allow(clippy::too_many_arguments)]
+fn edge_penalty(
+    sigma: f64,
+    (lambda, mu): (f64, f64),
+    left: &Basis,
+    right: &Basis,
+    p1: &Point,
+    p2: &Point,
+    p3: &Point,
+    edge_index: usize,
+) -> f64 {
+    let degree = left.degree().max(right.degree()).max(1) as f64;
+    return sigma * (lambda + 2.0 * mu) * degree.powi(2)
+        / utils::edge_length(p1, p2, p3, edge_index);
+}
+
+#[cfg(test)]
+mod linear_elasticity {
+    use super::*;
+    use crate::triangle::{basis::dubiner, mesh};
+
+    /**
+     * Displacement at the vertices of every element
+     */
+    fn vertex_displacements(
+        domain: &Domain,
+        solution: &ElasticSolution,
+    ) -> Vec<(Rc<Point>, (f64, f64))> {
+        let mut values = Vec::new();
+        for element in domain.elements.iter() {
+            let indexes = domain.element_indexes(element);
+            let component = |coefficients: &DMatrix<f64>| {
+                let local = DVector::from_iterator(
+                    indexes.len(),
+                    indexes.iter().map(|index| coefficients[(*index, 0)]),
+                );
+                match domain.element_basis(element) {
+                    Basis::Nodal => (local[0], local[1], local[2]),
+                    Basis::Modal(degree) => dubiner::vertex_values(degree, &local),
+                }
+            };
+            let ux = component(&solution.displacement_x);
+            let uy = component(&solution.displacement_y);
+            values.push((Rc::clone(&element.p1), (ux.0, uy.0)));
+            values.push((Rc::clone(&element.p2), (ux.1, uy.1)));
+            values.push((Rc::clone(&element.p3), (ux.2, uy.2)));
+        }
+        return values;
+    }
+
+    #[test]
+    fn sample_1() {
+        /*
+            Uniaxial tension of the unit square: rollers at left and bottom,
+            unit traction at right, top free. Stress is sigma_xx = 1 everywhere,
+            and the linear displacement is recovered exactly
+        */
+        for model in [PlaneModel::Stress, PlaneModel::Strain].iter() {
+            for degree in [None, Some(2)].iter() {
+                let mut domain = match degree {
+                    None => Domain::new_empty(),
+                    Some(degree) => Domain::new_modal(*degree).unwrap(),
+                };
+                let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 3, 3, &mut domain);
+                let mut constraints = ElasticConstraints::new();
+                for edge in boundary.left.iter() {
+                    constraints.insert_displacement_x(&domain, edge, vec![0.0, 0.0]);
+                }
+                for edge in boundary.bottom.iter() {
+                    constraints.insert_displacement_y(&domain, edge, vec![0.0, 0.0]);
+                }
+                for edge in boundary.right.iter() {
+                    constraints.insert_traction(&domain, edge, vec![(1.0, 0.0), (1.0, 0.0)]);
+                }
+
+                let (young, poisson) = (200.0, 0.3);
+                let material = Material::new(young, poisson, *model);
+                let problem = ElasticityProblem::new(material, 10.0, &domain, &constraints);
+                assert!(
+                    (&problem.system_matrix - problem.system_matrix.transpose()).norm()
+                        < 1.0E-14 * problem.system_matrix.norm()
+                );
+                let solution = problem.solve();
+
+                /* Plane strain takes the effective modulus E / (1 - nu^2) and ratio nu / (1 - nu) */
+                let (young, poisson) = match model {
+                    PlaneModel::Stress => (young, poisson),
+                    PlaneModel::Strain => {
+                        (young / (1.0 - poisson * poisson), poisson / (1.0 - poisson))
+                    }
+                };
+                for (point, (ux, uy)) in vertex_displacements(&domain, &solution) {
+                    assert!((ux - point.x / young).abs() < 1.0E-10);
+                    assert!((uy + poisson * point.y / young).abs() < 1.0E-10);
+                }
+                for stress in problem.stresses(&domain, &solution) {
+                    assert!((stress - Matrix3x1::new(1.0, 0.0, 0.0)).norm() < 1.0E-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Pure shear u = g (y, x) prescribed at the whole boundary:
+            sigma_xy = 2 mu g, recovered exactly on a skewed mesh
+        */
+        let mut domain = Domain::new_empty();
+        let boundary = mesh::rectangle((-1.0, 0.0), (2.0, 1.0), 4, 2, &mut domain);
+        let shear = 0.01;
+        let mut constraints = ElasticConstraints::new();
+        for edges in [
+            &boundary.left,
+            &boundary.right,
+            &boundary.bottom,
+            &boundary.top,
+        ]
+        .iter()
+        {
+            for edge in edges.iter() {
+                let value = |point: &Point| (shear * point.y, shear * point.x);
+                constraints.insert_displacement(
+                    &domain,
+                    edge,
+                    vec![value(&edge.p1), value(&edge.p2)],
+                );
+            }
+        }
+
+        let problem = ElasticityProblem::new(
+            Material::new(3.0, 0.25, PlaneModel::Strain),
+            10.0,
+            &domain,
+            &constraints,
+        );
+        let solution = problem.solve();
+        let (_, mu) = problem.material.lame();
+        for stress in problem.stresses(&domain, &solution) {
+            assert!((stress - Matrix3x1::new(0.0, 0.0, 2.0 * mu * shear)).norm() < 1.0E-12);
+        }
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            Cantilever clamped at x = 0, loaded by a downward shear traction
+            at its tip: tip deflection close to Timoshenko beam theory,
+            P L^3 / (3 E I) + P L / (k G A)
+        */
+        let (length, height) = (8.0, 1.0);
+        let mut domain = Domain::new_modal(2).unwrap();
+        let boundary = mesh::rectangle(
+            (0.0, -height / 2.0),
+            (length, height / 2.0),
+            16,
+            2,
+            &mut domain,
+        );
+        let mut constraints = ElasticConstraints::new();
+        for edge in boundary.left.iter() {
+            constraints.insert_displacement(&domain, edge, vec![(0.0, 0.0), (0.0, 0.0)]);
+        }
+        let load = 1.0E-3;
+        for edge in boundary.right.iter() {
+            constraints.insert_traction(&domain, edge, vec![(0.0, -load), (0.0, -load)]);
+        }
+
+        let (young, poisson) = (1.0, 0.3);
+        let problem = ElasticityProblem::new(
+            Material::new(young, poisson, PlaneModel::Stress),
+            10.0,
+            &domain,
+            &constraints,
+        );
+        let solution = problem.solve();
+
+        let force = load * height;
+        let inertia = height.powi(3) / 12.0;
+        let shear_modulus = young / (2.0 * (1.0 + poisson));
+        let expected = force * length.powi(3) / (3.0 * young * inertia)
+            + force * length / (5.0 / 6.0 * shear_modulus * height);
+        let deflection = vertex_displacements(&domain, &solution)
+            .iter()
+            .filter(|(point, _)| (point.x - length).abs() < 1.0E-12 && point.y.abs() < 1.0E-12)
+            .map(|(_, (_, uy))| -uy)
+            .fold(0.0, f64::max);
+        assert!((deflection - expected).abs() < 0.05 * expected);
+    }
+}
@@ -1,22 +1,170 @@
 use nalgebra::DMatrix;
 
+use crate::common::{error::Error, point::Point};
 use crate::triangle::{
-    integrands::{dirichlet_constraint, flux_artificial, flux_natural},
+    boundary_constraint::BoundaryConstraint,
+    integrands::{dirichlet_constraint, flux_artificial, flux_natural, utils},
     system_builder::{assembler_utils, domain::Domain},
 };
 
 use std::rc::Rc;
 
 /**
- *  Fills both matrices
+ *  Fills both matrices with weakly imposed dirichlet values g:
+ *      - grad(u) . n v - grad(v) . n u + sigma max(p, 1)^2 / |e| u v
+ *      = - grad(v) . n g + sigma max(p, 1)^2 / |e| g v
  */
 pub fn build(
     system_matrix: &mut DMatrix<f64>, /* NxN matrix */
     extern_matrix: &mut DMatrix<f64>, /* Nx1 matrix */
     sigma: f64,
     domain: &Domain,
-) -> Result<(), ()> {
+) -> Result<(), Error> {
+    build_matrix(system_matrix, sigma, domain, &domain.dirichlet_constraints)?;
+    build_extern(extern_matrix, sigma, domain, &domain.dirichlet_constraints)?;
+    return Ok(());
+}
+
+/**
+ *  Fills both matrices with the penalty of dirichlet values alone:
+ *      sigma max(p, 1)^2 / |e| u v = sigma max(p, 1)^2 / |e| g v
+ *  as the local discontinuous Galerkin flux takes the natural terms elsewhere
+ */
+pub fn build_penalty(
+    system_matrix: &mut DMatrix<f64>, /* NxN matrix */
+    extern_matrix: &mut DMatrix<f64>, /* Nx1 matrix */
+    sigma: f64,
+    domain: &Domain,
+) -> Result<(), Error> {
     for d_constraint in domain.dirichlet_constraints.iter() {
+        let element = Rc::clone(&d_constraint.element);
+        let value = |point| *d_constraint.values.get(point).unwrap_or(&0.0);
+
+        let indexes = domain.element_indexes(&element);
+        let basis = domain.element_basis(&element);
+        let size = basis.size();
+
+        let edge_index = element.edge_index(&d_constraint.boundary_edge).unwrap();
+        let penalty = sigma * (basis.degree().max(1) as f64).powi(2)
+            / utils::edge_length(&element.p1, &element.p2, &element.p3, edge_index);
+
+        assembler_utils::map(
+            system_matrix,
+            &(penalty
+                * dirichlet_constraint::dirichlet_bilinear_penalty_basis(
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &basis,
+                    edge_index,
+                )),
+            &assembler_utils::block_map(&indexes, &indexes),
+        );
+        assembler_utils::map(
+            extern_matrix,
+            &(penalty
+                * dirichlet_constraint::dirichlet_linear_penalty_basis(
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &basis,
+                    value(&element.p1),
+                    value(&element.p2),
+                    value(&element.p3),
+                    edge_index,
+                ))
+            .slice((0, 0), (size, 1))
+            .clone_owned(),
+            &assembler_utils::column_map(&indexes),
+        );
+    }
+    return Ok(());
+}
+
+/**
+ * Left hand side of the dirichlet constraints, which does not depend on values
+ */
+pub fn build_matrix(
+    system_matrix: &mut DMatrix<f64>, /* NxN matrix */
+    sigma: f64,
+    domain: &Domain,
+    constraints: &[Rc<BoundaryConstraint>],
+) -> Result<(), Error> {
+    for d_constraint in constraints.iter() {
+        let element = Rc::clone(&d_constraint.element);
+        let inner_edge = Rc::clone(&d_constraint.boundary_edge);
+
+        let indexes = domain.element_indexes(&element);
+        let basis = domain.element_basis(&element);
+
+        let edge_index = element.edge_index(&inner_edge).unwrap();
+        let penalty = sigma * (basis.degree().max(1) as f64).powi(2)
+            / utils::edge_length(&element.p1, &element.p2, &element.p3, edge_index);
+
+        /* Natural Flux */
+        assembler_utils::map(
+            system_matrix,
+            &(-2.0
+                * flux_natural::half_flux_basis(
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &basis,
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &basis,
+                    edge_index,
+                )),
+            &assembler_utils::block_map(&indexes, &indexes),
+        );
+
+        /* Artificial flux */
+        assembler_utils::map(
+            system_matrix,
+            &(-2.0
+                * flux_artificial::half_flux_basis(
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &basis,
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &basis,
+                    edge_index,
+                )),
+            &assembler_utils::block_map(&indexes, &indexes),
+        );
+
+        /* Bilinear Penalty */
+        assembler_utils::map(
+            system_matrix,
+            &(penalty
+                * dirichlet_constraint::dirichlet_bilinear_penalty_basis(
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &basis,
+                    edge_index,
+                )),
+            &assembler_utils::block_map(&indexes, &indexes),
+        );
+    }
+
+    return Ok(());
+}
+
+/**
+ * Right hand side of the dirichlet constraints, given their values
+ */
+pub fn build_extern(
+    extern_matrix: &mut DMatrix<f64>, /* Nx1 matrix */
+    sigma: f64,
+    domain: &Domain,
+    constraints: &[Rc<BoundaryConstraint>],
+) -> Result<(), Error> {
+    for d_constraint in constraints.iter() {
         let element = Rc::clone(&d_constraint.element);
         let inner_edge = Rc::clone(&d_constraint.boundary_edge);
 
@@ -33,103 +181,108 @@ pub fn build(
             None => 0.0,
         };
 
-        let global_p1 = *domain
-            .index_mapping
-            .get(&(Rc::clone(&element), Rc::clone(&element.p1)))
-            .unwrap();
-
-        let global_p2 = *domain
-            .index_mapping
-            .get(&(Rc::clone(&element), Rc::clone(&element.p2)))
-            .unwrap();
+        let indexes = domain.element_indexes(&element);
+        let basis = domain.element_basis(&element);
+        let size = basis.size();
 
-        let global_p3 = *domain
-            .index_mapping
-            .get(&(Rc::clone(&element), Rc::clone(&element.p3)))
-            .unwrap();
+        let edge_index = element.edge_index(&inner_edge).unwrap();
+        let penalty = sigma * (basis.degree().max(1) as f64).powi(2)
+            / utils::edge_length(&element.p1, &element.p2, &element.p3, edge_index);
 
-        /* Natural Flux */
-        assembler_utils::map(
-            system_matrix,
-            &flux_natural::half_flux(
-                &element.p1,
-                &element.p2,
-                &element.p3,
-                &element.p1,
-                &element.p2,
-                &element.p3,
-                element.edge_index(&inner_edge).unwrap(),
-            )
-            .slice((0, 0), (3, 3))
-            .clone_owned(),
-            &assembler_utils::square_map(global_p1, global_p2, global_p3),
-        );
-
-        /* Artificial flux */
+        /* Linear Natural */
         assembler_utils::map(
-            system_matrix,
-            &(-flux_artificial::half_flux(
-                &element.p1,
-                &element.p2,
-                &element.p3,
+            extern_matrix,
+            &(-dirichlet_constraint::dirichlet_linear_natural_basis(
                 &element.p1,
                 &element.p2,
                 &element.p3,
-                element.edge_index(&inner_edge).unwrap(),
+                &basis,
+                u1,
+                u2,
+                u3,
+                edge_index,
             ))
-            .slice((0, 0), (3, 3))
+            .slice((0, 0), (size, 1))
             .clone_owned(),
-            &assembler_utils::square_map(global_p1, global_p2, global_p3),
+            &assembler_utils::column_map(&indexes),
         );
 
-        /* Bilinear Penalty */
+        /* Linear Penalty */
         assembler_utils::map(
-            system_matrix,
-            &(sigma
-                * dirichlet_constraint::dirichlet_bilinear_penalty(
+            extern_matrix,
+            &(penalty
+                * dirichlet_constraint::dirichlet_linear_penalty_basis(
                     &element.p1,
                     &element.p2,
                     &element.p3,
-                    element.edge_index(&inner_edge).unwrap(),
+                    &basis,
+                    u1,
+                    u2,
+                    u3,
+                    edge_index,
                 ))
-            .slice((0, 0), (3, 3))
+            .slice((0, 0), (size, 1))
             .clone_owned(),
-            &assembler_utils::square_map(global_p1, global_p2, global_p3),
+            &assembler_utils::column_map(&indexes),
         );
+    }
 
-        /* Linear Natural */
+    return Ok(());
+}
+
+/**
+ * Right hand side of the dirichlet constraints, with values given as a function g
+ * integrated at the edge quadrature points: the constraint values are not used
+ */
+pub fn build_extern_function(
+    extern_matrix: &mut DMatrix<f64>, /* Nx1 matrix */
+    sigma: f64,
+    domain: &Domain,
+    constraints: &[Rc<BoundaryConstraint>],
+    value: &dyn Fn(&Point) -> f64,
+) -> Result<(), Error> {
+    for d_constraint in constraints.iter() {
+        let element = Rc::clone(&d_constraint.element);
+
+        let indexes = domain.element_indexes(&element);
+        let basis = domain.element_basis(&element);
+        let size = basis.size();
+
+        let edge_index = element.edge_index(&d_constraint.boundary_edge)?;
+        let penalty = sigma * (basis.degree().max(1) as f64).powi(2)
+            / utils::edge_length(&element.p1, &element.p2, &element.p3, edge_index);
+
+        /* Function Natural */
         assembler_utils::map(
             extern_matrix,
-            &dirichlet_constraint::dirichlet_linear_natural(
+            &(-dirichlet_constraint::dirichlet_function_natural_basis(
                 &element.p1,
                 &element.p2,
                 &element.p3,
-                u1,
-                u2,
-                u3,
-                element.edge_index(&inner_edge).unwrap(),
-            )
-            .slice((0, 0), (3, 1))
+                &basis,
+                value,
+                edge_index,
+            ))
+            .slice((0, 0), (size, 1))
             .clone_owned(),
-            &assembler_utils::linear_map(global_p1, global_p2, global_p3),
+            &assembler_utils::column_map(&indexes),
         );
 
-        /* Linear Penalty */
+        /* Function Penalty */
         assembler_utils::map(
             extern_matrix,
-            &(sigma
-                * dirichlet_constraint::dirichlet_linear_penalty(
+            &(penalty
+                * dirichlet_constraint::dirichlet_function_penalty_basis(
                     &element.p1,
                     &element.p2,
                     &element.p3,
-                    u1,
-                    u2,
-                    u3,
-                    element.edge_index(&inner_edge).unwrap(),
+                    &basis,
+                    value,
+                    edge_index,
                 ))
-            .slice((0, 0), (3, 1))
+            .slice((0, 0), (size, 1))
             .clone_owned(),
-            &assembler_utils::linear_map(global_p1, global_p2, global_p3),
+            &assembler_utils::column_map(&indexes),
         );
     }
 
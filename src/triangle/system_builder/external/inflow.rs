@@ -0,0 +1,91 @@
+use nalgebra::DMatrix;
+
+use crate::common::error::Error;
+use crate::triangle::{
+    integrands::advection::{self, Velocity},
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+use std::rc::Rc;
+
+/**
+ *  Fills both matrices with the upwind flux at boundary edges:
+ *      max(b . n, 0) u v = - min(b . n, 0) g v
+ *  Inflow takes the dirichlet values g. Boundary edges without
+ *  dirichlet values are taken as outflow, so their inflow carries zero
+ */
+pub fn build(
+    system_matrix: &mut DMatrix<f64>, /* NxN matrix */
+    extern_matrix: &mut DMatrix<f64>, /* Nx1 matrix */
+    velocity: Velocity,
+    domain: &Domain,
+) -> Result<(), Error> {
+    for d_constraint in domain.dirichlet_constraints.iter() {
+        let element = Rc::clone(&d_constraint.element);
+        let inner_edge = Rc::clone(&d_constraint.boundary_edge);
+
+        let u1: f64 = match d_constraint.values.get(&element.p1) {
+            Some(value) => *value,
+            None => 0.0,
+        };
+        let u2: f64 = match d_constraint.values.get(&element.p2) {
+            Some(value) => *value,
+            None => 0.0,
+        };
+        let u3: f64 = match d_constraint.values.get(&element.p3) {
+            Some(value) => *value,
+            None => 0.0,
+        };
+
+        let indexes = domain.element_indexes(&element);
+        let basis = domain.element_basis(&element);
+        let edge_index = element.edge_index(&inner_edge).unwrap();
+
+        /* Inflow */
+        assembler_utils::map(
+            extern_matrix,
+            &advection::inflow_basis(
+                &element.p1,
+                &element.p2,
+                &element.p3,
+                &basis,
+                u1,
+                u2,
+                u3,
+                edge_index,
+                velocity,
+            )
+            .slice((0, 0), (basis.size(), 1))
+            .clone_owned(),
+            &assembler_utils::column_map(&indexes),
+        );
+    }
+
+    /* Outflow at every boundary edge */
+    for element in domain.elements.iter() {
+        let (e1, e2, e3) = element.inner_edges();
+        for edge in [e1, e2, e3].iter() {
+            if domain.adjacency.contains_key(&edge.opposed()) {
+                continue;
+            }
+            let indexes = domain.element_indexes(element);
+            let basis = domain.element_basis(element);
+            let edge_index = element.edge_index(edge).unwrap();
+
+            assembler_utils::map(
+                system_matrix,
+                &advection::outflow_basis(
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &basis,
+                    edge_index,
+                    velocity,
+                ),
+                &assembler_utils::block_map(&indexes, &indexes),
+            );
+        } /* end - for edge in triangle */
+    } /* end - for element in domain */
+
+    return Ok(());
+}
@@ -1,5 +1,6 @@
 use nalgebra::DMatrix;
 
+use crate::common::error::Error;
 use crate::triangle::{
     integrands::neumann_constraint,
     system_builder::{assembler_utils, domain::Domain},
@@ -10,7 +11,7 @@ use std::rc::Rc;
 /**
  * Fills the system matrix with mass matrix according to each element
  */
-pub fn build(b_matrix: &mut DMatrix<f64>, domain: &Domain) -> Result<(), ()> {
+pub fn build(b_matrix: &mut DMatrix<f64>, domain: &Domain) -> Result<(), Error> {
     for d_constraint in domain.neumann_constraints.iter() {
         let element = Rc::clone(&d_constraint.element);
         let inner_edge = Rc::clone(&d_constraint.boundary_edge);
@@ -28,35 +29,24 @@ pub fn build(b_matrix: &mut DMatrix<f64>, domain: &Domain) -> Result<(), ()> {
             None => 0.0,
         };
 
-        let global_p1 = *domain
-            .index_mapping
-            .get(&(Rc::clone(&element), Rc::clone(&element.p1)))
-            .unwrap();
-
-        let global_p2 = *domain
-            .index_mapping
-            .get(&(Rc::clone(&element), Rc::clone(&element.p2)))
-            .unwrap();
-
-        let global_p3 = *domain
-            .index_mapping
-            .get(&(Rc::clone(&element), Rc::clone(&element.p3)))
-            .unwrap();
+        let indexes = domain.element_indexes(&element);
+        let basis = domain.element_basis(&element);
 
         assembler_utils::map(
             b_matrix,
-            &neumann_constraint::neumann(
+            &neumann_constraint::neumann_basis(
                 &element.p1,
                 &element.p2,
                 &element.p3,
+                &basis,
                 u1,
                 u2,
                 u3,
                 element.edge_index(&inner_edge).unwrap(),
             )
-            .slice((0, 0), (3, 1))
+            .slice((0, 0), (basis.size(), 1))
             .clone_owned(),
-            &assembler_utils::linear_map(global_p1, global_p2, global_p3),
+            &assembler_utils::column_map(&indexes),
         );
     }
     return Ok(());
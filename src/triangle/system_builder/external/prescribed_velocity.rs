@@ -0,0 +1,67 @@
+use nalgebra::DMatrix;
+
+use crate::common::{error::Error, point::Point};
+use crate::triangle::{
+    boundary_constraint::BoundaryConstraint,
+    integrands::divergence,
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+use std::rc::Rc;
+
+/**
+ * Fills the divergence blocks and the pressure right hand side
+ * with the prescribed velocity g at the edges of the constraints:
+ *  q v . n = q g . n
+ *  g is integrated at the edge quadrature points, the constraint values are not used
+ */
+pub fn build(
+    bx: &mut DMatrix<f64>,            /* PxN matrix */
+    by: &mut DMatrix<f64>,            /* PxN matrix */
+    extern_matrix: &mut DMatrix<f64>, /* Px1 matrix */
+    velocity: &Domain,
+    pressure: &Domain,
+    constraints: &[Rc<BoundaryConstraint>],
+    boundary_velocity: &dyn Fn(&Point) -> (f64, f64),
+) -> Result<(), Error> {
+    for constraint in constraints.iter() {
+        let element = Rc::clone(&constraint.element);
+        let edge_index = element.edge_index(&constraint.boundary_edge)?;
+
+        let velocity_indexes = velocity.element_indexes(&element);
+        let pressure_indexes = pressure.element_indexes(&element);
+        let pressure_basis = pressure.element_basis(&element);
+
+        let (nx, ny) = divergence::normal_basis(
+            &element.p1,
+            &element.p2,
+            &element.p3,
+            &pressure_basis,
+            &element.p1,
+            &element.p2,
+            &element.p3,
+            &velocity.element_basis(&element),
+            edge_index,
+        );
+        let map = assembler_utils::block_map(&pressure_indexes, &velocity_indexes);
+        assembler_utils::map(bx, &nx, &map);
+        assembler_utils::map(by, &ny, &map);
+
+        let integral = divergence::normal_function_basis(
+            &element.p1,
+            &element.p2,
+            &element.p3,
+            &pressure_basis,
+            boundary_velocity,
+            edge_index,
+        );
+        assembler_utils::map(
+            extern_matrix,
+            &integral
+                .slice((0, 0), (pressure_basis.size(), 1))
+                .clone_owned(),
+            &assembler_utils::column_map(&pressure_indexes),
+        );
+    }
+    return Ok(());
+}
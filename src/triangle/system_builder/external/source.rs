@@ -0,0 +1,45 @@
+use nalgebra::DMatrix;
+
+use crate::common::error::Error;
+use crate::common::point::Point;
+use crate::triangle::{
+    integrands::l2_mass,
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+/**
+ * Fills the extern matrix with the volumetric source of each element: f v
+ */
+pub fn build(
+    b_matrix: &mut DMatrix<f64>,
+    source: &dyn Fn(&Point) -> f64,
+    domain: &Domain,
+) -> Result<(), Error> {
+    for element in domain.elements.iter() {
+        let indexes = domain.element_indexes(element);
+        let basis = domain.element_basis(element);
+
+        assembler_utils::map(
+            b_matrix,
+            &l2_mass::source_basis(&element.p1, &element.p2, &element.p3, &basis, source),
+            &assembler_utils::column_map(&indexes),
+        );
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod load_vector {
+    use super::*;
+    use crate::triangle::mesh;
+
+    #[test]
+    fn sample_1() {
+        /* Nodal load of a linear source sums up to its integral: 1 + x over [0,2]x[0,1] */
+        let mut domain = Domain::new_empty();
+        mesh::rectangle((0.0, 0.0), (2.0, 1.0), 3, 2, &mut domain);
+        let mut load = DMatrix::<f64>::zeros(domain.size(), 1);
+        build(&mut load, &|point: &Point| 1.0 + point.x, &domain).unwrap();
+        assert!((load.sum() - 4.0).abs() < 1.0E-12);
+    }
+}
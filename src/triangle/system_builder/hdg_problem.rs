@@ -0,0 +1,569 @@
+use nalgebra::{DMatrix, DVector};
+
+use crate::common::{edge::Edge, error::Error};
+use crate::triangle::{
+    boundary_constraint::BoundaryConstraint,
+    element::TriangleElementL1,
+    integrands::utils,
+    quadrature::{dunavant, gauss_legendre},
+    system_builder::domain::Domain,
+};
+
+use std::collections::{hash_map::Entry, HashMap};
+use std::rc::Rc;
+
+/**
+ * Trace at a local edge degree of freedom
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Trace {
+    /* Index of the global unknown */
+    Unknown(usize),
+    /* Projection of the dirichlet value */
+    Known(f64),
+}
+
+struct LocalSolver {
+    indexes: Vec<usize>,
+    /* Inverse of the local system in (qx, qy, u) */
+    inverse: DMatrix<f64>,
+    /* Right hand side of the local system, by local trace */
+    lift: DMatrix<f64>,
+    traces: Vec<Trace>,
+}
+
+/**
+ * Hybridizable discontinuous Galerkin system for - laplacian(u) = 0,
+ * with the dirichlet and neumann constraints of Domain.
+ *  On each element, the flux q = - grad(u) and u solve
+ *      integral of q . r - u div(r) + integral over edges of l r . n = 0
+ *      integral of div(q) w + integral over edges of tau (u - l) w = 0
+ *  given the trace l, a polynomial of the greatest neighbour degree at each edge,
+ *  taken in Legendre polynomials along the edge. Interiors are eliminated,
+ *  and the global system enforces the continuity of the normal flux
+ *  q . n + tau (u - l) across inner edges, and its prescribed value at neumann
+ *  edges. Traces at dirichlet edges are projections of the prescribed values.
+ *  The global system is symmetric and positive definite.
+ *
+ *  Reference:
+ *      Cockburn, B.; Gopalakrishnan, J.; Lazarov, R. Unified hybridization of
+ *      discontinuous Galerkin, mixed, and continuous Galerkin methods for second
+ *      order elliptic problems. SINUM, 2009. Vol 47, 1319-1365.
+ */
+pub struct HdgProblem {
+    pub tau: f64,
+    pub system_matrix: DMatrix<f64>,
+    pub extern_matrix: DMatrix<f64>,
+
+    /* Same elements as the domain, one degree above, for post processing */
+    pub enriched: Domain,
+
+    elements: Vec<LocalSolver>,
+    size: usize,
+}
+
+/**
+ * Coefficients of u and of the flux components on Domain,
+ * and of the traces at the global unknowns
+ */
+pub struct HdgSolution {
+    pub u: DMatrix<f64>,
+    pub flux_x: DMatrix<f64>,
+    pub flux_y: DMatrix<f64>,
+    pub trace: DMatrix<f64>,
+}
+
+impl HdgProblem {
+    /**
+     * Fails if the enriched degree of an element is beyond basis::shape::MAX_MODAL_DEGREE
+     */
+    pub fn new(tau: f64, domain: &Domain) -> Result<Self, Error> {
+        let mut enriched = Domain::new_empty();
+        for element in domain.elements.iter() {
+            let degree = domain.element_basis(element).degree();
+            enriched.insert_element_with_degree(element, degree + 1)?;
+        }
+
+        let dirichlet: HashMap<Rc<Edge>, Rc<BoundaryConstraint>> = domain
+            .dirichlet_constraints
+            .iter()
+            .map(|constraint| (Rc::clone(&constraint.boundary_edge), Rc::clone(constraint)))
+            .collect();
+        let neumann: HashMap<Rc<Edge>, Rc<BoundaryConstraint>> = domain
+            .neumann_constraints
+            .iter()
+            .map(|constraint| (Rc::clone(&constraint.boundary_edge), Rc::clone(constraint)))
+            .collect();
+
+        /* Global unknowns of each edge, numbered as edges are met */
+        let mut unknowns: HashMap<Rc<Edge>, usize> = HashMap::new();
+        let mut size = 0;
+        for element in domain.elements.iter() {
+            let (e1, e2, e3) = element.inner_edges();
+            for edge in [e1, e2, e3].iter() {
+                if dirichlet.contains_key(edge) {
+                    continue;
+                }
+                if let Entry::Vacant(entry) = unknowns.entry(canonical(edge)) {
+                    entry.insert(size);
+                    size += edge_degree(domain, element, edge) + 1;
+                }
+            }
+        }
+
+        let mut system_matrix = DMatrix::<f64>::zeros(size, size);
+        let mut extern_matrix = DMatrix::<f64>::zeros(size, 1);
+        let mut elements: Vec<LocalSolver> = Vec::new();
+        for element in domain.elements.iter() {
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let basis = domain.element_basis(element);
+            let n = basis.size();
+            let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+
+            /* Mass and derivatives: dx_ij = integral of phi_j dphi_i/dx */
+            let mut mass = DMatrix::<f64>::zeros(n, n);
+            let mut dx = DMatrix::<f64>::zeros(n, n);
+            let mut dy = DMatrix::<f64>::zeros(n, n);
+            let rule = dunavant::rule((2 * basis.degree()).max(1));
+            for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+                let phi = basis.evaluate(*x, *y);
+                let gradient = basis.physical_gradient(p1, p2, p3, *x, *y);
+                mass += &phi * phi.transpose() * (weight * determinant);
+                dx += gradient.column(0) * phi.transpose() * (weight * determinant);
+                dy += gradient.column(1) * phi.transpose() * (weight * determinant);
+            }
+
+            /* Edge terms, by local trace */
+            let (e1, e2, e3) = element.inner_edges();
+            let mut traces: Vec<Trace> = Vec::new();
+            let mut edge_terms: Vec<(DMatrix<f64>, DMatrix<f64>, DMatrix<f64>)> = Vec::new();
+            let mut stabilization = DMatrix::<f64>::zeros(n, n);
+            let mut penalties: Vec<DMatrix<f64>> = Vec::new();
+            let mut neumann_values: Vec<DVector<f64>> = Vec::new();
+            for edge in [e1, e2, e3].iter() {
+                let edge_index = element.edge_index(edge).unwrap();
+                let degree = edge_degree(domain, element, edge);
+                let normal = utils::outer_normal(p1, p2, p3, edge_index);
+                let length = utils::edge_length(p1, p2, p3, edge_index);
+                let reversed = canonical(edge) != *edge;
+
+                let rule = gauss_legendre::rule(2 * basis.degree().max(degree) + 1);
+                let mut trace_x = DMatrix::<f64>::zeros(n, degree + 1);
+                let mut trace_y = DMatrix::<f64>::zeros(n, degree + 1);
+                let mut trace_u = DMatrix::<f64>::zeros(n, degree + 1);
+                let mut penalty = DMatrix::<f64>::zeros(degree + 1, degree + 1);
+                let mut flux = DVector::<f64>::zeros(degree + 1);
+                for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+                    let (x, y) = utils::edge_coordinates(edge_index, *l);
+                    let phi = basis.evaluate(x, y);
+                    let s = if reversed { 1.0 - l } else { *l };
+                    let mu = legendre(degree, s);
+                    let product = &phi * mu.transpose() * (weight * length);
+                    trace_x += &product * normal[0];
+                    trace_y += &product * normal[1];
+                    trace_u += &product * tau;
+                    stabilization += &phi * phi.transpose() * (tau * weight * length);
+                    penalty += &mu * mu.transpose() * (tau * weight * length);
+                    if let Some(constraint) = neumann.get(edge) {
+                        let g = linear_value(constraint, edge, *l);
+                        flux += &mu * (g * weight * length);
+                    }
+                }
+
+                match dirichlet.get(edge) {
+                    Some(constraint) => {
+                        let values = project(constraint, edge, degree, reversed);
+                        traces.extend(values.iter().map(|value| Trace::Known(*value)));
+                    }
+                    None => {
+                        let offset = *unknowns.get(&canonical(edge)).unwrap();
+                        traces.extend((0..=degree).map(|a| Trace::Unknown(offset + a)));
+                    }
+                }
+                edge_terms.push((trace_x, trace_y, trace_u));
+                penalties.push(penalty);
+                neumann_values.push(flux);
+            }
+
+            let mut local = DMatrix::<f64>::zeros(3 * n, 3 * n);
+            local.slice_mut((0, 0), (n, n)).copy_from(&mass);
+            local.slice_mut((n, n), (n, n)).copy_from(&mass);
+            local.slice_mut((0, 2 * n), (n, n)).copy_from(&(-&dx));
+            local.slice_mut((n, 2 * n), (n, n)).copy_from(&(-&dy));
+            local
+                .slice_mut((2 * n, 0), (n, n))
+                .copy_from(&dx.transpose());
+            local
+                .slice_mut((2 * n, n), (n, n))
+                .copy_from(&dy.transpose());
+            local
+                .slice_mut((2 * n, 2 * n), (n, n))
+                .copy_from(&stabilization);
+            let inverse = local
+                .try_inverse()
+                .expect("Failed to invert local hdg system");
+
+            /* Lift: right hand side of the local system; flux: normal flux by unknown */
+            let count = traces.len();
+            let mut lift = DMatrix::<f64>::zeros(3 * n, count);
+            let mut flux = DMatrix::<f64>::zeros(3 * n, count);
+            let mut penalty = DMatrix::<f64>::zeros(count, count);
+            let mut neumann_flux = DVector::<f64>::zeros(count);
+            let mut column = 0;
+            for (((trace_x, trace_y, trace_u), edge_penalty), values) in edge_terms
+                .iter()
+                .zip(penalties.iter())
+                .zip(neumann_values.iter())
+            {
+                let m = trace_u.ncols();
+                lift.slice_mut((0, column), (n, m)).copy_from(&(-trace_x));
+                lift.slice_mut((n, column), (n, m)).copy_from(&(-trace_y));
+                lift.slice_mut((2 * n, column), (n, m)).copy_from(trace_u);
+                flux.slice_mut((0, column), (n, m)).copy_from(trace_x);
+                flux.slice_mut((n, column), (n, m)).copy_from(trace_y);
+                flux.slice_mut((2 * n, column), (n, m)).copy_from(trace_u);
+                penalty
+                    .slice_mut((column, column), (m, m))
+                    .copy_from(edge_penalty);
+                neumann_flux.rows_mut(column, m).copy_from(values);
+                column += m;
+            }
+
+            /* Normal flux q . n + tau (u - l) against the edge basis, with a minus sign */
+            let condensed = penalty - flux.transpose() * &inverse * &lift;
+            for (a, row) in traces.iter().enumerate() {
+                if let Trace::Unknown(i) = row {
+                    extern_matrix[(*i, 0)] += neumann_flux[a];
+                    for (b, col) in traces.iter().enumerate() {
+                        match col {
+                            Trace::Unknown(j) => system_matrix[(*i, *j)] += condensed[(a, b)],
+                            Trace::Known(value) => {
+                                extern_matrix[(*i, 0)] -= condensed[(a, b)] * value
+                            }
+                        }
+                    }
+                }
+            }
+
+            elements.push(LocalSolver {
+                indexes: domain.element_indexes(element),
+                inverse,
+                lift,
+                traces,
+            });
+        }
+
+        return Ok(HdgProblem {
+            tau,
+            system_matrix,
+            extern_matrix,
+            enriched,
+            elements,
+            size,
+        });
+    }
+
+    /**
+     * Number of global unknowns, the traces at edges without dirichlet constraints
+     */
+    pub fn size(&self) -> usize {
+        return self.size;
+    }
+
+    pub fn solve(&self) -> HdgSolution {
+        let trace = self
+            .system_matrix
+            .clone()
+            .cholesky()
+            .expect("Not expected hdg system without positive definiteness")
+            .solve(&self.extern_matrix);
+        return self.reconstruct(&trace);
+    }
+
+    /**
+     * Element solutions from the traces, by local solves
+     */
+    pub fn reconstruct(&self, trace: &DMatrix<f64>) -> HdgSolution {
+        let size: usize = self.elements.iter().map(|local| local.indexes.len()).sum();
+        let mut u = DMatrix::<f64>::zeros(size, 1);
+        let mut flux_x = DMatrix::<f64>::zeros(size, 1);
+        let mut flux_y = DMatrix::<f64>::zeros(size, 1);
+        for local in self.elements.iter() {
+            let values = DVector::from_iterator(
+                local.traces.len(),
+                local.traces.iter().map(|value| match value {
+                    Trace::Unknown(index) => trace[(*index, 0)],
+                    Trace::Known(value) => *value,
+                }),
+            );
+            let answer = &local.inverse * (&local.lift * values);
+            let n = local.indexes.len();
+            for (row, index) in local.indexes.iter().enumerate() {
+                flux_x[(*index, 0)] = answer[row];
+                flux_y[(*index, 0)] = answer[n + row];
+                u[(*index, 0)] = answer[2 * n + row];
+            }
+        }
+        return HdgSolution {
+            u,
+            flux_x,
+            flux_y,
+            trace: trace.clone(),
+        };
+    }
+
+    /**
+     * Coefficients on the enriched domain of the post processed u*, one degree above:
+     *  integral of grad(u*) . grad(w) = - integral of q . grad(w), with the mean of u
+     *
+     *  Reference:
+     *      Stenberg, R. Postprocessing schemes for some mixed finite elements.
+     *      M2AN, 1991. Vol 25, 151-167.
+     */
+    pub fn post_process(&self, domain: &Domain, solution: &HdgSolution) -> DMatrix<f64> {
+        let mut enhanced = DMatrix::<f64>::zeros(self.enriched.size(), 1);
+        for element in domain.elements.iter() {
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let basis = domain.element_basis(element);
+            let indexes = domain.element_indexes(element);
+            let enriched_basis = self.enriched.element_basis(element);
+            let m = enriched_basis.size();
+            let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+
+            let value = |coefficients: &DMatrix<f64>, phi: &DVector<f64>| -> f64 {
+                indexes
+                    .iter()
+                    .zip(phi.iter())
+                    .map(|(index, value)| coefficients[(*index, 0)] * value)
+                    .sum()
+            };
+
+            let mut stiffness = DMatrix::<f64>::zeros(m, m);
+            let mut load = DVector::<f64>::zeros(m);
+            let (mut integral, mut constant) = (0.0, 0.0);
+            let rule = dunavant::rule(2 * enriched_basis.degree());
+            for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+                let phi = basis.evaluate(*x, *y);
+                let psi = enriched_basis.evaluate(*x, *y);
+                let gradient = enriched_basis.physical_gradient(p1, p2, p3, *x, *y);
+                let (qx, qy) = (value(&solution.flux_x, &phi), value(&solution.flux_y, &phi));
+                stiffness += &gradient * gradient.transpose() * (weight * determinant);
+                load -=
+                    (gradient.column(0) * qx + gradient.column(1) * qy) * (weight * determinant);
+                integral += value(&solution.u, &phi) * weight * determinant;
+                constant += psi[0] * weight * determinant;
+            }
+
+            /* Modes above the constant one have zero mean */
+            let mut coefficients = DVector::<f64>::zeros(m);
+            coefficients[0] = integral / constant;
+            let slopes = stiffness
+                .slice((1, 1), (m - 1, m - 1))
+                .clone_owned()
+                .cholesky()
+                .expect("Failed to solve post processing")
+                .solve(&load.rows(1, m - 1).clone_owned());
+            coefficients.rows_mut(1, m - 1).copy_from(&slopes);
+
+            for (index, value) in self
+                .enriched
+                .element_indexes(element)
+                .iter()
+                .zip(coefficients.iter())
+            {
+                enhanced[(*index, 0)] = *value;
+            }
+        }
+        return enhanced;
+    }
+}
+
+/**
+ * Orientation of the edge shared by both neighbours:
+ * from the lower point to the greater one, by x and then y
+ */
+fn canonical(edge: &Rc<Edge>) -> Rc<Edge> {
+    let (p1, p2) = (&edge.p1, &edge.p2);
+    if (p1.x, p1.y) < (p2.x, p2.y) {
+        return Rc::clone(edge);
+    }
+    return Rc::new(edge.opposed());
+}
+
+/**
+ * Greatest degree of the elements sharing the edge
+ */
+fn edge_degree(domain: &Domain, element: &Rc<TriangleElementL1>, edge: &Rc<Edge>) -> usize {
+    let degree = domain.element_basis(element).degree();
+    return match domain.adjacency.get(&edge.opposed()) {
+        Some(t_right) => degree.max(domain.element_basis(t_right).degree()),
+        None => degree,
+    };
+}
+
+/**
+ * Legendre polynomials up to the degree, orthogonal over s in [0, 1]
+ */
+fn legendre(degree: usize, s: f64) -> DVector<f64> {
+    let t = 2.0 * s - 1.0;
+    let mut values = DVector::<f64>::zeros(degree + 1);
+    values[0] = 1.0;
+    if degree > 0 {
+        values[1] = t;
+    }
+    for n in 1..degree {
+        let n_f = n as f64;
+        values[n + 1] = ((2.0 * n_f + 1.0) * t * values[n] - n_f * values[n - 1]) / (n_f + 1.0);
+    }
+    return values;
+}
+
+/**
+ * Constraint value at parameter l along the edge, interpolated from its end points
+ */
+fn linear_value(constraint: &BoundaryConstraint, edge: &Edge, l: f64) -> f64 {
+    let value = |point| *constraint.values.get(point).unwrap_or(&0.0);
+    return (1.0 - l) * value(&edge.p1) + l * value(&edge.p2);
+}
+
+/**
+ * L2 projection of the constraint values into the Legendre polynomials of the edge
+ */
+fn project(
+    constraint: &BoundaryConstraint,
+    edge: &Edge,
+    degree: usize,
+    reversed: bool,
+) -> Vec<f64> {
+    let rule = gauss_legendre::rule(degree + 1);
+    let mut values = DVector::<f64>::zeros(degree + 1);
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let s = if reversed { 1.0 - l } else { *l };
+        values += legendre(degree, s) * (linear_value(constraint, edge, *l) * weight);
+    }
+    return values
+        .iter()
+        .enumerate()
+        .map(|(a, value)| value * (2 * a + 1) as f64)
+        .collect();
+}
+
+#[cfg(test)]
+mod hybridization {
+    use super::*;
+    use crate::common::point::Point;
+    use crate::triangle::{basis::shape::MAX_MODAL_DEGREE, mesh, system_builder::builder};
+
+    /* L2 error of the field given by its coefficients on the domain */
+    fn l2_error(
+        domain: &Domain,
+        coefficients: &DMatrix<f64>,
+        exact: &dyn Fn(&Point) -> f64,
+    ) -> f64 {
+        let mut error = 0.0;
+        for element in domain.elements.iter() {
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let basis = domain.element_basis(element);
+            let indexes = domain.element_indexes(element);
+            let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+            let rule = dunavant::rule(dunavant::MAX_DEGREE);
+            for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+                let value: f64 = basis
+                    .evaluate(*x, *y)
+                    .iter()
+                    .zip(indexes.iter())
+                    .map(|(phi, index)| phi * coefficients[(*index, 0)])
+                    .sum();
+                let point = utils::physical_point(p1, p2, p3, *x, *y);
+                error += (value - exact(&point)).powi(2) * weight * determinant;
+            }
+        }
+        return error.sqrt();
+    }
+
+    fn square(nodes: usize, degree: usize, exact: &dyn Fn(&Point) -> f64) -> Domain {
+        let mut domain = Domain::new_modal(degree).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), nodes, nodes, &mut domain);
+        let edges = boundary
+            .left
+            .iter()
+            .chain(boundary.right.iter())
+            .chain(boundary.bottom.iter())
+            .chain(boundary.top.iter());
+        for edge in edges {
+            domain.insert_dirichlet_constraint(edge, vec![exact(&edge.p1), exact(&edge.p2)]);
+        }
+        return domain;
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Linear solution and its flux are reproduced, with dirichlet and neumann edges */
+        let mut domain = Domain::new_modal(1).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 3, 3, &mut domain);
+        for edge in boundary.bottom.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+        for edge in boundary.left.iter() {
+            domain.insert_neumann_constraint(edge, vec![0.0, 0.0]);
+        }
+        for edge in boundary.right.iter() {
+            domain.insert_neumann_constraint(edge, vec![0.0, 0.0]);
+        }
+        for edge in boundary.top.iter() {
+            domain.insert_neumann_constraint(edge, vec![1.0, 1.0]);
+        }
+
+        let problem = HdgProblem::new(1.0, &domain).unwrap();
+        let solution = problem.solve();
+        assert!(l2_error(&domain, &solution.u, &|point: &Point| point.y) < 1.0E-12);
+        assert!(l2_error(&domain, &solution.flux_x, &|_: &Point| 0.0) < 1.0E-12);
+        assert!(l2_error(&domain, &solution.flux_y, &|_: &Point| -1.0) < 1.0E-12);
+
+        let enhanced = problem.post_process(&domain, &solution);
+        assert!(l2_error(&problem.enriched, &enhanced, &|point: &Point| point.y) < 1.0E-12);
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Harmonic u = x y at degree 1: u converges at second order,
+            the post processed u* at third order. The global system is
+            symmetric, positive definite, and smaller than the interior penalty one
+        */
+        let exact = |point: &Point| point.x * point.y;
+        let mut errors: Vec<(f64, f64)> = Vec::new();
+        for nodes in [2, 4, 8].iter() {
+            let domain = square(*nodes, 1, &exact);
+            let problem = HdgProblem::new(1.0, &domain).unwrap();
+            let solution = problem.solve();
+            let enhanced = problem.post_process(&domain, &solution);
+            errors.push((
+                l2_error(&domain, &solution.u, &exact),
+                l2_error(&problem.enriched, &enhanced, &exact),
+            ));
+
+            let system = &problem.system_matrix;
+            assert!((system - system.transpose()).norm() < 1.0E-14 * system.norm());
+            assert!(problem.size() < builder::build(10.0, &domain).0.nrows());
+        }
+        for pair in errors.windows(2) {
+            assert!((pair[0].0 / pair[1].0).log2() > 1.8);
+            assert!((pair[0].1 / pair[1].1).log2() > 2.7);
+        }
+        assert!(errors[2].1 < errors[2].0 / 10.0);
+    }
+
+    #[test]
+    fn sample_3() {
+        /* Post processing needs one degree above the domain one */
+        let exact = |point: &Point| point.x * point.y;
+        let domain = square(2, MAX_MODAL_DEGREE, &exact);
+        assert_eq!(
+            HdgProblem::new(1.0, &domain).err(),
+            Some(Error::UnsupportedDegree)
+        );
+        let domain = square(2, MAX_MODAL_DEGREE - 1, &exact);
+        assert!(HdgProblem::new(1.0, &domain).is_ok());
+    }
+}
@@ -1,30 +1,242 @@
+use nalgebra::DMatrix;
+
+use crate::common::point::Point;
+use crate::triangle::{
+    solvers::newton,
+    system_builder::{
+        domain::Domain,
+        internal::l2_mass,
+        nonlinear_diffusion_problem::{Conductivity, NonlinearDiffusionProblem},
+    },
+};
+
 pub enum DynamicState {
     SteadyState,
-    Transient,
+    /* Backward euler over the given number of steps */
+    Transient { dt: f64, steps: usize },
 }
 
-pub struct DirichletConstraint {
-    pub number: usize,
-    // value:
+/**
+ * Heat conduction: density * capacity * du/dt - div(k grad(u)) = q,
+ * with the dirichlet, neumann and radiation constraints of the domain.
+ *  Each state, or each time step, is solved by newton iterations
+ *  over the residual of the nonlinear diffusion problem
+ */
+pub struct HeatProblem<'a> {
+    pub conduction: Conductivity<'a>,
+    pub density: f64,
+    pub capacity: f64,
+    pub sigma: f64,
+    /* Volumetric heat source q */
+    pub external_field: Option<&'a dyn Fn(&Point) -> f64>,
+    pub state: DynamicState,
+    pub options: newton::Options,
 }
 
-pub struct NeumannConstraint {
-    pub number: usize,
-    // value:
-}
+impl<'a> HeatProblem<'a> {
+    pub fn new(
+        conduction: Conductivity<'a>,
+        density: f64,
+        capacity: f64,
+        sigma: f64,
+        state: DynamicState,
+    ) -> Self {
+        HeatProblem {
+            conduction,
+            density,
+            capacity,
+            sigma,
+            external_field: None,
+            state,
+            options: newton::Options::default(),
+        }
+    }
 
-pub struct ExternalField {
-    pub number: usize,
-    // value:
+    pub fn with_external_field(mut self, external_field: &'a dyn Fn(&Point) -> f64) -> Self {
+        self.external_field = Some(external_field);
+        return self;
+    }
+
+    fn diffusion(&self) -> NonlinearDiffusionProblem<'a> {
+        return NonlinearDiffusionProblem {
+            conductivity: self.conduction,
+            sigma: self.sigma,
+            source: self.external_field,
+        };
+    }
+
+    /**
+     * Steady temperature, or the one at the last time step from the initial temperature,
+     * with the newton report of each solve
+     */
+    pub fn solve(
+        &self,
+        domain: &Domain,
+        initial: &DMatrix<f64>,
+    ) -> (DMatrix<f64>, Vec<newton::Report>) {
+        match self.state {
+            DynamicState::SteadyState => {
+                let (temperature, report) = self.diffusion().solve(domain, initial, &self.options);
+                return (temperature, vec![report]);
+            }
+            DynamicState::Transient { dt, steps } => {
+                let mut temperature = initial.clone();
+                let mut reports = Vec::new();
+                for _ in 0..steps {
+                    let (next, report) = self.step(domain, &temperature, dt);
+                    temperature = next;
+                    reports.push(report);
+                }
+                return (temperature, reports);
+            }
+        }
+    }
+
+    /**
+     * Backward euler step from the previous temperature:
+     *  density * capacity / dt * M (u - u_old) + r(u) = 0
+     */
+    pub fn step(
+        &self,
+        domain: &Domain,
+        previous: &DMatrix<f64>,
+        dt: f64,
+    ) -> (DMatrix<f64>, newton::Report) {
+        let size = domain.size();
+        let mut mass = DMatrix::<f64>::zeros(size, size);
+        l2_mass::build(&mut mass, domain).expect("Failed to build mass matrix");
+        mass *= self.density * self.capacity / dt;
+
+        let diffusion = self.diffusion();
+        return newton::solve(
+            previous,
+            &|u: &DMatrix<f64>| &mass * (u - previous) + diffusion.residual(domain, u),
+            &|u: &DMatrix<f64>| &mass + diffusion.jacobian(domain, u),
+            &self.options,
+        );
+    }
 }
 
-pub struct HeatProblem {
-    pub triangulation: &Triangulation,
-    pub conduction: f64,
-    pub capacity: f64,
-    pub density: f64,
-    pub dirichlet_constraints: Vec<DirichletConstraint>,
-    pub neumann_constraints: Vec<NeumannConstraint>,
-    pub external_field: Vec<ExternalField>,
-    pub state: DynamicState,
+#[cfg(test)]
+mod radiation {
+    use super::*;
+    use crate::triangle::{basis::dubiner, boundary_constraint::STEFAN_BOLTZMANN, mesh};
+    use nalgebra::DVector;
+
+    /**
+     * Temperatures at the vertices of every element, with their points
+     */
+    fn vertex_values(domain: &Domain, solution: &DMatrix<f64>) -> Vec<(Point, f64)> {
+        let mut values = Vec::new();
+        for element in domain.elements.iter() {
+            let indexes = domain.element_indexes(element);
+            let local = DVector::from_iterator(
+                indexes.len(),
+                indexes.iter().map(|index| solution[(*index, 0)]),
+            );
+            let (u1, u2, u3) =
+                dubiner::vertex_values(domain.element_basis(element).degree(), &local);
+            values.push((Point::new(element.p1.x, element.p1.y), u1));
+            values.push((Point::new(element.p2.x, element.p2.y), u2));
+            values.push((Point::new(element.p3.x, element.p3.y), u3));
+        }
+        return values;
+    }
+
+    fn uniform(domain: &Domain, value: f64) -> DMatrix<f64> {
+        let mut field = DMatrix::<f64>::zeros(domain.size(), 1);
+        for element in domain.elements.iter() {
+            let degree = domain.element_basis(element).degree();
+            let local = dubiner::linear_to_modal(degree, value, value, value);
+            for (index, coefficient) in domain.element_indexes(element).iter().zip(local.iter()) {
+                field[(*index, 0)] = *coefficient;
+            }
+        }
+        return field;
+    }
+
+    #[test]
+    fn sample_1() {
+        /*
+            Slab held at 1000 K at left, radiating at right into 300 K:
+            conduction k (T0 - T1) / L balances emissivity * sigma (T1^4 - Ta^4),
+            and the profile is linear
+        */
+        let (length, conduction, emissivity) = (0.1, 2.0, 0.8);
+        let (hot, ambient) = (1000.0, 300.0);
+        let mut domain = Domain::new_modal(1).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (length, 0.02), 5, 1, &mut domain);
+        for edge in boundary.left.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![hot, hot]);
+        }
+        for edge in boundary.right.iter() {
+            domain.insert_radiation_constraint(edge, emissivity, vec![ambient, ambient]);
+        }
+
+        let problem = HeatProblem::new(
+            Conductivity::Constant(conduction),
+            1.0,
+            1.0,
+            10.0 * conduction,
+            DynamicState::SteadyState,
+        );
+        let (temperature, reports) = problem.solve(&domain, &uniform(&domain, hot));
+        assert!(reports[0].converged);
+
+        let mut surface = hot;
+        for _ in 0..50 {
+            let balance = conduction * (hot - surface) / length
+                - emissivity * STEFAN_BOLTZMANN * (surface.powi(4) - ambient.powi(4));
+            let derivative =
+                -conduction / length - 4.0 * emissivity * STEFAN_BOLTZMANN * surface.powi(3);
+            surface -= balance / derivative;
+        }
+        for (point, value) in vertex_values(&domain, &temperature) {
+            let exact = hot + (surface - hot) * point.x / length;
+            assert!((value - exact).abs() < 1.0E-6 * hot);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Highly conductive plate cooling by radiation at one edge: the mean
+            temperature follows the lumped balance
+                density * capacity * L dT/dt = - emissivity * sigma (T^4 - Ta^4)
+        */
+        let (length, emissivity, density, capacity) = (0.01, 0.9, 1.0, 10.0);
+        let (hot, ambient) = (800.0, 300.0);
+        let mut domain = Domain::new_modal(1).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (length, length), 2, 2, &mut domain);
+        for edge in boundary.right.iter() {
+            domain.insert_radiation_constraint(edge, emissivity, vec![ambient, ambient]);
+        }
+
+        let (dt, steps) = (2.0, 50);
+        let problem = HeatProblem::new(
+            Conductivity::Constant(100.0),
+            density,
+            capacity,
+            1000.0,
+            DynamicState::Transient { dt, steps },
+        );
+        let (temperature, reports) = problem.solve(&domain, &uniform(&domain, hot));
+        assert_eq!(reports.len(), steps);
+        assert!(reports.iter().all(|report| report.converged));
+
+        /* Reference by fine forward euler steps */
+        let mut lumped = hot;
+        let substeps = 100_000;
+        let h = dt * steps as f64 / substeps as f64;
+        for _ in 0..substeps {
+            lumped -= h * emissivity * STEFAN_BOLTZMANN * (lumped.powi(4) - ambient.powi(4))
+                / (density * capacity * length);
+        }
+
+        let values = vertex_values(&domain, &temperature);
+        let mean = values.iter().map(|(_, value)| value).sum::<f64>() / values.len() as f64;
+        assert!(lumped < hot - 100.0);
+        assert!((mean - lumped).abs() < 0.01 * (hot - lumped));
+        assert!(values.iter().all(|(_, value)| (value - mean).abs() < 1.0));
+    }
 }
@@ -0,0 +1,29 @@
+use nalgebra::DMatrix;
+
+use crate::common::error::Error;
+use crate::triangle::{
+    integrands::advection::{self, Velocity},
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+/**
+ * Fills the system matrix with the advection over each element:
+ *  - u b . grad(v)
+ */
+pub fn build(
+    system_matrix: &mut DMatrix<f64>,
+    velocity: Velocity,
+    domain: &Domain,
+) -> Result<(), Error> {
+    for element in domain.elements.iter() {
+        let indexes = domain.element_indexes(element);
+        let basis = domain.element_basis(element);
+
+        assembler_utils::map(
+            system_matrix,
+            &advection::volume_basis(&element.p1, &element.p2, &element.p3, &basis, velocity),
+            &assembler_utils::block_map(&indexes, &indexes),
+        );
+    }
+    return Ok(());
+}
@@ -0,0 +1,82 @@
+use nalgebra::DMatrix;
+
+use crate::common::error::Error;
+use crate::triangle::{
+    integrands::divergence,
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+use std::rc::Rc;
+
+/**
+ * Fills the divergence blocks of the x and y velocity components:
+ *  integral of -q div(v) over elements and {q} [v] . n at inner interfaces.
+ *  Rows follow the pressure domain and columns the velocity domain,
+ *  which share the same elements
+ */
+pub fn build(
+    bx: &mut DMatrix<f64>, /* PxN matrix */
+    by: &mut DMatrix<f64>, /* PxN matrix */
+    velocity: &Domain,
+    pressure: &Domain,
+) -> Result<(), Error> {
+    for element in velocity.elements.iter() {
+        let velocity_indexes = velocity.element_indexes(element);
+        let pressure_indexes = pressure.element_indexes(element);
+        let velocity_basis = velocity.element_basis(element);
+        let pressure_basis = pressure.element_basis(element);
+
+        let (vx, vy) = divergence::volume_basis(
+            &element.p1,
+            &element.p2,
+            &element.p3,
+            &velocity_basis,
+            &pressure_basis,
+        );
+        let map = assembler_utils::block_map(&pressure_indexes, &velocity_indexes);
+        assembler_utils::map(bx, &vx, &map);
+        assembler_utils::map(by, &vy, &map);
+
+        let (e1, e2, e3) = element.inner_edges();
+        for edge in [e1, e2, e3].iter() {
+            let edge_opp = Rc::new(edge.opposed());
+            if let Some(t_right) = velocity.adjacency.get(&edge_opp) {
+                let right_indexes = velocity.element_indexes(t_right);
+                let right_basis = velocity.element_basis(t_right);
+                let edge_index = element.edge_index(edge).unwrap();
+
+                /* left-left interaction */
+                let (nx, ny) = divergence::normal_basis(
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &pressure_basis,
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &velocity_basis,
+                    edge_index,
+                );
+                assembler_utils::map(bx, &(0.5 * nx), &map);
+                assembler_utils::map(by, &(0.5 * ny), &map);
+
+                /* left-right interaction */
+                let (nx, ny) = divergence::normal_basis(
+                    &element.p1,
+                    &element.p2,
+                    &element.p3,
+                    &pressure_basis,
+                    &t_right.p1,
+                    &t_right.p2,
+                    &t_right.p3,
+                    &right_basis,
+                    edge_index,
+                );
+                let map = assembler_utils::block_map(&pressure_indexes, &right_indexes);
+                assembler_utils::map(bx, &(-0.5 * nx), &map);
+                assembler_utils::map(by, &(-0.5 * ny), &map);
+            } /* end - if adjacency */
+        }
+    }
+    return Ok(());
+}
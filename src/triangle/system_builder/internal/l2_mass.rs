@@ -0,0 +1,47 @@
+use nalgebra::DMatrix;
+
+use crate::common::error::Error;
+use crate::triangle::{
+    integrands::l2_mass,
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+/**
+ * Fills the matrix with the mass matrix of each element: u v
+ */
+pub fn build(mass_matrix: &mut DMatrix<f64>, domain: &Domain) -> Result<(), Error> {
+    for element in domain.elements.iter() {
+        let indexes = domain.element_indexes(element);
+        let basis = domain.element_basis(element);
+
+        assembler_utils::map(
+            mass_matrix,
+            &l2_mass::matrix_basis(&element.p1, &element.p2, &element.p3, &basis),
+            &assembler_utils::block_map(&indexes, &indexes),
+        );
+    }
+    return Ok(());
+}
+
+/**
+ * Multiplies the matrix on the left by the inverse mass matrix,
+ * inverting each element block of the block diagonal mass matrix
+ */
+pub fn apply_inverse(matrix: &mut DMatrix<f64>, domain: &Domain) -> Result<(), Error> {
+    for element in domain.elements.iter() {
+        let indexes = domain.element_indexes(element);
+        let basis = domain.element_basis(element);
+
+        let inverse = match l2_mass::matrix_basis(&element.p1, &element.p2, &element.p3, &basis)
+            .try_inverse()
+        {
+            Some(inverse) => inverse,
+            None => return Err(Error::SingularMatrix),
+        };
+        let rows = inverse * matrix.select_rows(indexes.iter());
+        for (local, global) in indexes.iter().enumerate() {
+            matrix.set_row(*global, &rows.row(local));
+        }
+    }
+    return Ok(());
+}
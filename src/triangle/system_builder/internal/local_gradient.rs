@@ -0,0 +1,134 @@
+use nalgebra::DMatrix;
+
+use crate::common::edge::Edge;
+use crate::common::error::Error;
+use crate::triangle::{
+    boundary_constraint::BoundaryConstraint,
+    integrands::{divergence, l2_mass},
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/**
+ * Fills both matrices with the auxiliary gradient q of the local discontinuous
+ * Galerkin method, eliminated element by element. On each element,
+ *      integral of q . w = integral of grad(u) . w + integral over edges of (u* - u) w . n
+ *  that is M q = G u + d, so that the primal system takes G^T M^-1 G u = - G^T M^-1 d.
+ *  Fluxes alternate: at inner interfaces u* is the trace of the element whose
+ *  degrees of freedom come first, and q* the one of the other. At dirichlet edges
+ *  u* is the prescribed value g, interpolated from the edge values, and q* = q.
+ *  Penalties on jumps and dirichlet values are left to the other builders.
+ *
+ *  Reference:
+ *      Cockburn, B.; Shu, C.W. The local discontinuous Galerkin method for
+ *      time-dependent convection-diffusion systems. SINUM, 1998. Vol 35, 2440-2463.
+ */
+pub fn build(
+    system_matrix: &mut DMatrix<f64>, /* NxN matrix */
+    extern_matrix: &mut DMatrix<f64>, /* Nx1 matrix */
+    domain: &Domain,
+) -> Result<(), Error> {
+    let constraints: HashMap<Rc<Edge>, Rc<BoundaryConstraint>> = domain
+        .dirichlet_constraints
+        .iter()
+        .map(|constraint| (Rc::clone(&constraint.boundary_edge), Rc::clone(constraint)))
+        .collect();
+
+    for element in domain.elements.iter() {
+        let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+        let indexes = domain.element_indexes(element);
+        let basis = domain.element_basis(element);
+        let size = basis.size();
+
+        /* Columns: the element first, then the neighbours whose traces it takes */
+        let mut columns = indexes.clone();
+        let mut neighbours = Vec::new();
+        let (e1, e2, e3) = element.inner_edges();
+        let edges = [e1, e2, e3];
+        for edge in edges.iter() {
+            let edge_index = element.edge_index(edge).unwrap();
+            if let Some(t_right) = domain.adjacency.get(&edge.opposed()) {
+                let right_indexes = domain.element_indexes(t_right);
+                if right_indexes[0] < indexes[0] {
+                    neighbours.push((Rc::clone(t_right), edge_index, columns.len()));
+                    columns.extend(right_indexes);
+                }
+            }
+        }
+
+        let mut gx = DMatrix::<f64>::zeros(size, columns.len());
+        let mut gy = DMatrix::<f64>::zeros(size, columns.len());
+        let (vx, vy) = divergence::volume_basis(p1, p2, p3, &basis, &basis);
+        let (mut inner_x, mut inner_y) = (-vx, -vy);
+
+        /* Neighbour traces replace the inner ones */
+        for (t_right, edge_index, offset) in neighbours.iter() {
+            let right_basis = domain.element_basis(t_right);
+            let (nx, ny) = divergence::normal_basis(
+                p1,
+                p2,
+                p3,
+                &basis,
+                &t_right.p1,
+                &t_right.p2,
+                &t_right.p3,
+                &right_basis,
+                *edge_index,
+            );
+            let right_size = right_basis.size();
+            gx.slice_mut((0, *offset), (size, right_size))
+                .copy_from(&nx);
+            gy.slice_mut((0, *offset), (size, right_size))
+                .copy_from(&ny);
+
+            let (nx, ny) =
+                divergence::normal_basis(p1, p2, p3, &basis, p1, p2, p3, &basis, *edge_index);
+            inner_x -= nx;
+            inner_y -= ny;
+        }
+
+        /* Prescribed values replace the inner ones */
+        let mut dx = DMatrix::<f64>::zeros(size, 1);
+        let mut dy = DMatrix::<f64>::zeros(size, 1);
+        for edge in edges.iter() {
+            if let Some(constraint) = constraints.get(edge) {
+                let edge_index = element.edge_index(edge).unwrap();
+                let (nx, ny) =
+                    divergence::normal_basis(p1, p2, p3, &basis, p1, p2, p3, &basis, edge_index);
+                inner_x -= nx;
+                inner_y -= ny;
+
+                let value = |point| *constraint.values.get(point).unwrap_or(&0.0);
+                let values = (value(p1), value(p2), value(p3));
+                let zeros = (0.0, 0.0, 0.0);
+                dx +=
+                    divergence::normal_linear_basis(p1, p2, p3, &basis, values, zeros, edge_index);
+                dy +=
+                    divergence::normal_linear_basis(p1, p2, p3, &basis, zeros, values, edge_index);
+            }
+        }
+
+        gx.slice_mut((0, 0), (size, size)).copy_from(&inner_x);
+        gy.slice_mut((0, 0), (size, size)).copy_from(&inner_y);
+
+        let inverse_mass = match l2_mass::matrix_basis(p1, p2, p3, &basis).try_inverse() {
+            Some(inverse) => inverse,
+            None => return Err(Error::SingularMatrix),
+        };
+        let (lifted_x, lifted_y) = (&inverse_mass * &gx, &inverse_mass * &gy);
+
+        assembler_utils::map(
+            system_matrix,
+            &(gx.transpose() * &lifted_x + gy.transpose() * &lifted_y),
+            &assembler_utils::block_map(&columns, &columns),
+        );
+        assembler_utils::map(
+            extern_matrix,
+            &(-(lifted_x.transpose() * dx + lifted_y.transpose() * dy)),
+            &assembler_utils::column_map(&columns),
+        );
+    }
+    return Ok(());
+}
@@ -1,46 +1,24 @@
 use nalgebra::DMatrix;
 
-use crate::triangle::{integrands::mass, system_builder::domain::Domain};
-
-use std::rc::Rc;
+use crate::common::error::Error;
+use crate::triangle::{
+    integrands::mass,
+    system_builder::{assembler_utils, domain::Domain},
+};
 
 /**
- * Fills the system matrix with mass matrix according to each element
+ * Fills the system matrix with stiffness matrix according to each element
  */
-pub fn build(system_matrix: &mut DMatrix<f64>, domain: &Domain) -> Result<(), ()> {
-    for index in 0..domain.elements.len() {
-        let element = Rc::clone(domain.elements.get(index).unwrap());
-
-        let gl_p1 = *domain
-            .index_mapping
-            .get(&(Rc::clone(&element), Rc::clone(&element.p1)))
-            .unwrap();
-        let gl_p2 = *domain
-            .index_mapping
-            .get(&(Rc::clone(&element), Rc::clone(&element.p2)))
-            .unwrap();
-        let gl_p3 = *domain
-            .index_mapping
-            .get(&(Rc::clone(&element), Rc::clone(&element.p3)))
-            .unwrap();
-
-        let mass_matrix = mass::matrix(&element.p1, &element.p2, &element.p3);
-
-        let lo_p1: usize = 0;
-        let lo_p2: usize = 1;
-        let lo_p3: usize = 2;
-
-        system_matrix[(gl_p1, gl_p1)] += mass_matrix[(lo_p1, lo_p1)];
-        system_matrix[(gl_p1, gl_p2)] += mass_matrix[(lo_p1, lo_p2)];
-        system_matrix[(gl_p1, gl_p3)] += mass_matrix[(lo_p1, lo_p3)];
-
-        system_matrix[(gl_p2, gl_p1)] += mass_matrix[(lo_p2, lo_p1)];
-        system_matrix[(gl_p2, gl_p2)] += mass_matrix[(lo_p2, lo_p2)];
-        system_matrix[(gl_p2, gl_p3)] += mass_matrix[(lo_p2, lo_p3)];
-
-        system_matrix[(gl_p3, gl_p1)] += mass_matrix[(lo_p3, lo_p1)];
-        system_matrix[(gl_p3, gl_p2)] += mass_matrix[(lo_p3, lo_p2)];
-        system_matrix[(gl_p3, gl_p3)] += mass_matrix[(lo_p3, lo_p3)];
+pub fn build(system_matrix: &mut DMatrix<f64>, domain: &Domain) -> Result<(), Error> {
+    for element in domain.elements.iter() {
+        let indexes = domain.element_indexes(element);
+        let basis = domain.element_basis(element);
+
+        assembler_utils::map(
+            system_matrix,
+            &mass::matrix_basis(&element.p1, &element.p2, &element.p3, &basis),
+            &assembler_utils::block_map(&indexes, &indexes),
+        );
     }
     return Ok(());
 }
@@ -51,6 +29,8 @@ mod build {
     use crate::common::point::Point;
     use crate::triangle::element::TriangleElementL1;
 
+    use std::rc::Rc;
+
     #[test]
     fn sample_1() {
         /* square into triangles */
@@ -68,13 +48,38 @@ mod build {
 
         let mut system_matrix: DMatrix<f64> = DMatrix::<f64>::zeros(6, 6);
 
-        build(&mut system_matrix, &domain);
-
-        assert_eq!(system_matrix[(0, 0)], 2.0);
-        assert_eq!(system_matrix[(0, 1)], -1.0);
-        assert_eq!(system_matrix[(0, 2)], -1.0);
-        assert_eq!(system_matrix[(1, 1)], 1.0);
-        assert_eq!(system_matrix[(2, 2)], 1.0);
+        build(&mut system_matrix, &domain).unwrap();
+
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(0, 0)],
+            1.0,
+            epsilon = 1.0E-14f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(0, 1)],
+            -0.5,
+            epsilon = 1.0E-14f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(0, 2)],
+            -0.5,
+            epsilon = 1.0E-14f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(1, 1)],
+            0.5,
+            epsilon = 1.0E-14f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(2, 2)],
+            0.5,
+            epsilon = 1.0E-14f64
+        ));
     }
 
     #[test]
@@ -107,13 +112,43 @@ mod build {
         let mut system_matrix: DMatrix<f64> =
             DMatrix::<f64>::zeros(variables_length, variables_length);
 
-        build(&mut system_matrix, &domain);
-
-        assert_eq!(system_matrix[(0, 0)], 2.0);
-        assert_eq!(system_matrix[(0, 1)], -2.0);
-        assert_eq!(system_matrix[(0, 2)], 0.0);
-        assert_eq!(system_matrix[(1, 1)], 2.5);
-        assert_eq!(system_matrix[(1, 2)], -0.5);
-        assert_eq!(system_matrix[(2, 2)], 0.5);
+        build(&mut system_matrix, &domain).unwrap();
+
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(0, 0)],
+            0.5,
+            epsilon = 1.0E-14f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(0, 1)],
+            -0.5,
+            epsilon = 1.0E-14f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(0, 2)],
+            0.0,
+            epsilon = 1.0E-14f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(1, 1)],
+            1.0,
+            epsilon = 1.0E-14f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(1, 2)],
+            -0.5,
+            epsilon = 1.0E-14f64
+        ));
+        assert!(float_cmp::approx_eq!(
+            f64,
+            system_matrix[(2, 2)],
+            0.5,
+            epsilon = 1.0E-14f64
+        ));
     }
 }
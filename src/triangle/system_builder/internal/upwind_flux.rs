@@ -0,0 +1,65 @@
+use nalgebra::DMatrix;
+
+use crate::common::error::Error;
+use crate::triangle::{
+    integrands::advection::{self, Velocity},
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+use std::rc::Rc;
+
+/**
+ * Fills the system matrix with the upwind flux at inner interfaces:
+ *  b . n u* v, where u* is the trace of the element the flow comes from
+ */
+pub fn build(
+    system_matrix: &mut DMatrix<f64>,
+    velocity: Velocity,
+    domain: &Domain,
+) -> Result<(), Error> {
+    for element in domain.elements.iter() {
+        let (e1, e2, e3) = element.inner_edges();
+        for edge in [e1, e2, e3].iter() {
+            let edge = Rc::clone(edge);
+            let edge_opp = Rc::new(edge.opposed());
+            if domain.adjacency.contains_key(&edge_opp) {
+                let t_left = Rc::clone(domain.adjacency.get(&edge).unwrap());
+                let t_right = Rc::clone(domain.adjacency.get(&edge_opp).unwrap());
+
+                let left_indexes = domain.element_indexes(&t_left);
+                let right_indexes = domain.element_indexes(&t_right);
+                let left_basis = domain.element_basis(&t_left);
+                let right_basis = domain.element_basis(&t_right);
+
+                let edge_index = t_left.edge_index(&edge).unwrap();
+                let (outflow, inflow) = advection::upwind_flux_basis(
+                    &t_left.p1,
+                    &t_left.p2,
+                    &t_left.p3,
+                    &left_basis,
+                    &t_right.p1,
+                    &t_right.p2,
+                    &t_right.p3,
+                    &right_basis,
+                    edge_index,
+                    velocity,
+                );
+
+                /* left-left interaction */
+                assembler_utils::map(
+                    system_matrix,
+                    &outflow,
+                    &assembler_utils::block_map(&left_indexes, &left_indexes),
+                );
+
+                /* left-right interaction */
+                assembler_utils::map(
+                    system_matrix,
+                    &inflow,
+                    &assembler_utils::block_map(&left_indexes, &right_indexes),
+                );
+            } /* end - if adjacency */
+        } /* end - for edge in triangle */
+    } /* end - for element in domain */
+    return Ok(());
+}
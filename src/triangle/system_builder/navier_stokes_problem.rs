@@ -0,0 +1,628 @@
+use nalgebra::{linalg::LU, DMatrix, DVector, Dynamic};
+
+use crate::common::{edge::Edge, error::Error};
+use crate::triangle::{
+    element::TriangleElementL1,
+    integrands::utils,
+    quadrature::{dunavant, gauss_legendre},
+    system_builder::{
+        advection_problem,
+        domain::Domain,
+        internal::l2_mass,
+        stokes_problem::{self, BoundaryVelocity, StokesSolution},
+    },
+};
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/**
+ * Numerical flux of the convective term u u . n, for each velocity component
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConvectiveFlux {
+    /* Central flux plus jumps scaled by the greatest normal speed */
+    LaxFriedrichs,
+    /* Trace of the element the flow comes from, carried at the mean normal speed */
+    Upwind,
+}
+
+impl ConvectiveFlux {
+    pub fn evaluate(
+        &self,
+        (ux, uy): (f64, f64),
+        (vx, vy): (f64, f64),
+        (nx, ny): (f64, f64),
+    ) -> (f64, f64) {
+        let (inner_speed, outer_speed) = (ux * nx + uy * ny, vx * nx + vy * ny);
+        match self {
+            ConvectiveFlux::LaxFriedrichs => {
+                let speed = inner_speed.abs().max(outer_speed.abs());
+                return (
+                    0.5 * (inner_speed * ux + outer_speed * vx) + 0.5 * speed * (ux - vx),
+                    0.5 * (inner_speed * uy + outer_speed * vy) + 0.5 * speed * (uy - vy),
+                );
+            }
+            ConvectiveFlux::Upwind => {
+                let speed = 0.5 * (inner_speed + outer_speed);
+                if speed >= 0.0 {
+                    return (speed * ux, speed * uy);
+                }
+                return (speed * vx, speed * vy);
+            }
+        }
+    }
+}
+
+/* Basis values and physical gradients at an integration point, weight included */
+struct VolumePoint {
+    weight: f64,
+    phi: DVector<f64>,
+    gradient: DMatrix<f64>,
+}
+
+/* Basis values of both sides at an edge integration point, weight included */
+struct FacePoint {
+    weight: f64,
+    phi: DVector<f64>,
+    neighbour_phi: Option<DVector<f64>>,
+    prescribed: (f64, f64),
+}
+
+enum Exterior {
+    /* Position of the neighbour element at Domain::elements */
+    Element(usize),
+    /* Boundary velocity outside */
+    Prescribed,
+    /* Inner velocity outside */
+    Free,
+}
+
+struct Face {
+    exterior: Exterior,
+    normal: (f64, f64),
+    points: Vec<FacePoint>,
+}
+
+struct ElementOperator {
+    indexes: Vec<usize>,
+    volume: Vec<VolumePoint>,
+    faces: Vec<Face>,
+}
+
+/**
+ * Transient incompressible Navier-Stokes flow:
+ *  du/dt + div(u u) - nu laplacian(u) + grad(p) = 0, div(u) = 0,
+ *  with velocity prescribed at boundary edges but the free ones,
+ *  where the outflow condition nu du/dn = 0, p = 0 holds weakly.
+ *
+ *  Operators are the ones of the Stokes problem. Each step of length dt is an
+ *  incremental pressure correction: convection is explicit and viscosity implicit
+ *  at the predictor
+ *      (M / dt + nu A) u* = M u / dt + C(u) + nu f - B^T p,
+ *  the pressure increment follows from
+ *      B M^-1 B^T q = (B u* - g) / dt,
+ *  and the corrected velocity u* - dt M^-1 B^T q meets the discrete divergence.
+ *  When no edge is free, the increment has zero mean.
+ *
+ *  Reference:
+ *      Guermond, J.L.; Minev, P.; Shen, J. An overview of projection methods for
+ *      incompressible flows. CMAME, 2006. Vol 195, 6011-6045.
+ */
+pub struct NavierStokesProblem {
+    pub viscosity: f64,
+    pub flux: ConvectiveFlux,
+    pub dt: f64,
+    pub pressure: Domain,
+
+    /* Smallest inscribed circle diameter over the greatest basis degree */
+    pub min_length: f64,
+
+    mass: DMatrix<f64>,
+    predictor: LU<f64, Dynamic, Dynamic>,
+    source_x: DMatrix<f64>,
+    source_y: DMatrix<f64>,
+    bx: DMatrix<f64>,
+    by: DMatrix<f64>,
+    g: DMatrix<f64>,
+    /* M^-1 Bx^T and M^-1 By^T */
+    gradient_x: DMatrix<f64>,
+    gradient_y: DMatrix<f64>,
+    projection: LU<f64, Dynamic, Dynamic>,
+    /* Projection bordered by the zero mean of the pressure increment, when no edge is free */
+    zero_mean: bool,
+    elements: Vec<ElementOperator>,
+}
+
+impl NavierStokesProblem {
+    /**
+     * Fails if a velocity element has degree 0, which leaves no pressure space
+     */
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        viscosity: f64,
+        sigma: f64,
+        dt: f64,
+        flux: ConvectiveFlux,
+        velocity: &Domain,
+        boundary_velocity: BoundaryVelocity,
+        free_edges: &[Rc<Edge>],
+    ) -> Result<Self, Error> {
+        let free_edges: HashSet<Rc<Edge>> = free_edges.iter().map(Rc::clone).collect();
+        let pressure = stokes_problem::pressure_domain(velocity)?;
+        let constraints = stokes_problem::boundary_constraints(velocity, &free_edges);
+        let (stiffness, fx, fy) =
+            stokes_problem::viscous_operator(sigma, velocity, &constraints, boundary_velocity);
+        let (bx, by, g) = stokes_problem::divergence_operator(
+            velocity,
+            &pressure,
+            &constraints,
+            boundary_velocity,
+        );
+
+        let n = velocity.size();
+        let mut mass = DMatrix::<f64>::zeros(n, n);
+        l2_mass::build(&mut mass, velocity).expect("Failed to build mass matrix");
+        let predictor = (&mass / dt + &stiffness * viscosity).lu();
+
+        let mut gradient_x = bx.transpose();
+        let mut gradient_y = by.transpose();
+        l2_mass::apply_inverse(&mut gradient_x, velocity).expect("Failed to invert mass matrix");
+        l2_mass::apply_inverse(&mut gradient_y, velocity).expect("Failed to invert mass matrix");
+
+        let m = pressure.size();
+        let laplacian = &bx * &gradient_x + &by * &gradient_y;
+        let zero_mean = free_edges.is_empty();
+        let projection = if zero_mean {
+            let mean = stokes_problem::pressure_mean(&pressure);
+            let mut bordered = DMatrix::<f64>::zeros(m + 1, m + 1);
+            bordered.slice_mut((0, 0), (m, m)).copy_from(&laplacian);
+            bordered.slice_mut((0, m), (m, 1)).copy_from(&mean);
+            bordered
+                .slice_mut((m, 0), (1, m))
+                .copy_from(&mean.transpose());
+            bordered.lu()
+        } else {
+            laplacian.lu()
+        };
+
+        let (elements, min_length) = element_operators(velocity, boundary_velocity, &free_edges);
+
+        return Ok(NavierStokesProblem {
+            viscosity,
+            flux,
+            dt,
+            pressure,
+            min_length,
+            mass,
+            predictor,
+            source_x: fx * viscosity,
+            source_y: fy * viscosity,
+            bx,
+            by,
+            g,
+            gradient_x,
+            gradient_y,
+            projection,
+            zero_mean,
+            elements,
+        });
+    }
+
+    /**
+     * Convective residuals of the velocity components:
+     *  integral of u (u . grad(phi)) - integral over edges of F*(u-, u+, n) phi
+     */
+    pub fn convection(
+        &self,
+        velocity_x: &DMatrix<f64>,
+        velocity_y: &DMatrix<f64>,
+    ) -> (DMatrix<f64>, DMatrix<f64>) {
+        let size = velocity_x.nrows();
+        let mut residual_x = DMatrix::<f64>::zeros(size, 1);
+        let mut residual_y = DMatrix::<f64>::zeros(size, 1);
+        for operator in self.elements.iter() {
+            let indexes = &operator.indexes;
+            for point in operator.volume.iter() {
+                let u = trace(velocity_x, indexes, &point.phi);
+                let v = trace(velocity_y, indexes, &point.phi);
+                for (i, index) in indexes.iter().enumerate() {
+                    let transport =
+                        (point.gradient[(i, 0)] * u + point.gradient[(i, 1)] * v) * point.weight;
+                    residual_x[(*index, 0)] += transport * u;
+                    residual_y[(*index, 0)] += transport * v;
+                }
+            }
+
+            for face in operator.faces.iter() {
+                for point in face.points.iter() {
+                    let inner = (
+                        trace(velocity_x, indexes, &point.phi),
+                        trace(velocity_y, indexes, &point.phi),
+                    );
+                    let outer = match face.exterior {
+                        Exterior::Element(position) => {
+                            let neighbour = &self.elements[position].indexes;
+                            let phi = point.neighbour_phi.as_ref().unwrap();
+                            (
+                                trace(velocity_x, neighbour, phi),
+                                trace(velocity_y, neighbour, phi),
+                            )
+                        }
+                        Exterior::Prescribed => point.prescribed,
+                        Exterior::Free => inner,
+                    };
+                    let (fx, fy) = self.flux.evaluate(inner, outer, face.normal);
+                    for (index, phi) in indexes.iter().zip(point.phi.iter()) {
+                        residual_x[(*index, 0)] -= phi * fx * point.weight;
+                        residual_y[(*index, 0)] -= phi * fy * point.weight;
+                    }
+                }
+            }
+        }
+        return (residual_x, residual_y);
+    }
+
+    /**
+     * Greatest time step for the explicit convection at the given courant number,
+     * from the speeds at volume integration points
+     */
+    pub fn time_step(&self, state: &StokesSolution, courant: f64) -> f64 {
+        let mut max_speed: f64 = 0.0;
+        for operator in self.elements.iter() {
+            for point in operator.volume.iter() {
+                let u = trace(&state.velocity_x, &operator.indexes, &point.phi);
+                let v = trace(&state.velocity_y, &operator.indexes, &point.phi);
+                max_speed = max_speed.max((u * u + v * v).sqrt());
+            }
+        }
+        return courant * self.min_length / max_speed;
+    }
+
+    /**
+     * Advances velocity and pressure by dt
+     */
+    pub fn step(&self, state: &StokesSolution) -> StokesSolution {
+        let (convection_x, convection_y) = self.convection(&state.velocity_x, &state.velocity_y);
+
+        let predictor_x = &self.mass * &state.velocity_x / self.dt + convection_x + &self.source_x
+            - self.bx.tr_mul(&state.pressure);
+        let predictor_y = &self.mass * &state.velocity_y / self.dt + convection_y + &self.source_y
+            - self.by.tr_mul(&state.pressure);
+        let velocity_x = self
+            .predictor
+            .solve(&predictor_x)
+            .expect("Failed to solve velocity predictor");
+        let velocity_y = self
+            .predictor
+            .solve(&predictor_y)
+            .expect("Failed to solve velocity predictor");
+
+        let divergence = (&self.bx * &velocity_x + &self.by * &velocity_y - &self.g) / self.dt;
+        let increment = self.pressure_increment(&divergence);
+
+        return StokesSolution {
+            velocity_x: velocity_x - &self.gradient_x * &increment * self.dt,
+            velocity_y: velocity_y - &self.gradient_y * &increment * self.dt,
+            pressure: &state.pressure + increment,
+        };
+    }
+
+    /**
+     * Advances velocity and pressure by whole steps of dt, until final time or just past it
+     */
+    pub fn solve(&self, state: &StokesSolution, final_time: f64) -> StokesSolution {
+        let steps = (final_time / self.dt - 1.0E-9).ceil() as usize;
+        let mut state = StokesSolution {
+            velocity_x: state.velocity_x.clone(),
+            velocity_y: state.velocity_y.clone(),
+            pressure: state.pressure.clone(),
+        };
+        for _ in 0..steps {
+            state = self.step(&state);
+        }
+        return state;
+    }
+
+    /**
+     * Force of the flow over the given boundary edges, such as drag and lift
+     * over a body: integral of - (- p I + nu (grad(u) + grad(u)^T)) n,
+     * with n the outer normal of the fluid elements
+     */
+    pub fn forces(
+        &self,
+        velocity: &Domain,
+        state: &StokesSolution,
+        edges: &[Rc<Edge>],
+    ) -> (f64, f64) {
+        let (mut force_x, mut force_y) = (0.0, 0.0);
+        for edge in edges.iter() {
+            let element = velocity
+                .adjacency
+                .get(edge)
+                .expect("Not expected edge outside domain");
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let edge_index = element.edge_index(edge).unwrap();
+            let normal = utils::outer_normal(p1, p2, p3, edge_index);
+            let length = utils::edge_length(p1, p2, p3, edge_index);
+
+            let velocity_basis = velocity.element_basis(element);
+            let velocity_indexes = velocity.element_indexes(element);
+            let pressure_basis = self.pressure.element_basis(element);
+            let pressure_indexes = self.pressure.element_indexes(element);
+
+            let rule = gauss_legendre::rule(2 * velocity_basis.degree());
+            for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+                let (x, y) = utils::edge_coordinates(edge_index, *l);
+                let gradient = velocity_basis.physical_gradient(p1, p2, p3, x, y);
+                let slope = |coefficients: &DMatrix<f64>, column: usize| -> f64 {
+                    velocity_indexes
+                        .iter()
+                        .enumerate()
+                        .map(|(i, index)| gradient[(i, column)] * coefficients[(*index, 0)])
+                        .sum()
+                };
+                let (ux_x, ux_y) = (slope(&state.velocity_x, 0), slope(&state.velocity_x, 1));
+                let (uy_x, uy_y) = (slope(&state.velocity_y, 0), slope(&state.velocity_y, 1));
+                let p = trace(
+                    &state.pressure,
+                    &pressure_indexes,
+                    &pressure_basis.evaluate(x, y),
+                );
+
+                let (nx, ny) = (normal[0], normal[1]);
+                let shear = ux_y + uy_x;
+                let traction_x = -p * nx + self.viscosity * (2.0 * ux_x * nx + shear * ny);
+                let traction_y = -p * ny + self.viscosity * (shear * nx + 2.0 * uy_y * ny);
+                force_x -= traction_x * weight * length;
+                force_y -= traction_y * weight * length;
+            }
+        }
+        return (force_x, force_y);
+    }
+
+    fn pressure_increment(&self, divergence: &DMatrix<f64>) -> DMatrix<f64> {
+        let m = divergence.nrows();
+        if !self.zero_mean {
+            return self
+                .projection
+                .solve(divergence)
+                .expect("Failed to solve pressure correction");
+        }
+        let mut bordered = DMatrix::<f64>::zeros(m + 1, 1);
+        bordered.slice_mut((0, 0), (m, 1)).copy_from(divergence);
+        let answer = self
+            .projection
+            .solve(&bordered)
+            .expect("Failed to solve pressure correction");
+        return answer.slice((0, 0), (m, 1)).clone_owned();
+    }
+}
+
+/**
+ * Quadrature data of each element, in the order of Domain::elements,
+ * with the smallest inscribed circle diameter over the greatest basis degree
+ */
+fn element_operators(
+    domain: &Domain,
+    boundary_velocity: BoundaryVelocity,
+    free_edges: &HashSet<Rc<Edge>>,
+) -> (Vec<ElementOperator>, f64) {
+    let positions: HashMap<Rc<TriangleElementL1>, usize> = domain
+        .elements
+        .iter()
+        .enumerate()
+        .map(|(position, element)| (Rc::clone(element), position))
+        .collect();
+
+    let mut min_length = f64::INFINITY;
+    let mut elements: Vec<ElementOperator> = Vec::new();
+    for element in domain.elements.iter() {
+        let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+        let basis = domain.element_basis(element);
+        let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+
+        let rule = dunavant::rule((3 * basis.degree()).clamp(1, dunavant::MAX_DEGREE));
+        let volume = rule
+            .points
+            .iter()
+            .zip(rule.weights.iter())
+            .map(|((x, y), weight)| VolumePoint {
+                weight: weight * determinant,
+                phi: basis.evaluate(*x, *y),
+                gradient: basis.physical_gradient(p1, p2, p3, *x, *y),
+            })
+            .collect();
+
+        let (e1, e2, e3) = element.inner_edges();
+        let mut faces: Vec<Face> = Vec::new();
+        for edge in [e1, e2, e3].iter() {
+            let edge_index = element.edge_index(edge).unwrap();
+            let normal = utils::outer_normal(p1, p2, p3, edge_index);
+            let length = utils::edge_length(p1, p2, p3, edge_index);
+
+            let edge_opp = Rc::new(edge.opposed());
+            let neighbour = domain.adjacency.get(&edge_opp);
+            let exterior = match neighbour {
+                Some(t_right) => Exterior::Element(*positions.get(t_right).unwrap()),
+                None if free_edges.contains(edge) => Exterior::Free,
+                None => Exterior::Prescribed,
+            };
+
+            let degree = match neighbour {
+                Some(t_right) => basis.degree().max(domain.element_basis(t_right).degree()),
+                None => basis.degree(),
+            };
+            let rule = gauss_legendre::rule(3 * degree + 1);
+            let points = rule
+                .points
+                .iter()
+                .zip(rule.weights.iter())
+                .map(|(l, weight)| {
+                    let (x, y) = utils::edge_coordinates(edge_index, *l);
+                    let point = utils::physical_point(p1, p2, p3, x, y);
+                    FacePoint {
+                        weight: weight * length,
+                        phi: basis.evaluate(x, y),
+                        neighbour_phi: neighbour.map(|t_right| {
+                            domain.element_basis(t_right).evaluate_at(
+                                &t_right.p1,
+                                &t_right.p2,
+                                &t_right.p3,
+                                &point,
+                            )
+                        }),
+                        prescribed: match exterior {
+                            Exterior::Prescribed => boundary_velocity(&point),
+                            _ => (0.0, 0.0),
+                        },
+                    }
+                })
+                .collect();
+
+            faces.push(Face {
+                exterior,
+                normal: (normal[0], normal[1]),
+                points,
+            });
+        }
+
+        min_length = min_length.min(
+            advection_problem::inscribed_diameter(p1, p2, p3) / (2.0 * basis.degree() as f64 + 1.0),
+        );
+        elements.push(ElementOperator {
+            indexes: domain.element_indexes(element),
+            volume,
+            faces,
+        });
+    }
+    return (elements, min_length);
+}
+
+/**
+ * Field value at a point, given the basis values there
+ */
+fn trace(coefficients: &DMatrix<f64>, indexes: &[usize], phi: &DVector<f64>) -> f64 {
+    return indexes
+        .iter()
+        .zip(phi.iter())
+        .map(|(index, value)| coefficients[(*index, 0)] * value)
+        .sum();
+}
+
+#[cfg(test)]
+mod incompressible_flow {
+    use super::*;
+    use crate::common::point::Point;
+    use crate::triangle::{basis::dubiner, mesh};
+
+    fn rest(velocity: &Domain, problem: &NavierStokesProblem) -> StokesSolution {
+        return StokesSolution {
+            velocity_x: DMatrix::zeros(velocity.size(), 1),
+            velocity_y: DMatrix::zeros(velocity.size(), 1),
+            pressure: DMatrix::zeros(problem.pressure.size(), 1),
+        };
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Both fluxes are consistent and conservative */
+        let (inner, outer, normal) = ((1.0, 0.5), (0.8, -0.2), (0.6, 0.8));
+        for flux in [ConvectiveFlux::LaxFriedrichs, ConvectiveFlux::Upwind].iter() {
+            let (fx, fy) = flux.evaluate(inner, inner, normal);
+            assert!((fx - 1.0).abs() < 1.0E-14 && (fy - 0.5).abs() < 1.0E-14);
+            let forward = flux.evaluate(inner, outer, normal);
+            let backward = flux.evaluate(outer, inner, (-normal.0, -normal.1));
+            assert!((forward.0 + backward.0).abs() < 1.0E-14);
+            assert!((forward.1 + backward.1).abs() < 1.0E-14);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Uniform stream through a channel with free outflow is kept, at zero pressure */
+        let mut domain = Domain::new_modal(1).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (2.0, 1.0), 4, 2, &mut domain);
+        let problem = NavierStokesProblem::new(
+            0.1,
+            10.0,
+            0.05,
+            ConvectiveFlux::LaxFriedrichs,
+            &domain,
+            &|_: &Point| (1.0, 0.0),
+            &boundary.right,
+        )
+        .unwrap();
+
+        let mut state = rest(&domain, &problem);
+        for element in domain.elements.iter() {
+            let modal = dubiner::linear_to_modal(1, 1.0, 1.0, 1.0);
+            for (index, value) in domain.element_indexes(element).iter().zip(modal.iter()) {
+                state.velocity_x[(*index, 0)] = *value;
+            }
+        }
+        let answer = problem.solve(&state, 0.5);
+        assert!((&answer.velocity_x - &state.velocity_x).norm() < 1.0E-10);
+        assert!(answer.velocity_y.norm() < 1.0E-10);
+        assert!(answer.pressure.norm() < 1.0E-10);
+    }
+
+    /* Flow past a cylinder of unit diameter at Reynolds number 10, at t = 3 */
+    fn cylinder_flow(sectors: usize) -> (Domain, NavierStokesProblem, StokesSolution, f64, f64) {
+        let mut domain = Domain::new_modal(1).unwrap();
+        let boundary = mesh::cylinder((0.0, 0.0), 0.5, 3.0, sectors, sectors, &mut domain);
+        let stream = |point: &Point| {
+            if point.x * point.x + point.y * point.y < 0.5 * 0.5 + 1.0E-9 {
+                return (0.0, 0.0);
+            }
+            return (1.0, 0.0);
+        };
+        let problem = NavierStokesProblem::new(
+            0.1,
+            10.0,
+            0.05,
+            ConvectiveFlux::LaxFriedrichs,
+            &domain,
+            &stream,
+            &boundary.outer.right,
+        )
+        .unwrap();
+
+        let state = problem.solve(&rest(&domain, &problem), 3.0);
+        let (drag, lift) = problem.forces(&domain, &state, &boundary.cylinder);
+        return (domain, problem, state, drag, lift);
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            Flow past a cylinder started from rest, inside a box with the free
+            stream prescribed at the left, top and bottom sides, and free outflow
+            at the right side. The walls of the box only raise the drag coefficient
+            2 Fx / (U^2 D) above the unbounded value 2.846 of Dennis and Chang
+            (JFM, 1970. Vol 42, 471-489). Meshes of 5 and 6 sectors and layers
+            agree within 3% on it, at 4.17 and 4.26.
+            The finer mesh is symmetric, so is the flow: no lift
+        */
+        let (_, _, _, coarse_drag, _) = cylinder_flow(5);
+        let (domain, problem, state, drag, lift) = cylinder_flow(6);
+        assert!(2.0 * coarse_drag > 2.846 && 2.0 * drag > 2.846);
+        assert!((drag - coarse_drag).abs() < 0.03 * drag);
+        assert!(lift.abs() < 1.0E-10);
+
+        /* Stagnation pressure at the front exceeds the one behind the cylinder */
+        let pressure = |point: &Point| -> f64 {
+            for element in domain.elements.iter() {
+                let (x, y) =
+                    utils::reference_coordinates(&element.p1, &element.p2, &element.p3, point);
+                if x >= 0.0 && y >= 0.0 && x + y <= 1.0 {
+                    let basis = problem.pressure.element_basis(element);
+                    let indexes = problem.pressure.element_indexes(element);
+                    return trace(&state.pressure, &indexes, &basis.evaluate(x, y));
+                }
+            }
+            panic!("Not expected point outside domain");
+        };
+        assert!(pressure(&Point::new(-0.6, 0.01)) > pressure(&Point::new(0.6, 0.01)));
+    }
+}
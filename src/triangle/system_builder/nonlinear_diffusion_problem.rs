@@ -0,0 +1,606 @@
+use nalgebra::{DMatrix, DVector, Matrix3x1};
+
+use crate::common::point::Point;
+use crate::triangle::{
+    basis::shape::Basis,
+    boundary_constraint::{RadiationConstraint, STEFAN_BOLTZMANN},
+    element::TriangleElementL1,
+    integrands::utils,
+    quadrature::{dunavant, gauss_legendre},
+    solvers::newton,
+    system_builder::{assembler_utils, domain::Domain},
+};
+
+use std::rc::Rc;
+
+/**
+ * Conductivity k of the flux k grad(u)
+ */
+#[derive(Clone, Copy)]
+pub enum Conductivity<'a> {
+    Constant(f64),
+    /* Temperature dependent: u -> (k(u), dk/du) */
+    Temperature(&'a dyn Fn(f64) -> (f64, f64)),
+    /* p-Laplacian: k = (|grad(u)|^2 + epsilon^2)^((p - 2) / 2) */
+    PLaplacian { p: f64, epsilon: f64 },
+}
+
+impl<'a> Conductivity<'a> {
+    /**
+     * Conductivity at value u and gradient g, with its derivatives
+     * (k, dk/du, dk/dgx, dk/dgy)
+     */
+    pub fn evaluate(&self, u: f64, gx: f64, gy: f64) -> (f64, f64, f64, f64) {
+        match self {
+            Conductivity::Constant(k) => (*k, 0.0, 0.0, 0.0),
+            Conductivity::Temperature(k) => {
+                let (k, dk) = k(u);
+                return (k, dk, 0.0, 0.0);
+            }
+            Conductivity::PLaplacian { p, epsilon } => {
+                let square = gx * gx + gy * gy + epsilon * epsilon;
+                let k = square.powf((p - 2.0) / 2.0);
+                let dk = (p - 2.0) * square.powf((p - 4.0) / 2.0);
+                return (k, 0.0, dk * gx, dk * gy);
+            }
+        }
+    }
+}
+
+/**
+ * Nonlinear diffusion: - div(k grad(u)) = f, with the dirichlet and neumann
+ * constraints of the domain, neumann values being the flux k grad(u) . n.
+ *  The residual follows the interior penalty form of builder::build,
+ *  with k grad(u) in place of grad(u):
+ *      integral of k grad(u) . grad(v) - f v
+ *      - integral over inner edges of {k grad(u) . n} [v] + {k grad(v) . n} [u]
+ *      + sigma max(p, 1)^2 / |e| [u] [v]
+ *  and the same terms at dirichlet edges against the prescribed values.
+ *  Radiation constraints add their heat loss to the boundary flux.
+ *  The penalty is not scaled by k: sigma should follow the conductivity scale.
+ */
+pub struct NonlinearDiffusionProblem<'a> {
+    pub conductivity: Conductivity<'a>,
+    pub sigma: f64,
+    pub source: Option<&'a dyn Fn(&Point) -> f64>,
+}
+
+/**
+ * Solution value and conductivity at a quadrature point of an element,
+ * with the shape functions evaluated there
+ */
+struct Local {
+    phi: DVector<f64>,
+    gradient: DMatrix<f64>,
+    u: f64,
+    g: DVector<f64>,
+    k: f64,
+    /* Derivative of k along each shape function */
+    dk: DVector<f64>,
+}
+
+impl<'a> NonlinearDiffusionProblem<'a> {
+    pub fn new(conductivity: Conductivity<'a>, sigma: f64) -> Self {
+        NonlinearDiffusionProblem {
+            conductivity,
+            sigma,
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: &'a dyn Fn(&Point) -> f64) -> Self {
+        self.source = Some(source);
+        return self;
+    }
+
+    fn local(
+        &self,
+        phi: DVector<f64>,
+        gradient: DMatrix<f64>,
+        coefficients: &DVector<f64>,
+    ) -> Local {
+        let u = phi.dot(coefficients);
+        let g = gradient.transpose() * coefficients;
+        let (k, dk_du, dk_dgx, dk_dgy) = self.conductivity.evaluate(u, g[0], g[1]);
+        let dk = &phi * dk_du + gradient.column(0) * dk_dgx + gradient.column(1) * dk_dgy;
+        return Local {
+            phi,
+            gradient,
+            u,
+            g,
+            k,
+            dk,
+        };
+    }
+
+    pub fn residual(&self, domain: &Domain, solution: &DMatrix<f64>) -> DMatrix<f64> {
+        return self.assemble(domain, solution).0;
+    }
+
+    pub fn jacobian(&self, domain: &Domain, solution: &DMatrix<f64>) -> DMatrix<f64> {
+        return self.assemble(domain, solution).1;
+    }
+
+    /**
+     * Residual and jacobian, both over the same element and edge loops
+     */
+    pub fn assemble(
+        &self,
+        domain: &Domain,
+        solution: &DMatrix<f64>,
+    ) -> (DMatrix<f64>, DMatrix<f64>) {
+        let size = domain.size();
+        let mut residual = DMatrix::<f64>::zeros(size, 1);
+        let mut jacobian = DMatrix::<f64>::zeros(size, size);
+        let coefficients = |element: &Rc<TriangleElementL1>| {
+            let indexes = domain.element_indexes(element);
+            return DVector::from_iterator(
+                indexes.len(),
+                indexes.iter().map(|index| solution[(*index, 0)]),
+            );
+        };
+
+        for element in domain.elements.iter() {
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let indexes = domain.element_indexes(element);
+            let basis = domain.element_basis(element);
+            let c = coefficients(element);
+            let (r, j) = self.volume(p1, p2, p3, &basis, &c);
+            assembler_utils::map(&mut residual, &r, &assembler_utils::column_map(&indexes));
+            assembler_utils::map(
+                &mut jacobian,
+                &j,
+                &assembler_utils::block_map(&indexes, &indexes),
+            );
+
+            let (e1, e2, e3) = element.inner_edges();
+            for edge in [e1, e2, e3].iter() {
+                let t_right = match domain.adjacency.get(&edge.opposed()) {
+                    Some(t_right) => t_right,
+                    None => continue,
+                };
+                let right_indexes = domain.element_indexes(t_right);
+                let right_basis = domain.element_basis(t_right);
+                let edge_index = element.edge_index(edge).unwrap();
+                let (r, left_left, left_right) = self.interface(
+                    element,
+                    &basis,
+                    &c,
+                    t_right,
+                    &right_basis,
+                    &coefficients(t_right),
+                    edge_index,
+                );
+                assembler_utils::map(&mut residual, &r, &assembler_utils::column_map(&indexes));
+                assembler_utils::map(
+                    &mut jacobian,
+                    &left_left,
+                    &assembler_utils::block_map(&indexes, &indexes),
+                );
+                assembler_utils::map(
+                    &mut jacobian,
+                    &left_right,
+                    &assembler_utils::block_map(&indexes, &right_indexes),
+                );
+            }
+        }
+
+        for d_constraint in domain.dirichlet_constraints.iter() {
+            let element = &d_constraint.element;
+            let indexes = domain.element_indexes(element);
+            let basis = domain.element_basis(element);
+            let value = |point| *d_constraint.values.get(point).unwrap_or(&0.0);
+            let values = Matrix3x1::new(value(&element.p1), value(&element.p2), value(&element.p3));
+            let edge_index = element.edge_index(&d_constraint.boundary_edge).unwrap();
+            let (r, j) =
+                self.dirichlet(element, &basis, &coefficients(element), &values, edge_index);
+            assembler_utils::map(&mut residual, &r, &assembler_utils::column_map(&indexes));
+            assembler_utils::map(
+                &mut jacobian,
+                &j,
+                &assembler_utils::block_map(&indexes, &indexes),
+            );
+        }
+
+        for n_constraint in domain.neumann_constraints.iter() {
+            let element = &n_constraint.element;
+            let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+            let indexes = domain.element_indexes(element);
+            let basis = domain.element_basis(element);
+            let value = |point| *n_constraint.values.get(point).unwrap_or(&0.0);
+            let values = Matrix3x1::new(value(p1), value(p2), value(p3));
+            let edge_index = element.edge_index(&n_constraint.boundary_edge).unwrap();
+
+            let length = utils::edge_length(p1, p2, p3, edge_index);
+            let rule = gauss_legendre::rule(basis.degree() + 1);
+            let mut r = DMatrix::<f64>::zeros(basis.size(), 1);
+            for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+                let (x, y) = utils::edge_coordinates(edge_index, *l);
+                let h = (utils::reference_basis(x, y) * values)[(0, 0)];
+                r -= basis.evaluate(x, y) * (h * weight * length);
+            }
+            assembler_utils::map(&mut residual, &r, &assembler_utils::column_map(&indexes));
+        }
+
+        for r_constraint in domain.radiation_constraints.iter() {
+            let element = &r_constraint.element;
+            let indexes = domain.element_indexes(element);
+            let basis = domain.element_basis(element);
+            let (r, j) = radiation(r_constraint, &basis, &coefficients(element));
+            assembler_utils::map(&mut residual, &r, &assembler_utils::column_map(&indexes));
+            assembler_utils::map(
+                &mut jacobian,
+                &j,
+                &assembler_utils::block_map(&indexes, &indexes),
+            );
+        }
+        return (residual, jacobian);
+    }
+
+    fn volume(
+        &self,
+        p1: &Point,
+        p2: &Point,
+        p3: &Point,
+        basis: &Basis,
+        coefficients: &DVector<f64>,
+    ) -> (DMatrix<f64>, DMatrix<f64>) {
+        let determinant = utils::jacobian(p1, p2, p3).determinant().abs();
+        let rule = dunavant::rule((2 * basis.degree() + 2).min(dunavant::MAX_DEGREE));
+        let mut r = DMatrix::<f64>::zeros(basis.size(), 1);
+        let mut j = DMatrix::<f64>::zeros(basis.size(), basis.size());
+        for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+            let local = self.local(
+                basis.evaluate(*x, *y),
+                basis.physical_gradient(p1, p2, p3, *x, *y),
+                coefficients,
+            );
+            let dw = weight * determinant;
+            let flux = &local.gradient * &local.g;
+            r += &flux * (local.k * dw);
+            j += (&local.gradient * local.gradient.transpose() * local.k
+                + &flux * local.dk.transpose())
+                * dw;
+            if let Some(source) = self.source {
+                let f = source(&utils::physical_point(p1, p2, p3, *x, *y));
+                r -= &local.phi * (f * dw);
+            }
+        }
+        return (r, j);
+    }
+
+    /**
+     * Rows of the left element at an inner edge: residual,
+     * and jacobian blocks of the left and right unknowns
+     */
+    #[allow(clippy::too_many_arguments)]
+    fn interface(
+        &self,
+        left: &Rc<TriangleElementL1>,
+        left_basis: &Basis,
+        left_coefficients: &DVector<f64>,
+        right: &Rc<TriangleElementL1>,
+        right_basis: &Basis,
+        right_coefficients: &DVector<f64>,
+        edge_index: usize,
+    ) -> (DMatrix<f64>, DMatrix<f64>, DMatrix<f64>) {
+        let (p1, p2, p3) = (&left.p1, &left.p2, &left.p3);
+        let (p4, p5, p6) = (&right.p1, &right.p2, &right.p3);
+        let length = utils::edge_length(p1, p2, p3, edge_index);
+        let normal = utils::outer_normal(p1, p2, p3, edge_index);
+        let normal = DVector::from_vec(vec![normal[0], normal[1]]);
+        let degree = left_basis.degree().max(right_basis.degree());
+        let penalty = self.sigma * (degree.max(1) as f64).powi(2) / length;
+
+        let rule = gauss_legendre::rule((3 * degree).max(2));
+        let mut r = DMatrix::<f64>::zeros(left_basis.size(), 1);
+        let mut left_left = DMatrix::<f64>::zeros(left_basis.size(), left_basis.size());
+        let mut left_right = DMatrix::<f64>::zeros(left_basis.size(), right_basis.size());
+        for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+            let (x, y) = utils::edge_coordinates(edge_index, *l);
+            let point = utils::physical_point(p1, p2, p3, x, y);
+            let inner = self.local(
+                left_basis.evaluate(x, y),
+                left_basis.physical_gradient(p1, p2, p3, x, y),
+                left_coefficients,
+            );
+            let outer = self.local(
+                right_basis.evaluate_at(p4, p5, p6, &point),
+                right_basis.physical_gradient_at(p4, p5, p6, &point),
+                right_coefficients,
+            );
+            let dw = weight * length;
+
+            let (inner_flux, inner_derivative) = normal_flux(&inner, &normal);
+            let (outer_flux, outer_derivative) = normal_flux(&outer, &normal);
+            let jump = inner.u - outer.u;
+            let test_flux = &inner.gradient * &normal;
+
+            r += (&inner.phi * (-0.5 * (inner_flux + outer_flux) + penalty * jump)
+                - &test_flux * (0.5 * inner.k * jump))
+                * dw;
+            left_left += (&inner.phi * (&inner.phi * penalty - inner_derivative * 0.5).transpose()
+                - &test_flux * (&inner.phi * inner.k + &inner.dk * jump).transpose() * 0.5)
+                * dw;
+            left_right +=
+                (&inner.phi * (&outer.phi * penalty + outer_derivative * 0.5).transpose() * -1.0
+                    + &test_flux * outer.phi.transpose() * (0.5 * inner.k))
+                    * dw;
+        }
+        return (r, left_left, left_right);
+    }
+
+    /**
+     * Weakly imposed dirichlet values g at a boundary edge:
+     * residual and jacobian of the element
+     */
+    fn dirichlet(
+        &self,
+        element: &Rc<TriangleElementL1>,
+        basis: &Basis,
+        coefficients: &DVector<f64>,
+        values: &Matrix3x1<f64>,
+        edge_index: usize,
+    ) -> (DMatrix<f64>, DMatrix<f64>) {
+        let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+        let length = utils::edge_length(p1, p2, p3, edge_index);
+        let normal = utils::outer_normal(p1, p2, p3, edge_index);
+        let normal = DVector::from_vec(vec![normal[0], normal[1]]);
+        let penalty = self.sigma * (basis.degree().max(1) as f64).powi(2) / length;
+
+        let rule = gauss_legendre::rule((3 * basis.degree()).max(2));
+        let mut r = DMatrix::<f64>::zeros(basis.size(), 1);
+        let mut j = DMatrix::<f64>::zeros(basis.size(), basis.size());
+        for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+            let (x, y) = utils::edge_coordinates(edge_index, *l);
+            let local = self.local(
+                basis.evaluate(x, y),
+                basis.physical_gradient(p1, p2, p3, x, y),
+                coefficients,
+            );
+            let dw = weight * length;
+
+            let (flux, derivative) = normal_flux(&local, &normal);
+            let jump = local.u - (utils::reference_basis(x, y) * values)[(0, 0)];
+            let test_flux = &local.gradient * &normal;
+
+            r += (&local.phi * (penalty * jump - flux) - &test_flux * (local.k * jump)) * dw;
+            j += (&local.phi * (&local.phi * penalty - derivative).transpose()
+                - &test_flux * (&local.phi * local.k + &local.dk * jump).transpose())
+                * dw;
+        }
+        return (r, j);
+    }
+
+    /**
+     * Solution with constant conductivity k, as initial guess of the newton iterations:
+     * a single newton step from zero, since the problem is then linear
+     */
+    pub fn linear_guess(&self, domain: &Domain, conductivity: f64) -> DMatrix<f64> {
+        let linear = NonlinearDiffusionProblem {
+            conductivity: Conductivity::Constant(conductivity),
+            sigma: self.sigma,
+            source: self.source,
+        };
+        let (residual, jacobian) = linear.assemble(domain, &DMatrix::zeros(domain.size(), 1));
+        return jacobian
+            .lu()
+            .solve(&(-residual))
+            .expect("Not expected singular linear diffusion system");
+    }
+
+    pub fn solve(
+        &self,
+        domain: &Domain,
+        initial: &DMatrix<f64>,
+        options: &newton::Options,
+    ) -> (DMatrix<f64>, newton::Report) {
+        return newton::solve(
+            initial,
+            &|u| self.residual(domain, u),
+            &|u| self.jacobian(domain, u),
+            options,
+        );
+    }
+}
+
+/**
+ * Radiative loss at a boundary edge: residual of
+ *  integral of emissivity * STEFAN_BOLTZMANN * (u^4 - u_amb^4) v
+ *  and its jacobian, u_amb interpolated from the edge values
+ */
+fn radiation(
+    constraint: &RadiationConstraint,
+    basis: &Basis,
+    coefficients: &DVector<f64>,
+) -> (DMatrix<f64>, DMatrix<f64>) {
+    let element = &constraint.element;
+    let (p1, p2, p3) = (&element.p1, &element.p2, &element.p3);
+    let ambient = |point| *constraint.ambient.get(point).unwrap_or(&0.0);
+    let ambient = Matrix3x1::new(ambient(p1), ambient(p2), ambient(p3));
+    let edge_index = element.edge_index(&constraint.boundary_edge).unwrap();
+    let length = utils::edge_length(p1, p2, p3, edge_index);
+    let coefficient = constraint.emissivity * STEFAN_BOLTZMANN;
+
+    let rule = gauss_legendre::rule(5 * basis.degree());
+    let mut r = DMatrix::<f64>::zeros(basis.size(), 1);
+    let mut j = DMatrix::<f64>::zeros(basis.size(), basis.size());
+    for (l, weight) in rule.points.iter().zip(rule.weights.iter()) {
+        let (x, y) = utils::edge_coordinates(edge_index, *l);
+        let phi = basis.evaluate(x, y);
+        let u = phi.dot(coefficients);
+        let u_amb = (utils::reference_basis(x, y) * ambient)[(0, 0)];
+        let dw = coefficient * weight * length;
+        r += &phi * ((u.powi(4) - u_amb.powi(4)) * dw);
+        j += &phi * phi.transpose() * (4.0 * u.powi(3) * dw);
+    }
+    return (r, j);
+}
+
+/**
+ * Normal flux k grad(u) . n and its derivative along each shape function
+ */
+fn normal_flux(local: &Local, normal: &DVector<f64>) -> (f64, DVector<f64>) {
+    let gn = local.g.dot(normal);
+    let derivative = &local.gradient * normal * local.k + &local.dk * gn;
+    return (local.k * gn, derivative);
+}
+
+#[cfg(test)]
+mod newton_raphson {
+    use super::*;
+    use crate::triangle::{basis::dubiner, mesh, system_builder::builder};
+
+    fn square(degree: usize, n: usize) -> Domain {
+        let mut domain = Domain::new_modal(degree).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), n, n, &mut domain);
+        for edge in boundary.left.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+        for edge in boundary.right.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![1.0, 1.0]);
+        }
+        for edge in boundary.bottom.iter() {
+            domain.insert_neumann_constraint(edge, vec![0.5, -0.5]);
+        }
+        return domain;
+    }
+
+    /**
+     * Greatest difference to the exact solution at the vertices of every element
+     */
+    fn vertex_error(
+        domain: &Domain,
+        solution: &DMatrix<f64>,
+        exact: &dyn Fn(&Point) -> f64,
+    ) -> f64 {
+        let mut error: f64 = 0.0;
+        for element in domain.elements.iter() {
+            let indexes = domain.element_indexes(element);
+            let local = DVector::from_iterator(
+                indexes.len(),
+                indexes.iter().map(|index| solution[(*index, 0)]),
+            );
+            let (u1, u2, u3) =
+                dubiner::vertex_values(domain.element_basis(element).degree(), &local);
+            error = error
+                .max((u1 - exact(&element.p1)).abs())
+                .max((u2 - exact(&element.p2)).abs())
+                .max((u3 - exact(&element.p3)).abs());
+        }
+        return error;
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Constant conductivity: residual A u - b and jacobian A of builder::build */
+        let domain = square(2, 2);
+        let (system_matrix, extern_matrix) = builder::build(10.0, &domain);
+        let problem = NonlinearDiffusionProblem::new(Conductivity::Constant(1.0), 10.0);
+        let u = DMatrix::from_fn(domain.size(), 1, |i, _| (i as f64).sin());
+        let (residual, jacobian) = problem.assemble(&domain, &u);
+        assert!((&jacobian - &system_matrix).norm() < 1.0E-12 * system_matrix.norm());
+        assert!((residual - (&system_matrix * &u - extern_matrix)).norm() < 1.0E-11);
+    }
+
+    #[test]
+    fn sample_2() {
+        /* Jacobian against central differences of the residual */
+        let domain = square(2, 2);
+        let source = |point: &Point| point.x * point.y;
+        let temperature = |u: f64| (1.0 + u * u, 2.0 * u);
+        for conductivity in [
+            Conductivity::Temperature(&temperature),
+            Conductivity::PLaplacian {
+                p: 3.5,
+                epsilon: 0.1,
+            },
+        ]
+        .iter()
+        {
+            let problem = NonlinearDiffusionProblem::new(*conductivity, 10.0).with_source(&source);
+            let u = DMatrix::from_fn(domain.size(), 1, |i, _| (i as f64).sin());
+            let jacobian = problem.jacobian(&domain, &u);
+            let h = 1.0E-6;
+            for j in 0..domain.size() {
+                let (mut forward, mut backward) = (u.clone(), u.clone());
+                forward[(j, 0)] += h;
+                backward[(j, 0)] -= h;
+                let difference = (problem.residual(&domain, &forward)
+                    - problem.residual(&domain, &backward))
+                    / (2.0 * h);
+                assert!((difference - jacobian.column(j)).norm() < 1.0E-6 * jacobian.norm());
+            }
+        }
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            k(u) = 1 + u^2 between u = 0 at left and u = 1 at right, insulated
+            top and bottom: the Kirchhoff transform u + u^3 / 3 = 4 x / 3 is linear.
+            Newton converges quadratically from the solution with k = 1
+        */
+        let mut domain = Domain::new_modal(2).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 1.0), 4, 4, &mut domain);
+        for edge in boundary.left.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+        for edge in boundary.right.iter() {
+            domain.insert_dirichlet_constraint(edge, vec![1.0, 1.0]);
+        }
+
+        let temperature = |u: f64| (1.0 + u * u, 2.0 * u);
+        let problem = NonlinearDiffusionProblem::new(Conductivity::Temperature(&temperature), 20.0);
+        let guess = problem.linear_guess(&domain, 1.0);
+        let (solution, report) = problem.solve(&domain, &guess, &newton::Options::default());
+        assert!(report.converged);
+        assert!(report.iterations.len() <= 6);
+        assert!(report
+            .iterations
+            .iter()
+            .all(|iteration| iteration.damping == 1.0));
+
+        let exact = |point: &Point| {
+            let mut u = point.x;
+            for _ in 0..50 {
+                u -= (u + u.powi(3) / 3.0 - 4.0 * point.x / 3.0) / (1.0 + u * u);
+            }
+            u
+        };
+        assert!(vertex_error(&domain, &guess, &exact) > 0.05);
+        assert!(vertex_error(&domain, &solution, &exact) < 1.0E-3);
+    }
+
+    #[test]
+    fn sample_4() {
+        /*
+            p-Laplacian, p = 3, with unit source on a strip vanishing at both ends:
+            |u'| u' = 1 / 2 - x, so that the peak is u(1 / 2) = (2 / 3) (1 / 2)^(3 / 2)
+        */
+        let mut domain = Domain::new_modal(2).unwrap();
+        let boundary = mesh::rectangle((0.0, 0.0), (1.0, 0.25), 8, 1, &mut domain);
+        for edge in boundary.left.iter().chain(boundary.right.iter()) {
+            domain.insert_dirichlet_constraint(edge, vec![0.0, 0.0]);
+        }
+
+        let source = |_: &Point| 1.0;
+        let problem = NonlinearDiffusionProblem::new(
+            Conductivity::PLaplacian {
+                p: 3.0,
+                epsilon: 1.0E-3,
+            },
+            10.0,
+        )
+        .with_source(&source);
+        let guess = problem.linear_guess(&domain, 1.0);
+        let (solution, report) = problem.solve(&domain, &guess, &newton::Options::default());
+        assert!(report.converged);
+
+        let exact = |point: &Point| {
+            let distance = (0.5 - point.x).abs();
+            (2.0 / 3.0) * (0.5_f64.powf(1.5) - distance.powf(1.5))
+        };
+        assert!(vertex_error(&domain, &solution, &exact) < 0.02 * exact(&Point::new(0.5, 0.0)));
+    }
+}
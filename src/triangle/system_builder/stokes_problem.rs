@@ -0,0 +1,374 @@
+use nalgebra::DMatrix;
+
+use crate::common::{edge::Edge, error::Error, point::Point};
+use crate::triangle::{
+    boundary_constraint::BoundaryConstraint,
+    integrands::utils,
+    quadrature::dunavant,
+    system_builder::{
+        domain::Domain,
+        external::{dirichlet, prescribed_velocity},
+        internal::{divergence, flux_artificial, flux_natural, jump_penalty, mass},
+    },
+};
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/**
+ * Velocity field g(x, y) prescribed at boundary edges
+ */
+pub type BoundaryVelocity<'a> = &'a dyn Fn(&Point) -> (f64, f64);
+
+/**
+ * Steady Stokes flow: - nu laplacian(u) + grad(p) = 0, div(u) = 0,
+ * with velocity prescribed at every boundary edge.
+ *  Each velocity component takes the interior penalty system of the velocity
+ *  domain. Pressure lives on the same elements, one degree below velocity,
+ *  and its mean is fixed by a lagrange multiplier. The saddle point system is
+ *      [ nu A    0    Bx^T  0 ] [ ux ]   [ nu fx ]
+ *      [  0    nu A   By^T  0 ] [ uy ] = [ nu fy ]
+ *      [ Bx     By     0    m ] [ p  ]   [   g   ]
+ *      [  0      0    m^T   0 ] [ l  ]   [   0   ]
+ */
+pub struct StokesProblem {
+    pub pressure: Domain,
+    pub system_matrix: DMatrix<f64>,
+    pub extern_matrix: DMatrix<f64>,
+}
+
+/**
+ * Coefficients of the velocity components on the velocity domain
+ * and of the pressure on the pressure domain
+ */
+pub struct StokesSolution {
+    pub velocity_x: DMatrix<f64>,
+    pub velocity_y: DMatrix<f64>,
+    pub pressure: DMatrix<f64>,
+}
+
+impl StokesProblem {
+    /**
+     * Dirichlet constraints already set on the velocity domain are not used:
+     * the boundary velocity applies at every boundary edge.
+     * Fails if a velocity element has degree 0, which leaves no pressure space
+     */
+    pub fn new(
+        viscosity: f64,
+        sigma: f64,
+        velocity: &Domain,
+        boundary_velocity: BoundaryVelocity,
+    ) -> Result<Self, Error> {
+        let pressure = pressure_domain(velocity)?;
+        let constraints = boundary_constraints(velocity, &HashSet::new());
+        let (stiffness, fx, fy) =
+            viscous_operator(sigma, velocity, &constraints, boundary_velocity);
+        let (bx, by, g) = divergence_operator(velocity, &pressure, &constraints, boundary_velocity);
+
+        let n = velocity.size();
+        let m = pressure.size();
+        let mean = pressure_mean(&pressure);
+
+        let size = 2 * n + m + 1;
+        let mut system_matrix = DMatrix::<f64>::zeros(size, size);
+        system_matrix
+            .slice_mut((0, 0), (n, n))
+            .copy_from(&(&stiffness * viscosity));
+        system_matrix
+            .slice_mut((n, n), (n, n))
+            .copy_from(&(&stiffness * viscosity));
+        system_matrix.slice_mut((2 * n, 0), (m, n)).copy_from(&bx);
+        system_matrix.slice_mut((2 * n, n), (m, n)).copy_from(&by);
+        system_matrix
+            .slice_mut((0, 2 * n), (n, m))
+            .copy_from(&bx.transpose());
+        system_matrix
+            .slice_mut((n, 2 * n), (n, m))
+            .copy_from(&by.transpose());
+        system_matrix
+            .slice_mut((2 * n, 2 * n + m), (m, 1))
+            .copy_from(&mean);
+        system_matrix
+            .slice_mut((2 * n + m, 2 * n), (1, m))
+            .copy_from(&mean.transpose());
+
+        let mut extern_matrix = DMatrix::<f64>::zeros(size, 1);
+        extern_matrix
+            .slice_mut((0, 0), (n, 1))
+            .copy_from(&(fx * viscosity));
+        extern_matrix
+            .slice_mut((n, 0), (n, 1))
+            .copy_from(&(fy * viscosity));
+        extern_matrix.slice_mut((2 * n, 0), (m, 1)).copy_from(&g);
+
+        return Ok(StokesProblem {
+            pressure,
+            system_matrix,
+            extern_matrix,
+        });
+    }
+
+    pub fn solve(&self) -> StokesSolution {
+        let m = self.pressure.size();
+        let n = (self.system_matrix.nrows() - m - 1) / 2;
+        let answer = self
+            .system_matrix
+            .clone()
+            .lu()
+            .solve(&self.extern_matrix)
+            .expect("Failed to solve stokes system");
+
+        return StokesSolution {
+            velocity_x: answer.slice((0, 0), (n, 1)).clone_owned(),
+            velocity_y: answer.slice((n, 0), (n, 1)).clone_owned(),
+            pressure: answer.slice((2 * n, 0), (m, 1)).clone_owned(),
+        };
+    }
+}
+
+/**
+ * Domain of the pressure: same elements as velocity, one degree below.
+ * Fails for velocity elements of degree 0
+ */
+pub(crate) fn pressure_domain(velocity: &Domain) -> Result<Domain, Error> {
+    let mut pressure = Domain::new_empty();
+    for element in velocity.elements.iter() {
+        let degree = velocity.element_basis(element).degree();
+        if degree == 0 {
+            return Err(Error::UnsupportedDegree);
+        }
+        pressure.insert_element_with_degree(element, degree - 1)?;
+    }
+    return Ok(pressure);
+}
+
+/**
+ * Interior penalty operator A of each velocity component,
+ * with the dirichlet right hand sides fx and fy of the boundary velocity
+ * at the edges of the constraints
+ */
+pub(crate) fn viscous_operator(
+    sigma: f64,
+    velocity: &Domain,
+    constraints: &[Rc<BoundaryConstraint>],
+    boundary_velocity: BoundaryVelocity,
+) -> (DMatrix<f64>, DMatrix<f64>, DMatrix<f64>) {
+    let n = velocity.size();
+    let mut stiffness = DMatrix::<f64>::zeros(n, n);
+    mass::build(&mut stiffness, velocity).expect("Failed to build mass matrix");
+    flux_natural::build(&mut stiffness, velocity).expect("Failed to set natural flux");
+    flux_artificial::build(&mut stiffness, velocity)
+        .expect("Failed to set artificial anti-symmetric flux");
+    jump_penalty::build(&mut stiffness, sigma, velocity)
+        .expect("Failed to set inter element penalties");
+    dirichlet::build_matrix(&mut stiffness, sigma, velocity, constraints)
+        .expect("Failed to set dirichlet constraints");
+
+    let mut fx = DMatrix::<f64>::zeros(n, 1);
+    let mut fy = DMatrix::<f64>::zeros(n, 1);
+    dirichlet::build_extern_function(&mut fx, sigma, velocity, constraints, &|point| {
+        boundary_velocity(point).0
+    })
+    .expect("Failed to set dirichlet constraints");
+    dirichlet::build_extern_function(&mut fy, sigma, velocity, constraints, &|point| {
+        boundary_velocity(point).1
+    })
+    .expect("Failed to set dirichlet constraints");
+    return (stiffness, fx, fy);
+}
+
+/**
+ * Divergence blocks Bx and By, with the prescribed normal velocity g
+ */
+pub(crate) fn divergence_operator(
+    velocity: &Domain,
+    pressure: &Domain,
+    constraints: &[Rc<BoundaryConstraint>],
+    boundary_velocity: BoundaryVelocity,
+) -> (DMatrix<f64>, DMatrix<f64>, DMatrix<f64>) {
+    let (n, m) = (velocity.size(), pressure.size());
+    let mut bx = DMatrix::<f64>::zeros(m, n);
+    let mut by = DMatrix::<f64>::zeros(m, n);
+    let mut g = DMatrix::<f64>::zeros(m, 1);
+    divergence::build(&mut bx, &mut by, velocity, pressure)
+        .expect("Failed to build divergence matrix");
+    prescribed_velocity::build(
+        &mut bx,
+        &mut by,
+        &mut g,
+        velocity,
+        pressure,
+        constraints,
+        boundary_velocity,
+    )
+    .expect("Failed to set boundary velocity");
+    return (bx, by, g);
+}
+
+/**
+ * Dirichlet constraints of the velocity at every boundary edge, but the ones left free.
+ * They carry no values: the boundary velocity is integrated at the edge quadrature points
+ */
+pub(crate) fn boundary_constraints(
+    domain: &Domain,
+    free_edges: &HashSet<Rc<Edge>>,
+) -> Vec<Rc<BoundaryConstraint>> {
+    let mut constraints: Vec<Rc<BoundaryConstraint>> = Vec::new();
+    for element in domain.elements.iter() {
+        let (e1, e2, e3) = element.inner_edges();
+        for edge in [e1, e2, e3].iter() {
+            if domain.adjacency.contains_key(&edge.opposed()) || free_edges.contains(edge) {
+                continue;
+            }
+            constraints.push(Rc::new(BoundaryConstraint {
+                element: Rc::clone(element),
+                boundary_edge: Rc::clone(edge),
+                values: HashMap::new(),
+            }));
+        }
+    }
+    return constraints;
+}
+
+/**
+ * Integral of each pressure basis function, as a Px1 matrix
+ */
+pub(crate) fn pressure_mean(pressure: &Domain) -> DMatrix<f64> {
+    let mut mean = DMatrix::<f64>::zeros(pressure.size(), 1);
+    for element in pressure.elements.iter() {
+        let basis = pressure.element_basis(element);
+        let determinant = utils::jacobian(&element.p1, &element.p2, &element.p3)
+            .determinant()
+            .abs();
+        let rule = dunavant::rule(basis.degree());
+        for ((x, y), weight) in rule.points.iter().zip(rule.weights.iter()) {
+            let phi = basis.evaluate(*x, *y);
+            for (index, value) in pressure.element_indexes(element).iter().zip(phi.iter()) {
+                mean[(*index, 0)] += value * weight * determinant;
+            }
+        }
+    }
+    return mean;
+}
+
+#[cfg(test)]
+mod creeping_flow {
+    use super::*;
+    use crate::triangle::mesh;
+
+    /* Field at the point, given the coefficients on the domain */
+    fn evaluate(domain: &Domain, coefficients: &DMatrix<f64>, point: &Point) -> f64 {
+        for element in domain.elements.iter() {
+            let (x, y) = utils::reference_coordinates(&element.p1, &element.p2, &element.p3, point);
+            if x >= -1.0E-12 && y >= -1.0E-12 && x + y <= 1.0 + 1.0E-12 {
+                return domain
+                    .element_basis(element)
+                    .evaluate(x, y)
+                    .iter()
+                    .zip(domain.element_indexes(element).iter())
+                    .map(|(phi, index)| phi * coefficients[(*index, 0)])
+                    .sum();
+            }
+        }
+        panic!("Not expected point outside domain");
+    }
+
+    fn sample_points() -> Vec<Point> {
+        let mut points = Vec::new();
+        for i in 1..5 {
+            for j in 1..5 {
+                points.push(Point::new(0.2 * i as f64 + 0.01, 0.2 * j as f64 - 0.01));
+            }
+        }
+        return points;
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Couette flow u = (y, 0) with constant pressure is exact */
+        let mut domain = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 3, 3, &mut domain);
+        let problem =
+            StokesProblem::new(1.0, 10.0, &domain, &|point: &Point| (point.y, 0.0)).unwrap();
+        let solution = problem.solve();
+
+        for point in sample_points().iter() {
+            assert!((evaluate(&domain, &solution.velocity_x, point) - point.y).abs() < 1.0E-9);
+            assert!(evaluate(&domain, &solution.velocity_y, point).abs() < 1.0E-9);
+            assert!(evaluate(&problem.pressure, &solution.pressure, point).abs() < 1.0E-9);
+        }
+    }
+
+    #[test]
+    fn sample_2() {
+        /*
+            Poiseuille flow u = (y (1 - y), 0), p = 2 nu (1/2 - x).
+            Boundary values are integrated at the edge quadrature points,
+            and the flow lies in the P2 - P1 spaces, so it is recovered exactly
+        */
+        let viscosity = 0.5;
+        let mut domain = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 4, 4, &mut domain);
+        let problem = StokesProblem::new(viscosity, 10.0, &domain, &|point: &Point| {
+            (point.y * (1.0 - point.y), 0.0)
+        })
+        .unwrap();
+        let solution = problem.solve();
+
+        for point in sample_points().iter() {
+            let u = point.y * (1.0 - point.y);
+            let p = 2.0 * viscosity * (0.5 - point.x);
+            assert!((evaluate(&domain, &solution.velocity_x, point) - u).abs() < 1.0E-8);
+            assert!(evaluate(&domain, &solution.velocity_y, point).abs() < 1.0E-8);
+            assert!((evaluate(&problem.pressure, &solution.pressure, point) - p).abs() < 1.0E-8);
+        }
+    }
+
+    #[test]
+    fn sample_3() {
+        /*
+            Regularized lid driven cavity: the lid u = 16 x^2 (1 - x)^2 drags the fluid
+            to the right, which comes back along the bottom, rising at the left wall
+            and sinking at the right one. The lid vanishes at the corners, away from
+            the singularity of the constant one
+        */
+        let mut domain = Domain::new_modal(2).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 4, 4, &mut domain);
+        let lid = |point: &Point| {
+            if point.y > 1.0 - 1.0E-12 {
+                return (16.0 * (point.x * (1.0 - point.x)).powi(2), 0.0);
+            }
+            return (0.0, 0.0);
+        };
+        let problem = StokesProblem::new(1.0, 10.0, &domain, &lid).unwrap();
+        let solution = problem.solve();
+
+        let u = |x: f64, y: f64| evaluate(&domain, &solution.velocity_x, &Point::new(x, y));
+        let v = |x: f64, y: f64| evaluate(&domain, &solution.velocity_y, &Point::new(x, y));
+        assert!(u(0.5, 0.9) > 0.0);
+        assert!(u(0.5, 0.3) < 0.0);
+        assert!(v(0.2, 0.6) > 0.0);
+        assert!(v(0.8, 0.6) < 0.0);
+
+        /* Creeping flow is nearly symmetric about x = 1/2 */
+        for (x, y) in [(0.2, 0.7), (0.3, 0.4), (0.25, 0.2)].iter() {
+            assert!((u(*x, *y) - u(1.0 - x, *y)).abs() < 2.0E-2);
+            assert!((v(*x, *y) + v(1.0 - x, *y)).abs() < 2.0E-2);
+        }
+
+        /* Net flux through the vertical center line vanishes */
+        let flux: f64 = (0..20)
+            .map(|j| u(0.5, (j as f64 + 0.5) / 20.0) / 20.0)
+            .sum();
+        assert!(flux.abs() < 1.0E-2);
+    }
+
+    #[test]
+    fn sample_4() {
+        /* Velocity of degree 0 leaves no pressure space */
+        let mut domain = Domain::new_modal(0).unwrap();
+        mesh::rectangle((0.0, 0.0), (1.0, 1.0), 2, 2, &mut domain);
+        let problem = StokesProblem::new(1.0, 10.0, &domain, &|_: &Point| (0.0, 0.0));
+        assert_eq!(problem.err(), Some(Error::UnsupportedDegree));
+    }
+}
@@ -0,0 +1,81 @@
+use nalgebra::DMatrix;
+
+/**
+ * Strong stability preserving Runge-Kutta schemes, written as convex
+ * combinations of forward euler steps
+ *
+ *  Reference:
+ *      Gottlieb, S.; Shu, C.W. Total variation diminishing Runge-Kutta schemes.
+ *      Mathematics of Computation, 1998. Vol 67, 73-85.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scheme {
+    ForwardEuler,
+    SspRk2,
+    SspRk3,
+}
+
+/**
+ * Advances du/dt = rhs(u) by dt
+ */
+pub fn step(
+    scheme: Scheme,
+    u: &DMatrix<f64>,
+    dt: f64,
+    rhs: &dyn Fn(&DMatrix<f64>) -> DMatrix<f64>,
+) -> DMatrix<f64> {
+    return step_limited(scheme, u, dt, rhs, &|v: &DMatrix<f64>| v.clone());
+}
+
+/**
+ * Advances du/dt = rhs(u) by dt, applying the limiter after each stage
+ */
+pub fn step_limited(
+    scheme: Scheme,
+    u: &DMatrix<f64>,
+    dt: f64,
+    rhs: &dyn Fn(&DMatrix<f64>) -> DMatrix<f64>,
+    limiter: &dyn Fn(&DMatrix<f64>) -> DMatrix<f64>,
+) -> DMatrix<f64> {
+    let euler = |v: &DMatrix<f64>| -> DMatrix<f64> { v + rhs(v) * dt };
+    match scheme {
+        Scheme::ForwardEuler => limiter(&euler(u)),
+        Scheme::SspRk2 => {
+            let u1 = limiter(&euler(u));
+            return limiter(&((u + euler(&u1)) * 0.5));
+        }
+        Scheme::SspRk3 => {
+            let u1 = limiter(&euler(u));
+            let u2 = limiter(&(u * 0.75 + euler(&u1) * 0.25));
+            return limiter(&(u / 3.0 + euler(&u2) * (2.0 / 3.0)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod order {
+    use super::*;
+
+    /* Error at t = 1 of du/dt = -u, u(0) = 1 */
+    fn error(scheme: Scheme, steps: usize) -> f64 {
+        let dt = 1.0 / steps as f64;
+        let mut u = DMatrix::<f64>::from_element(1, 1, 1.0);
+        for _ in 0..steps {
+            u = step(scheme, &u, dt, &|v: &DMatrix<f64>| -v);
+        }
+        return (u[(0, 0)] - (-1.0_f64).exp()).abs();
+    }
+
+    #[test]
+    fn sample_1() {
+        /* Halving the step divides the error by 2^order */
+        for (scheme, order) in [
+            (Scheme::ForwardEuler, 1),
+            (Scheme::SspRk2, 2),
+            (Scheme::SspRk3, 3),
+        ] {
+            let rate = (error(scheme, 50) / error(scheme, 100)).log2();
+            assert!((rate - order as f64).abs() < 0.1);
+        }
+    }
+}